@@ -0,0 +1,404 @@
+//! Texture atlases: sprite sheets cut into frames
+//!
+//! An atlas is described by a file next to its image, `<image>.atlas.ron`, which
+//! either slices the image into a grid of equal cells or points at a manifest
+//! written by a texture packer. Manifests can be JSON or RON and list each frame's
+//! name and rectangle, in the hash or array layout most packers export. Sprite
+//! assets pick a frame by index or name, and the frame's rectangle becomes the
+//! sprite's texture rectangle.
+
+use bevy::prelude::*;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::components::SpriteFrame;
+
+/// File name ending of atlas files, added to the image's file name
+pub const ATLAS_SUFFIX: &str = ".atlas.ron";
+
+/// How an atlas image is cut into frames
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum AtlasSlicing {
+    Grid(AtlasGrid),
+    /// Path of a texture packer manifest, relative to the assets folder
+    Manifest(String),
+}
+
+impl Default for AtlasSlicing {
+    fn default() -> Self {
+        AtlasSlicing::Grid(AtlasGrid::default())
+    }
+}
+
+/// Grid of equal cells, read left to right and top to bottom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AtlasGrid {
+    pub cell_size: [u32; 2],
+    /// Gap between neighbouring cells
+    pub padding: [u32; 2],
+    /// Margin before the first column and row
+    pub offset: [u32; 2],
+    /// Columns to cut, or 0 for as many as fit in the image
+    pub columns: u32,
+    /// Rows to cut, or 0 for as many as fit in the image
+    pub rows: u32,
+}
+
+impl Default for AtlasGrid {
+    fn default() -> Self {
+        Self {
+            cell_size: [32, 32],
+            padding: [0, 0],
+            offset: [0, 0],
+            columns: 0,
+            rows: 0,
+        }
+    }
+}
+
+impl AtlasGrid {
+    /// Cells of an image of the given size, named by their index
+    pub fn frames(&self, image_size: UVec2) -> Vec<AtlasFrame> {
+        let count = self.cell_count(image_size);
+        (0..count.x.checked_mul(count.y).unwrap_or(0))
+            .filter_map(|index| {
+                Some(AtlasFrame {
+                    name: index.to_string(),
+                    rect: self.cell_rect(index as usize, image_size)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Rectangle of the cell at an index, without working out every other cell
+    pub fn cell_rect(&self, index: usize, image_size: UVec2) -> Option<URect> {
+        let count = self.cell_count(image_size);
+        if index >= count.x.checked_mul(count.y)? as usize {
+            return None;
+        }
+        let index = index as u32;
+        let cell = UVec2::from(self.cell_size);
+        let padding = UVec2::from(self.padding);
+        let offset = UVec2::from(self.offset);
+
+        let cell_start = |offset: u32, position: u32, cell: u32, padding: u32| {
+            offset.checked_add(position.checked_mul(cell.checked_add(padding)?)?)
+        };
+        let min = UVec2::new(
+            cell_start(offset.x, index % count.x, cell.x, padding.x)?,
+            cell_start(offset.y, index / count.x, cell.y, padding.y)?,
+        );
+        let max = UVec2::new(min.x.checked_add(cell.x)?, min.y.checked_add(cell.y)?);
+        Some(URect::from_corners(min, max))
+    }
+
+    /// Columns and rows of cells cut from an image of the given size
+    ///
+    /// Explicit column and row counts are limited to the cells that fit, so no
+    /// cell reaches outside the image.
+    fn cell_count(&self, image_size: UVec2) -> UVec2 {
+        let cell = UVec2::from(self.cell_size);
+        if cell.x == 0 || cell.y == 0 {
+            return UVec2::ZERO;
+        }
+        let padding = UVec2::from(self.padding);
+        let offset = UVec2::from(self.offset);
+
+        // Cells that fit: the first takes a cell, every further one a cell and a gap
+        let fit = |extent: u32, offset: u32, cell: u32, padding: u32| {
+            let rest = offset.checked_add(cell).and_then(|first| extent.checked_sub(first));
+            match (rest, cell.checked_add(padding)) {
+                (Some(rest), Some(stride)) => rest / stride + 1,
+                (Some(_), None) => 1,
+                (None, _) => 0,
+            }
+        };
+        let columns = fit(image_size.x, offset.x, cell.x, padding.x);
+        let rows = fit(image_size.y, offset.y, cell.y, padding.y);
+        UVec2::new(
+            match self.columns {
+                0 => columns,
+                explicit => explicit.min(columns),
+            },
+            match self.rows {
+                0 => rows,
+                explicit => explicit.min(rows),
+            },
+        )
+    }
+}
+
+/// One frame of an atlas, in pixels from the image's top-left corner
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtlasFrame {
+    pub name: String,
+    pub rect: URect,
+}
+
+/// An atlas read from its file, with the frames of its manifest if it has one
+#[derive(Clone, Debug)]
+pub struct SpriteAtlas {
+    pub slicing: AtlasSlicing,
+    manifest_frames: Vec<AtlasFrame>,
+}
+
+impl SpriteAtlas {
+    /// Atlas for a slicing, reading its manifest if it has one
+    pub fn new(slicing: AtlasSlicing) -> Result<Self, Box<dyn std::error::Error>> {
+        let manifest_frames = match &slicing {
+            AtlasSlicing::Grid(_) => Vec::new(),
+            AtlasSlicing::Manifest(path) => read_manifest(path)?,
+        };
+        Ok(Self { slicing, manifest_frames })
+    }
+
+    /// Frames of the atlas for an image of the given size
+    pub fn frames(&self, image_size: UVec2) -> Vec<AtlasFrame> {
+        match &self.slicing {
+            AtlasSlicing::Grid(grid) => grid.frames(image_size),
+            AtlasSlicing::Manifest(_) => self.manifest_frames.clone(),
+        }
+    }
+
+    /// Rectangle of a frame, if the atlas has it
+    pub fn frame_rect(&self, frame: &SpriteFrame, image_size: UVec2) -> Option<URect> {
+        // Grid cells are found directly, since animations look one up every frame
+        if let (AtlasSlicing::Grid(grid), SpriteFrame::Index(index)) = (&self.slicing, frame) {
+            return grid.cell_rect(*index, image_size);
+        }
+        let frames = self.frames(image_size);
+        let found = match frame {
+            SpriteFrame::Index(index) => frames.get(*index),
+            SpriteFrame::Name(name) => frames.iter().find(|frame| &frame.name == name),
+        };
+        found.map(|frame| frame.rect)
+    }
+}
+
+/// File path of the atlas for an image, given by its path in the assets folder
+pub fn atlas_path(image_path: &str) -> String {
+    format!("assets/{}{}", image_path, ATLAS_SUFFIX)
+}
+
+/// Read the atlas of an image
+pub fn read_atlas(image_path: &str) -> Result<SpriteAtlas, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(atlas_path(image_path))?;
+    SpriteAtlas::new(ron::from_str(&text)?)
+}
+
+/// Write the atlas of an image next to it
+pub fn write_atlas(image_path: &str, slicing: &AtlasSlicing) -> Result<(), Box<dyn std::error::Error>> {
+    let text = ron::ser::to_string_pretty(slicing, ron::ser::PrettyConfig::default())?;
+    fs::write(atlas_path(image_path), text)?;
+    Ok(())
+}
+
+/// Read every atlas in the assets folder, by the path of its image, skipping files
+/// that fail to parse
+pub fn read_atlas_directory() -> Vec<(String, SpriteAtlas)> {
+    let mut files = Vec::new();
+    collect_atlas_files(Path::new("assets"), &mut files);
+
+    let mut atlases = Vec::new();
+    for file in files {
+        // Keep image paths in the same form as sprite asset paths
+        let Some(image_path) = file
+            .strip_prefix("assets")
+            .ok()
+            .and_then(|path| path.to_str())
+            .and_then(|path| path.strip_suffix(ATLAS_SUFFIX))
+            .map(|path| path.replace('\\', "/").trim_start_matches('/').to_string())
+        else {
+            continue;
+        };
+
+        match read_atlas(&image_path) {
+            Ok(atlas) => atlases.push((image_path, atlas)),
+            Err(e) => warn!("Failed to read atlas {}: {}", file.display(), e),
+        }
+    }
+    atlases
+}
+
+fn collect_atlas_files(directory: &Path, files: &mut Vec<std::path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_atlas_files(&path, files);
+        } else if path.to_str().is_some_and(|path| path.ends_with(ATLAS_SUFFIX)) {
+            files.push(path);
+        }
+    }
+}
+
+/// Texture packer manifest, with frames keyed by name or listed in order
+#[derive(Deserialize)]
+struct PackerManifest {
+    frames: PackerFrames,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PackerFrames {
+    Hash(NamedFrames),
+    Array(Vec<PackerFrame>),
+}
+
+/// Frames keyed by name, in the order the manifest lists them
+///
+/// Frame indices count frames in manifest order, which a sorted map would lose:
+/// `walk_10` sorts before `walk_2`.
+struct NamedFrames(Vec<(String, PackerFrame)>);
+
+impl<'de> Deserialize<'de> for NamedFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NamedFramesVisitor;
+
+        impl<'de> Visitor<'de> for NamedFramesVisitor {
+            type Value = NamedFrames;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of frames by name")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<NamedFrames, A::Error> {
+                let mut frames = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(NamedFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_map(NamedFramesVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct PackerFrame {
+    #[serde(default)]
+    filename: String,
+    frame: PackerRect,
+}
+
+#[derive(Deserialize)]
+struct PackerRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+/// Read the frames of a texture packer manifest, as JSON or RON by its extension
+///
+/// Packers that rotate frames to fit them tighter aren't supported, so packing
+/// should be done with rotation turned off.
+fn read_manifest(path: &str) -> Result<Vec<AtlasFrame>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(format!("assets/{}", path))?;
+    parse_manifest(&text, path)
+}
+
+/// Parse the frames of a manifest read from `path`, in the order it lists them
+fn parse_manifest(text: &str, path: &str) -> Result<Vec<AtlasFrame>, Box<dyn std::error::Error>> {
+    let manifest: PackerManifest = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_str(text)?,
+        _ => ron::from_str(text)?,
+    };
+
+    let frames = match manifest.frames {
+        PackerFrames::Hash(NamedFrames(frames)) => frames
+            .into_iter()
+            .map(|(name, frame)| PackerFrame { filename: name, ..frame })
+            .collect(),
+        PackerFrames::Array(frames) => frames,
+    };
+    Ok(frames
+        .into_iter()
+        .map(|frame| AtlasFrame {
+            name: frame.filename,
+            rect: URect::new(frame.frame.x, frame.frame.y, frame.frame.x + frame.frame.w, frame.frame.y + frame.frame.h),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_names(frames: &[AtlasFrame]) -> Vec<&str> {
+        frames.iter().map(|frame| frame.name.as_str()).collect()
+    }
+
+    #[test]
+    fn grid_cells_stay_inside_the_image() {
+        let grid = AtlasGrid {
+            cell_size: [16, 16],
+            columns: 10,
+            rows: 10,
+            ..default()
+        };
+        let image_size = UVec2::new(64, 32);
+
+        let frames = grid.frames(image_size);
+        assert_eq!(frames.len(), 8);
+        assert!(frames.iter().all(|frame| frame.rect.max.cmple(image_size).all()));
+        assert_eq!(grid.cell_rect(8, image_size), None);
+    }
+
+    #[test]
+    fn huge_grids_do_not_overflow() {
+        let grid = AtlasGrid {
+            cell_size: [u32::MAX, 16],
+            padding: [u32::MAX, 0],
+            offset: [u32::MAX, 0],
+            columns: u32::MAX,
+            rows: u32::MAX,
+        };
+        assert!(grid.frames(UVec2::new(u32::MAX, 64)).is_empty());
+        assert_eq!(grid.cell_rect(0, UVec2::new(u32::MAX, 64)), None);
+    }
+
+    #[test]
+    fn hash_manifest_keeps_frame_order() {
+        let json = r#"{
+            "frames": {
+                "walk_2": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } },
+                "walk_10": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 } },
+                "idle": { "frame": { "x": 32, "y": 0, "w": 16, "h": 24 } }
+            }
+        }"#;
+        let frames = parse_manifest(json, "sheets/hero.json").unwrap();
+        assert_eq!(frame_names(&frames), ["walk_2", "walk_10", "idle"]);
+        assert_eq!(frames[1].rect, URect::new(16, 0, 32, 16));
+        assert_eq!(frames[2].rect, URect::new(32, 0, 48, 24));
+
+        let ron = r#"(
+            frames: {
+                "walk_2": (frame: (x: 0, y: 0, w: 16, h: 16)),
+                "walk_10": (frame: (x: 16, y: 0, w: 16, h: 16)),
+                "idle": (frame: (x: 32, y: 0, w: 16, h: 24)),
+            },
+        )"#;
+        let frames = parse_manifest(ron, "sheets/hero.ron").unwrap();
+        assert_eq!(frame_names(&frames), ["walk_2", "walk_10", "idle"]);
+    }
+
+    #[test]
+    fn array_manifest_keeps_frame_order() {
+        let json = r#"{
+            "frames": [
+                { "filename": "b", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 } },
+                { "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } }
+            ]
+        }"#;
+        let frames = parse_manifest(json, "sheets/tiles.json").unwrap();
+        assert_eq!(frame_names(&frames), ["b", "a"]);
+        assert_eq!(frames[0].rect, URect::new(8, 0, 16, 8));
+    }
+}
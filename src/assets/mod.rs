@@ -1,9 +1,14 @@
 //! Enhanced asset management and loading system
 
 use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
 use std::path::Path;
 use std::fs;
 
+pub mod atlas;
+
 use crate::components::*;
 use crate::resources::*;
 
@@ -12,9 +17,15 @@ pub fn load_default_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut asset_registry: ResMut<AssetRegistry>,
+    mut atlas_library: ResMut<AtlasLibrary>,
+    mut images: ResMut<Assets<Image>>,
+    game_sounds: Res<GameSounds>,
 ) {
     info!("Loading default assets...");
     
+    asset_registry.missing_texture = images.add(missing_texture_image());
+    atlas_library.atlases = atlas::read_atlas_directory().into_iter().collect();
+    
     // Try to load default assets from the assets folder
     let default_assets = [
         "sprites/player.png",
@@ -23,7 +34,9 @@ pub fn load_default_assets(
         "backgrounds/default.png",
     ];
     
-    for asset_path in default_assets.iter() {
+    let default_sounds = [&game_sounds.shoot, &game_sounds.hit, &game_sounds.destroy];
+    
+    for asset_path in default_assets.iter().copied().chain(default_sounds.iter().map(|path| path.as_str())) {
         if let Err(err) = try_load_asset(&asset_server, &mut asset_registry, asset_path) {
             warn!("Failed to load default asset '{}': {}", asset_path, err);
         }
@@ -32,6 +45,136 @@ pub fn load_default_assets(
     info!("Default asset loading completed");
 }
 
+/// Kind of asset a file is imported as, by its extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetKind {
+    Image,
+    Sound,
+}
+
+impl AssetKind {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" => Some(AssetKind::Image),
+            "ogg" | "wav" | "flac" => Some(AssetKind::Sound),
+            _ => None,
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            AssetKind::Image => "Image",
+            AssetKind::Sound => "Sound",
+        }
+    }
+}
+
+/// Magenta and black checkerboard shown in place of textures that can't be found
+fn missing_texture_image() -> Image {
+    const MAGENTA: [u8; 4] = [255, 0, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    
+    let mut image = Image::new(
+        Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        [MAGENTA, BLACK, BLACK, MAGENTA].concat(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Keep the squares sharp however far the sprite is stretched
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/// A sprite asset with the sprite and texture it drives, and the animation picking its frame
+type SpriteTextureData = (
+    Entity,
+    Ref<'static, SpriteAsset>,
+    &'static mut Sprite,
+    Option<&'static Handle<Image>>,
+    Option<Ref<'static, AnimationState>>,
+);
+
+/// Show each entity's sprite asset texture, resolved through the asset registry
+///
+/// Textures cover the entity's unit quad, so the transform scale still sets the
+/// sprite's size. Paths the registry doesn't know yet are imported, and entities
+/// whose file is missing or failed to load show the missing-texture placeholder.
+/// Sprite assets with a frame show only that frame of their image's atlas, and
+/// animated sprites show the frame their animation is on.
+/// Everything is resolved again whenever the registry, the atlases or an image
+/// change, so textures and frames appear as soon as their image finishes loading.
+pub fn apply_sprite_asset_textures(
+    mut commands: Commands,
+    asset_registry: Res<AssetRegistry>,
+    mut asset_importer: ResMut<AssetImporter>,
+    atlas_library: Res<AtlasLibrary>,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut sprite_query: Query<SpriteTextureData>,
+) {
+    let images_changed = image_events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::LoadedWithDependencies { .. } | AssetEvent::Modified { .. }))
+        .count()
+        > 0;
+    let refresh_all = asset_registry.is_changed() || atlas_library.is_changed() || images_changed;
+    
+    for (entity, sprite_asset, mut sprite, texture, animation) in sprite_query.iter_mut() {
+        let animation_changed = animation.as_ref().is_some_and(|animation| animation.is_changed());
+        if !refresh_all && !sprite_asset.is_changed() && !animation_changed {
+            continue;
+        }
+        
+        let handle = match &sprite_asset.asset_path {
+            Some(path) => sprite_texture(&asset_registry, &mut asset_importer, path),
+            None => Handle::default(),
+        };
+        
+        // Frames need the image's size, so they wait until it has loaded
+        let frame = match &animation {
+            Some(animation) => Some(SpriteFrame::Index(animation.atlas_frame)),
+            None => sprite_asset.frame.clone(),
+        };
+        let rect = match (&sprite_asset.asset_path, &frame) {
+            (Some(path), Some(frame)) if handle != asset_registry.missing_texture => images
+                .get(&handle)
+                .and_then(|image| atlas_library.frame_rect(path, frame, image.size()))
+                .map(|rect| rect.as_rect()),
+            _ => None,
+        };
+        
+        if texture != Some(&handle) {
+            commands.entity(entity).insert(handle);
+        }
+        if sprite.custom_size != Some(Vec2::ONE) {
+            sprite.custom_size = Some(Vec2::ONE);
+        }
+        if sprite.rect != rect {
+            sprite.rect = rect;
+        }
+    }
+}
+
+/// Texture for a sprite asset path, starting an import if the file exists but isn't
+/// known to the registry yet
+fn sprite_texture(asset_registry: &AssetRegistry, asset_importer: &mut AssetImporter, path: &str) -> Handle<Image> {
+    if let Some(handle) = asset_registry.get_texture(path) {
+        return handle.clone();
+    }
+    
+    let failed = asset_importer.failed_imports.iter().any(|(failed, _)| failed == path);
+    if !failed && Path::new(&format!("assets/{}", path)).exists() {
+        asset_importer.queue_import(path.to_string());
+    }
+    asset_registry.missing_texture.clone()
+}
+
 /// Try to load an asset, handling errors gracefully
 fn try_load_asset(
     asset_server: &AssetServer,
@@ -44,14 +187,17 @@ fn try_load_asset(
         return Err(format!("File not found: {}", full_path));
     }
     
-    // Load the asset
-    let handle: Handle<Image> = asset_server.load(asset_path.to_owned());
-    
-    // Create metadata
-    let metadata = create_asset_metadata(asset_path, &full_path)?;
-    
-    // Register in loading state
-    asset_registry.start_loading(asset_path.to_string(), handle);
+    // Load the asset and register it in loading state
+    match AssetKind::from_path(asset_path) {
+        Some(AssetKind::Sound) => {
+            let handle: Handle<AudioSource> = asset_server.load(asset_path.to_owned());
+            asset_registry.start_loading_sound(asset_path.to_string(), handle);
+        }
+        _ => {
+            let handle: Handle<Image> = asset_server.load(asset_path.to_owned());
+            asset_registry.start_loading(asset_path.to_string(), handle);
+        }
+    }
     
     info!("Started loading asset: {}", asset_path);
     Ok(())
@@ -109,13 +255,19 @@ pub fn handle_asset_imports(
     let paths_to_process: Vec<String> = asset_importer.pending_imports.iter().cloned().collect();
     
     for path in paths_to_process {
-        if let Some(handle) = asset_registry.loading_assets.get(&path) {
-            match asset_server.get_load_state(handle) {
+        let loading_id = match asset_registry.loading_assets.get(&path) {
+            Some(handle) => Some(handle.id().untyped()),
+            None => asset_registry.loading_sounds.get(&path).map(|handle| handle.id().untyped()),
+        };
+        if let Some(id) = loading_id {
+            match asset_server.get_load_state(id) {
                 Some(bevy::asset::LoadState::Loaded) => {
                     // Asset loaded successfully
-                    if let Some(handle) = asset_registry.loading_assets.get(&path).cloned() {
-                        if let Ok(metadata) = create_asset_metadata(&path, &format!("assets/{}", path)) {
+                    if let Ok(metadata) = create_asset_metadata(&path, &format!("assets/{}", path)) {
+                        if let Some(handle) = asset_registry.loading_assets.get(&path).cloned() {
                             asset_registry.register_image(path.clone(), handle, metadata);
+                        } else if let Some(handle) = asset_registry.loading_sounds.get(&path).cloned() {
+                            asset_registry.register_sound(path.clone(), handle, metadata);
                         }
                     }
                     asset_registry.finish_loading(&path);
@@ -250,13 +402,13 @@ pub fn import_asset(
     // Validate file extension
     let path = Path::new(asset_path);
     if let Some(extension) = path.extension() {
-        match extension.to_str() {
-            Some("png") | Some("jpg") | Some("jpeg") => {
-                // Valid image format
+        match AssetKind::from_path(asset_path) {
+            Some(_) => {
+                // Valid image or sound format
                 asset_importer.queue_import(asset_path.to_string());
                 Ok(())
             }
-            _ => Err(format!("Unsupported file format: {:?}", extension)),
+            None => Err(format!("Unsupported file format: {:?}", extension)),
         }
     } else {
         Err("File has no extension".to_string())
@@ -4,38 +4,83 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// Player component marker
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Player;
 
 /// Enemy component marker
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Enemy;
 
 /// Projectile component with velocity
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Projectile {
     pub velocity: Vec2,
 }
 
 /// Shooting component with cooldown timer
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Shooting {
     pub cooldown: f32,
 }
 
 /// Health component
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Health {
     pub current: f32,
     pub max: f32,
 }
 
 /// Collision component with radius
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Collision {
     pub radius: f32,
 }
 
+/// Stable identifier of a scene entity, saved with the scene so it survives
+/// reloads, unlike the `Entity` index
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[reflect(Component, Default)]
+pub struct EntityId(pub u32);
+
+/// Name and tags shown in the hierarchy and used to search for an entity
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct EntityLabel {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+impl EntityLabel {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Whether the entity carries this tag, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Link from an entity to the prefab it was instanced from
+#[derive(Component, Reflect, Clone, Debug, Default, PartialEq)]
+#[reflect(Component, Default)]
+pub struct PrefabInstance {
+    /// Prefab file the entity was instanced from
+    pub path: String,
+    /// Type paths of the components this instance changed, which prefab edits leave
+    /// alone; `children` stands for the instance's child entities
+    pub overrides: Vec<String>,
+}
+
 /// Selection component marker for selected entities
 #[derive(Component)]
 pub struct Selected;
@@ -44,16 +89,91 @@ pub struct Selected;
 #[derive(Component)]
 pub struct GridLine;
 
+/// Render layer for editor overlays such as the grid and gizmos, which only the
+/// scene view shows
+pub const EDITOR_RENDER_LAYER: u8 = 1;
+
+/// Camera that renders the editor's scene view
+///
+/// It belongs to the editor, so its framing is kept across play and stop.
+#[derive(Component)]
+pub struct EditorCamera;
+
+/// Camera that renders the game view, framed by the scene's `GameCamera`
+#[derive(Component)]
+pub struct GameViewCamera;
+
+/// Scene entity the player sees the game through
+///
+/// The game view is rendered from the first game camera in the scene, at the
+/// entity's position and rotation. In play mode the camera follows its target,
+/// letting it move freely inside the dead zone and staying inside the bounds.
+/// Shakes are offsets on top of the entity's transform, so they never move it.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct GameCamera {
+    /// World units per screen pixel
+    pub zoom: f32,
+    /// Entity to follow in play mode, by stable ID
+    pub follow: Option<EntityId>,
+    /// Seconds the camera takes to catch up with its target; 0 keeps it locked on
+    pub smoothing: f32,
+    /// Size of the area around the view's centre the target moves in without
+    /// moving the camera
+    pub dead_zone: Vec2,
+    /// How far ahead of a moving target the camera looks, in seconds of its movement
+    pub look_ahead: f32,
+    /// World area the view is kept inside
+    pub bounds: Option<Rect>,
+    /// Furthest a full-strength shake moves the view, in world units
+    pub shake_offset: f32,
+    /// Furthest a full-strength shake turns the view, in radians
+    pub shake_angle: f32,
+    /// Trauma lost per second
+    pub shake_decay: f32,
+    /// Shake strength from 0 to 1; the view shakes by its square
+    #[reflect(ignore)]
+    pub trauma: f32,
+}
+
+impl GameCamera {
+    /// Shake the camera harder, up to full strength
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+impl Default for GameCamera {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            follow: None,
+            smoothing: 0.15,
+            dead_zone: Vec2::ZERO,
+            look_ahead: 0.0,
+            bounds: None,
+            shake_offset: 12.0,
+            shake_angle: 0.05,
+            shake_decay: 1.5,
+            trauma: 0.0,
+        }
+    }
+}
+
 /// Background image component marker
 #[derive(Component)]
 pub struct BackgroundImage;
 
 /// Sprite asset component for entities with custom textures
-#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component, Default)]
 pub struct SpriteAsset {
     pub asset_path: Option<String>,
     pub tint_color: [f32; 4], // RGBA values for serialization
     pub scale: [f32; 2], // Vec2 as array for serialization
+    /// Frame of the image's atlas to show, or None for the whole image
+    #[serde(default)]
+    pub frame: Option<SpriteFrame>,
 }
 
 impl Default for SpriteAsset {
@@ -62,6 +182,7 @@ impl Default for SpriteAsset {
             asset_path: None,
             tint_color: [1.0, 1.0, 1.0, 1.0], // White
             scale: [1.0, 1.0],
+            frame: None,
         }
     }
 }
@@ -72,6 +193,7 @@ impl SpriteAsset {
             asset_path,
             tint_color: [1.0, 1.0, 1.0, 1.0],
             scale: [1.0, 1.0],
+            frame: None,
         }
     }
     
@@ -92,54 +214,621 @@ impl SpriteAsset {
     }
 }
 
-/// Entity types for spawning
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum EntityType {
-    Player,
-    Enemy,
-    Projectile,
+/// Frame of a sprite sheet, by its position in the atlas or its name
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SpriteFrame {
+    Index(usize),
+    Name(String),
 }
 
-/// Serializable transform for scene saving/loading
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SerializableTransform {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
-    pub rotation: f32,
-    pub scale_x: f32,
-    pub scale_y: f32,
+/// Frame-based animation of an entity's sprite sheet
+///
+/// Each clip steps through a range of frames of the atlas of the entity's sprite
+/// asset. Setting `clip` to another clip's name switches to that clip from its
+/// first frame. The playback position lives in [`AnimationState`], so it is never
+/// stored in scene files.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct AnimatedSprite {
+    pub clips: Vec<SpriteClip>,
+    /// Name of the clip playing
+    pub clip: String,
+    pub playing: bool,
+    /// Playback rate, 1 for each clip's own frame rate
+    pub speed: f32,
 }
 
-impl From<Transform> for SerializableTransform {
-    fn from(transform: Transform) -> Self {
+impl Default for AnimatedSprite {
+    fn default() -> Self {
+        let clip = SpriteClip::default();
+        Self {
+            clip: clip.name.clone(),
+            clips: vec![clip],
+            playing: true,
+            speed: 1.0,
+        }
+    }
+}
+
+impl AnimatedSprite {
+    pub fn get_clip(&self, name: &str) -> Option<&SpriteClip> {
+        self.clips.iter().find(|clip| clip.name == name)
+    }
+    
+    pub fn current_clip(&self) -> Option<&SpriteClip> {
+        self.get_clip(&self.clip)
+    }
+    
+    /// Switch to a clip by name; the clip already playing carries on
+    pub fn play(&mut self, name: &str) {
+        if self.clip != name {
+            self.clip = name.to_string();
+        }
+        self.playing = true;
+    }
+}
+
+/// A named range of atlas frames played at a fixed rate
+#[derive(Reflect, Clone, Debug, PartialEq)]
+#[reflect(Default)]
+pub struct SpriteClip {
+    pub name: String,
+    /// Atlas frame the clip starts on
+    pub first_frame: usize,
+    /// Atlas frame the clip ends on, before the first frame to play backwards
+    pub last_frame: usize,
+    /// Frames per second
+    pub fps: f32,
+    pub playback: AnimationPlayback,
+    pub events: Vec<AnimationFrameEvent>,
+}
+
+impl Default for SpriteClip {
+    fn default() -> Self {
         Self {
-            x: transform.translation.x,
-            y: transform.translation.y,
-            z: transform.translation.z,
-            rotation: transform.rotation.to_euler(EulerRot::ZYX).0,
-            scale_x: transform.scale.x,
-            scale_y: transform.scale.y,
+            name: "idle".to_string(),
+            first_frame: 0,
+            last_frame: 0,
+            fps: 12.0,
+            playback: AnimationPlayback::Loop,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl SpriteClip {
+    pub fn frame_count(&self) -> usize {
+        self.first_frame.abs_diff(self.last_frame) + 1
+    }
+    
+    /// Atlas frame shown at a step of the clip, counting from 0
+    pub fn atlas_frame(&self, step: usize) -> usize {
+        let step = step.min(self.frame_count() - 1);
+        if self.first_frame <= self.last_frame {
+            self.first_frame + step
+        } else {
+            self.first_frame - step
         }
     }
 }
 
-impl From<SerializableTransform> for Transform {
-    fn from(st: SerializableTransform) -> Self {
-        Transform {
-            translation: Vec3::new(st.x, st.y, st.z),
-            rotation: Quat::from_rotation_z(st.rotation),
-            scale: Vec3::new(st.scale_x, st.scale_y, 1.0),
+/// How a clip carries on after its last frame
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum AnimationPlayback {
+    #[default]
+    Loop,
+    /// Play backwards to the first frame, then forwards again
+    PingPong,
+    /// Stop on the last frame
+    Once,
+}
+
+/// Named event fired when a clip reaches one of its frames
+#[derive(Reflect, Clone, Debug, PartialEq, Default)]
+#[reflect(Default)]
+pub struct AnimationFrameEvent {
+    /// Step of the clip, counting from 0
+    pub frame: usize,
+    pub name: String,
+}
+
+/// Playback position of an [`AnimatedSprite`], kept up to date by the animation system
+#[derive(Component, Clone, Debug, Default)]
+pub struct AnimationState {
+    /// Name of the clip the position is in
+    pub clip: String,
+    /// Step of the clip on screen, counting from 0
+    pub step: usize,
+    /// Seconds spent on the current step
+    pub elapsed: f32,
+    /// Whether a ping-pong clip is on its way back to the first frame
+    pub reversing: bool,
+    /// Whether a clip that plays once has reached its last frame
+    pub finished: bool,
+    /// Atlas frame on screen
+    pub atlas_frame: usize,
+}
+
+impl AnimationState {
+    /// Position at the start of a clip
+    pub fn start(clip: &SpriteClip) -> Self {
+        Self {
+            clip: clip.name.clone(),
+            atlas_frame: clip.atlas_frame(0),
+            ..default()
         }
     }
+    
+    /// Jump to a step of the clip
+    pub fn seek(&mut self, clip: &SpriteClip, step: usize) {
+        self.step = step.min(clip.frame_count() - 1);
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.atlas_frame = clip.atlas_frame(self.step);
+    }
+    
+    /// Move the position on by some seconds, returning the steps reached on the way
+    pub fn advance(&mut self, clip: &SpriteClip, seconds: f32) -> Vec<usize> {
+        let mut reached = Vec::new();
+        if self.finished || clip.fps <= 0.0 {
+            return reached;
+        }
+        
+        let frame_time = 1.0 / clip.fps;
+        let last = clip.frame_count() - 1;
+        self.step = self.step.min(last);
+        self.elapsed += seconds;
+        while self.elapsed >= frame_time {
+            self.elapsed -= frame_time;
+            match clip.playback {
+                AnimationPlayback::Loop => {
+                    self.step = if self.step >= last { 0 } else { self.step + 1 };
+                }
+                AnimationPlayback::PingPong if last == 0 => {}
+                AnimationPlayback::PingPong => {
+                    if (self.reversing && self.step == 0) || (!self.reversing && self.step == last) {
+                        self.reversing = !self.reversing;
+                    }
+                    self.step = if self.reversing { self.step - 1 } else { self.step + 1 };
+                }
+                AnimationPlayback::Once => {
+                    if self.step >= last {
+                        self.finished = true;
+                        self.elapsed = 0.0;
+                        break;
+                    }
+                    self.step += 1;
+                }
+            }
+            reached.push(self.step);
+        }
+        self.atlas_frame = clip.atlas_frame(self.step);
+        reached
+    }
+}
+
+/// Keyframe timeline tweening an entity's transform and colors
+///
+/// Each track moves one property through its keyframes, one after another, and
+/// the tracks all play side by side, so keyframes sequence changes and tracks run
+/// them in parallel. Between two keyframes the value follows the later keyframe's
+/// easing curve. The playback position lives in [`TimelineState`]; removing it
+/// starts the timeline over.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct Timeline {
+    /// Name sent with the timeline's completion event
+    pub name: String,
+    pub tracks: Vec<TweenTrack>,
+    pub repeat: TimelineRepeat,
+    pub playing: bool,
+    /// Playback rate, 1 for real time
+    pub speed: f32,
+    /// Whether position and rotation keyframes are offsets from where the entity
+    /// was when the timeline started, and scale keyframes multiples of its scale
+    pub relative: bool,
 }
 
-/// Serializable entity for scene saving/loading
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SerializableEntity {
-    pub entity_type: EntityType,
-    pub transform: SerializableTransform,
-    pub health: Option<(f32, f32)>, // (current, max)
-    pub collision_radius: Option<f32>,
-    pub sprite_asset: Option<SpriteAsset>,
-}
\ No newline at end of file
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            name: "timeline".to_string(),
+            tracks: Vec::new(),
+            repeat: TimelineRepeat::Once,
+            playing: true,
+            speed: 1.0,
+            relative: false,
+        }
+    }
+}
+
+impl Timeline {
+    /// Length in seconds, up to the last keyframe of any track
+    pub fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .filter_map(|track| track.keyframes.last())
+            .map(|keyframe| keyframe.time)
+            .fold(0.0, f32::max)
+    }
+    
+    pub fn get_track(&self, property: TweenProperty) -> Option<&TweenTrack> {
+        self.tracks.iter().find(|track| track.property == property)
+    }
+    
+    /// Add a keyframe to a property's track, creating the track if there isn't one
+    pub fn insert_keyframe(&mut self, property: TweenProperty, keyframe: TweenKeyframe) {
+        let track = match self.tracks.iter().position(|track| track.property == property) {
+            Some(index) => &mut self.tracks[index],
+            None => {
+                self.tracks.push(TweenTrack {
+                    property,
+                    keyframes: Vec::new(),
+                });
+                self.tracks.last_mut().unwrap()
+            }
+        };
+        track.keyframes.push(keyframe);
+        track.sort();
+    }
+    
+    /// Builder form of [`Timeline::insert_keyframe`], for timelines made in code
+    pub fn with_keyframe(mut self, property: TweenProperty, time: f32, value: Vec4, easing: Easing) -> Self {
+        self.insert_keyframe(property, TweenKeyframe { time, value, easing });
+        self
+    }
+    
+    /// Value of every track with keyframes at a time
+    pub fn sample(&self, time: f32) -> Vec<(TweenProperty, Vec4)> {
+        self.tracks
+            .iter()
+            .filter_map(|track| Some((track.property, track.sample(time)?)))
+            .collect()
+    }
+}
+
+/// Keyframes of one property, in time order
+#[derive(Reflect, Clone, Debug, PartialEq, Default)]
+#[reflect(Default)]
+pub struct TweenTrack {
+    pub property: TweenProperty,
+    pub keyframes: Vec<TweenKeyframe>,
+}
+
+impl TweenTrack {
+    /// Put the keyframes back in time order after editing them
+    pub fn sort(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+    
+    /// Value at a time, holding the first and last keyframes outside their range
+    pub fn sample(&self, time: f32) -> Option<Vec4> {
+        let next = self.keyframes.iter().position(|keyframe| keyframe.time > time);
+        match next {
+            None => self.keyframes.last().map(|keyframe| keyframe.value),
+            Some(0) => Some(self.keyframes[0].value),
+            Some(index) => {
+                let from = &self.keyframes[index - 1];
+                let to = &self.keyframes[index];
+                let progress = (time - from.time) / (to.time - from.time);
+                Some(from.value.lerp(to.value, to.easing.apply(progress)))
+            }
+        }
+    }
+}
+
+/// Value a track reaches at a point in time
+#[derive(Reflect, Clone, Debug, PartialEq, Default)]
+#[reflect(Default)]
+pub struct TweenKeyframe {
+    /// Seconds from the start of the timeline
+    pub time: f32,
+    /// Value in the layout of the track's [`TweenProperty`]
+    pub value: Vec4,
+    /// Curve the value follows on its way here from the keyframe before
+    pub easing: Easing,
+}
+
+/// Property a timeline track animates
+///
+/// Keyframe values are stored as `Vec4`s: positions and scales use `x` and `y`,
+/// rotation and opacity use `x`, and tints use all four channels as RGBA.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum TweenProperty {
+    #[default]
+    Position,
+    /// Angle in degrees
+    Rotation,
+    Scale,
+    /// Sprite color, and the sprite asset's tint when there is one
+    Tint,
+    /// Alpha of the sprite color
+    Opacity,
+}
+
+impl TweenProperty {
+    pub const ALL: [TweenProperty; 5] = [
+        TweenProperty::Position,
+        TweenProperty::Rotation,
+        TweenProperty::Scale,
+        TweenProperty::Tint,
+        TweenProperty::Opacity,
+    ];
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            TweenProperty::Position => "Position",
+            TweenProperty::Rotation => "Rotation",
+            TweenProperty::Scale => "Scale",
+            TweenProperty::Tint => "Tint",
+            TweenProperty::Opacity => "Opacity",
+        }
+    }
+    
+    /// The property's current value on an entity, as a keyframe value
+    pub fn read(self, transform: &Transform, color: Color) -> Vec4 {
+        match self {
+            TweenProperty::Position => transform.translation.truncate().extend(0.0).extend(0.0),
+            TweenProperty::Rotation => Vec4::new(transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees(), 0.0, 0.0, 0.0),
+            TweenProperty::Scale => transform.scale.truncate().extend(0.0).extend(0.0),
+            TweenProperty::Tint => Vec4::from(color.as_rgba_f32()),
+            TweenProperty::Opacity => Vec4::new(color.a(), 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+/// How a timeline carries on after its last keyframe
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum TimelineRepeat {
+    /// Stop on the last keyframe
+    #[default]
+    Once,
+    Loop,
+    /// Play backwards to the start, then forwards again
+    PingPong,
+}
+
+/// Curve shaping how a value moves between two keyframes
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Jump to the value at the end
+    Step,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    /// Overshoot the value slightly, then settle on it
+    BackOut,
+    /// Spring past the value and wobble back to it
+    ElasticOut,
+    /// Bounce to a stop on the value
+    BounceOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 12] = [
+        Easing::Linear,
+        Easing::Step,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineInOut,
+        Easing::BackOut,
+        Easing::ElasticOut,
+        Easing::BounceOut,
+    ];
+    
+    /// Eased progress for a linear progress from 0 to 1
+    pub fn apply(self, t: f32) -> f32 {
+        use std::f32::consts::PI;
+        
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::Step => if t < 1.0 { 0.0 } else { 1.0 },
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0 }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+            }
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::BackOut => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::Step => "Step",
+            Easing::QuadIn => "Quad In",
+            Easing::QuadOut => "Quad Out",
+            Easing::QuadInOut => "Quad In-Out",
+            Easing::CubicIn => "Cubic In",
+            Easing::CubicOut => "Cubic Out",
+            Easing::CubicInOut => "Cubic In-Out",
+            Easing::SineInOut => "Sine In-Out",
+            Easing::BackOut => "Back Out",
+            Easing::ElasticOut => "Elastic Out",
+            Easing::BounceOut => "Bounce Out",
+        }
+    }
+}
+
+/// Playback position of a [`Timeline`], kept up to date by the tween system
+#[derive(Component, Clone, Debug, Default)]
+pub struct TimelineState {
+    /// Seconds into the timeline
+    pub time: f32,
+    /// Whether a ping-pong timeline is on its way back to the start
+    pub reversing: bool,
+    /// Whether a timeline that plays once has reached its end
+    pub finished: bool,
+    /// Transform when the timeline started, which relative keyframes build on
+    pub start_transform: Transform,
+}
+
+impl TimelineState {
+    pub fn start(transform: Transform) -> Self {
+        Self {
+            time: 0.0,
+            reversing: false,
+            finished: false,
+            start_transform: transform,
+        }
+    }
+    
+    /// Move the position on by some seconds, returning whether the timeline reached
+    /// its end, or the end of a loop, on the way
+    pub fn advance(&mut self, timeline: &Timeline, seconds: f32) -> bool {
+        let duration = timeline.duration();
+        if self.finished {
+            return false;
+        }
+        if duration <= 0.0 {
+            // Nothing to play through: a single pose that is done straight away
+            self.finished = timeline.repeat == TimelineRepeat::Once;
+            return self.finished;
+        }
+        
+        let mut completed = false;
+        self.time += if self.reversing { -seconds } else { seconds };
+        loop {
+            match timeline.repeat {
+                TimelineRepeat::Once if self.time >= duration => {
+                    self.time = duration;
+                    self.finished = true;
+                    return true;
+                }
+                TimelineRepeat::Loop if self.time >= duration => {
+                    self.time -= duration;
+                    completed = true;
+                }
+                TimelineRepeat::PingPong if !self.reversing && self.time >= duration => {
+                    self.time = 2.0 * duration - self.time;
+                    self.reversing = true;
+                }
+                TimelineRepeat::PingPong if self.reversing && self.time <= 0.0 => {
+                    self.time = -self.time;
+                    self.reversing = false;
+                    completed = true;
+                }
+                _ => return completed,
+            }
+        }
+    }
+}
+
+/// Mixer bus a sound plays on, each with its own volume in `AudioSettings`
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[reflect(Default)]
+pub enum AudioBus {
+    Music,
+    /// Sound effects
+    #[default]
+    Sfx,
+}
+
+/// Sound an entity plays in play mode
+///
+/// Spatial sounds come from the entity's position, panning and fading with their
+/// distance from the game camera; other sounds play the same everywhere, like
+/// music. The sound itself plays on an entity of its own, see [`SoundPlayback`].
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default)]
+pub struct SoundEmitter {
+    /// Path of the sound in the assets folder
+    pub sound: Option<String>,
+    pub bus: AudioBus,
+    /// Volume before the bus volumes are applied, from 0 to 1
+    pub volume: f32,
+    pub looping: bool,
+    /// Whether the sound starts when the game starts playing
+    pub autoplay: bool,
+    pub spatial: bool,
+}
+
+impl Default for SoundEmitter {
+    fn default() -> Self {
+        Self {
+            sound: None,
+            bus: AudioBus::Sfx,
+            volume: 1.0,
+            looping: false,
+            autoplay: true,
+            spatial: true,
+        }
+    }
+}
+
+/// A sound playing on its own entity, spawned by the audio systems
+///
+/// Playback entities have no `Transform`, so they never become scene entities;
+/// spatial ones get their `GlobalTransform` set directly.
+#[derive(Component, Clone, Debug)]
+pub struct SoundPlayback {
+    pub bus: AudioBus,
+    /// Volume before the bus volumes are applied
+    pub volume: f32,
+    /// Entity whose `SoundEmitter` the sound belongs to, which it follows
+    pub emitter: Option<Entity>,
+    /// Whether this is the asset browser's preview, which plays in edit mode too
+    pub preview: bool,
+    /// Whether the sound had loaded by the last frame without starting, which
+    /// happens when there's no audio output to play it on
+    pub stalled: bool,
+}
+
+/// Link from a `SoundEmitter` entity to the entity playing its sound
+#[derive(Component, Clone, Copy, Debug)]
+pub struct EmitterPlayback(pub Entity);
+
+/// Entity types for spawning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityType {
+    Player,
+    Enemy,
+    Projectile,
+}
@@ -0,0 +1,780 @@
+//! Undo/redo history for editor operations
+//!
+//! Every reversible editor operation is recorded as an [`EditorAction`]. Actions are
+//! applied and reverted with exclusive world access through the [`Undo`] and [`Redo`]
+//! commands, so any system or UI panel can trigger them through `Commands`.
+
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use std::any::TypeId;
+use std::collections::VecDeque;
+
+use crate::resources::*;
+use crate::systems::tween::end_timeline_preview;
+use crate::scene::{hierarchy_entities, serialize_entity, spawn_serialized_hierarchy, top_level_entities, SerializableEntity};
+
+/// Edits to the same components closer together than this collapse into one undo step
+const MERGE_WINDOW_SECONDS: f64 = 0.5;
+
+/// An entity and its descendants as the history captured them
+pub struct EntityRecord {
+    /// The entity followed by its descendants, in the order `data` stores them
+    pub entities: Vec<Entity>,
+    /// Parent the entity is attached to again when it is restored
+    pub parent: Option<Entity>,
+    pub data: SerializableEntity,
+}
+
+impl EntityRecord {
+    /// The top entity of the recorded subtree
+    pub fn root(&self) -> Entity {
+        self.entities[0]
+    }
+
+    fn remap_entity(&mut self, from: Entity, to: Entity) {
+        for entity in self.entities.iter_mut().chain(self.parent.as_mut()) {
+            if *entity == from {
+                *entity = to;
+            }
+        }
+    }
+}
+
+/// A single reversible editor operation
+pub enum EditorAction {
+    /// An entity was spawned from the given data
+    Spawn(EntityRecord),
+    /// An entity was deleted, and the given data restores it
+    Delete(EntityRecord),
+    /// An entity moved to a different parent, where `None` is the scene root
+    SetParent {
+        entity: Entity,
+        before: Option<Entity>,
+        after: Option<Entity>,
+    },
+    /// A component value changed, where `None` means the component was absent
+    SetComponent {
+        entity: Entity,
+        type_id: TypeId,
+        before: Option<Box<dyn Reflect>>,
+        after: Option<Box<dyn Reflect>>,
+    },
+    /// Several actions that are undone and redone as one step
+    Group(Vec<EditorAction>),
+}
+
+impl EditorAction {
+    /// Record a change to a typed component
+    pub fn set_component<T: Component + Reflect>(entity: Entity, before: Option<T>, after: Option<T>) -> Self {
+        Self::SetComponent {
+            entity,
+            type_id: TypeId::of::<T>(),
+            before: before.map(|value| Box::new(value) as Box<dyn Reflect>),
+            after: after.map(|value| Box::new(value) as Box<dyn Reflect>),
+        }
+    }
+
+    /// Human readable description for menus
+    pub fn describe(&self) -> String {
+        match self {
+            EditorAction::Spawn(record) => format!("Spawn {}", record.data.display_name()),
+            EditorAction::Delete(record) => format!("Delete {}", record.data.display_name()),
+            EditorAction::SetParent { .. } => "Reparent Entity".to_string(),
+            EditorAction::SetComponent { before, after, .. } => {
+                let name = after
+                    .as_ref()
+                    .or(before.as_ref())
+                    .and_then(|value| value.get_represented_type_info())
+                    .map(|info| info.type_path_table().short_path())
+                    .unwrap_or("Component");
+                format!("Edit {}", name)
+            }
+            EditorAction::Group(actions) => match actions.as_slice() {
+                [single] => single.describe(),
+                _ => format!("{} Changes", actions.len()),
+            },
+        }
+    }
+
+    /// Whether `other` edits exactly the same components and can be folded into this action
+    fn can_merge(&self, other: &EditorAction) -> bool {
+        match (self, other) {
+            (
+                EditorAction::SetComponent { entity, type_id, .. },
+                EditorAction::SetComponent { entity: other_entity, type_id: other_type_id, .. },
+            ) => entity == other_entity && type_id == other_type_id,
+            (EditorAction::Group(actions), EditorAction::Group(other_actions)) => {
+                actions.len() == other_actions.len()
+                    && actions.iter().zip(other_actions).all(|(action, other)| action.can_merge(other))
+            }
+            _ => false,
+        }
+    }
+
+    /// Keep this action's starting values and take the final values from `other`
+    fn merge(&mut self, other: EditorAction) {
+        match (self, other) {
+            (EditorAction::SetComponent { after, .. }, EditorAction::SetComponent { after: other_after, .. }) => {
+                *after = other_after;
+            }
+            (EditorAction::Group(actions), EditorAction::Group(other_actions)) => {
+                for (action, other) in actions.iter_mut().zip(other_actions) {
+                    action.merge(other);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Point references to a respawned entity at its new id
+    fn remap_entity(&mut self, from: Entity, to: Entity) {
+        match self {
+            EditorAction::Spawn(record) | EditorAction::Delete(record) => record.remap_entity(from, to),
+            EditorAction::SetParent { entity, before, after } => {
+                for reference in [Some(entity), before.as_mut(), after.as_mut()].into_iter().flatten() {
+                    if *reference == from {
+                        *reference = to;
+                    }
+                }
+            }
+            EditorAction::SetComponent { entity, .. } => {
+                if *entity == from {
+                    *entity = to;
+                }
+            }
+            EditorAction::Group(actions) => {
+                for action in actions {
+                    action.remap_entity(from, to);
+                }
+            }
+        }
+    }
+}
+
+/// Undo and redo stacks for editor operations
+#[derive(Resource)]
+pub struct EditorHistory {
+    undo_stack: VecDeque<EditorAction>,
+    redo_stack: Vec<EditorAction>,
+    max_depth: usize,
+    last_merge_time: f64,
+    revision: u64,
+}
+
+impl Default for EditorHistory {
+    fn default() -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            max_depth: 100,
+            last_merge_time: f64::NEG_INFINITY,
+            revision: 0,
+        }
+    }
+}
+
+impl EditorHistory {
+    /// Record a new action, discarding anything that could be redone
+    pub fn push(&mut self, action: EditorAction) {
+        self.redo_stack.clear();
+        self.undo_stack.push_back(action);
+        self.last_merge_time = f64::NEG_INFINITY;
+        self.revision += 1;
+        self.trim();
+    }
+
+    /// Record an action that may be folded into the previous one, used for continuous
+    /// edits such as dragging a value in the inspector
+    pub fn push_merged(&mut self, action: EditorAction, now: f64) {
+        let within_window = now - self.last_merge_time < MERGE_WINDOW_SECONDS;
+        let mergeable = within_window
+            && self.redo_stack.is_empty()
+            && self.undo_stack.back().is_some_and(|last| last.can_merge(&action));
+
+        if mergeable {
+            if let Some(last) = self.undo_stack.back_mut() {
+                last.merge(action);
+            }
+            self.revision += 1;
+        } else {
+            self.push(action);
+        }
+        self.last_merge_time = now;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Description of the action the next undo would revert
+    pub fn undo_description(&self) -> Option<String> {
+        self.undo_stack.back().map(EditorAction::describe)
+    }
+
+    /// Description of the action the next redo would reapply
+    pub fn redo_description(&self) -> Option<String> {
+        self.redo_stack.last().map(EditorAction::describe)
+    }
+
+    /// Counter that changes whenever the scene is modified through the history
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Change how many actions are kept, dropping the oldest ones if needed
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth.max(1);
+        self.trim();
+    }
+
+    /// Forget every recorded action
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_merge_time = f64::NEG_INFINITY;
+    }
+
+    fn trim(&mut self) {
+        while self.undo_stack.len() > self.max_depth {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Point recorded actions at an entity's replacement after it was respawned
+    pub fn remap_entity(&mut self, from: Entity, to: Entity) {
+        for action in self.undo_stack.iter_mut().chain(self.redo_stack.iter_mut()) {
+            action.remap_entity(from, to);
+        }
+    }
+}
+
+/// Command that reverts the most recent action
+pub struct Undo;
+
+impl Command for Undo {
+    fn apply(self, world: &mut World) {
+        // Undone edits would be recorded against the pose instead of the entity
+        end_timeline_preview(world);
+        let Some(mut action) = world.resource_mut::<EditorHistory>().undo_stack.pop_back() else {
+            return;
+        };
+
+        let remaps = revert(&mut action, world);
+
+        let mut history = world.resource_mut::<EditorHistory>();
+        for (from, to) in remaps {
+            action.remap_entity(from, to);
+            history.remap_entity(from, to);
+        }
+        history.redo_stack.push(action);
+        history.last_merge_time = f64::NEG_INFINITY;
+        history.revision += 1;
+    }
+}
+
+/// Command that reapplies the most recently undone action
+pub struct Redo;
+
+impl Command for Redo {
+    fn apply(self, world: &mut World) {
+        end_timeline_preview(world);
+        let Some(mut action) = world.resource_mut::<EditorHistory>().redo_stack.pop() else {
+            return;
+        };
+
+        let remaps = reapply(&mut action, world);
+
+        let mut history = world.resource_mut::<EditorHistory>();
+        for (from, to) in remaps {
+            action.remap_entity(from, to);
+            history.remap_entity(from, to);
+        }
+        history.undo_stack.push_back(action);
+        history.last_merge_time = f64::NEG_INFINITY;
+        history.revision += 1;
+        history.trim();
+    }
+}
+
+/// Command that records an entity spawned earlier in the same command queue
+pub struct RecordSpawn {
+    pub entity: Entity,
+}
+
+impl Command for RecordSpawn {
+    fn apply(self, world: &mut World) {
+        if let Some(record) = capture_entity(world, self.entity) {
+            world.resource_mut::<EditorHistory>().push(EditorAction::Spawn(record));
+        }
+    }
+}
+
+/// Command that deletes an entity with its children and records it so it can be restored
+pub struct DeleteEntity {
+    pub entity: Entity,
+}
+
+impl Command for DeleteEntity {
+    fn apply(self, world: &mut World) {
+        // Nothing to record if the entity is already gone
+        if let Some(record) = despawn_entity(world, self.entity) {
+            world.resource_mut::<EditorHistory>().push(EditorAction::Delete(record));
+        }
+    }
+}
+
+/// Command that deletes several entities with their children as one undo step
+pub struct DeleteEntities {
+    pub entities: Vec<Entity>,
+}
+
+impl Command for DeleteEntities {
+    fn apply(self, world: &mut World) {
+        // Children go with their parent, so only delete the top of each subtree
+        let entities = top_level_entities(world, &self.entities);
+        let mut actions: Vec<EditorAction> = entities
+            .into_iter()
+            .filter_map(|entity| delete_entity(world, entity))
+            .collect();
+
+        match actions.len() {
+            0 => {}
+            1 => world.resource_mut::<EditorHistory>().push(actions.remove(0)),
+            _ => world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions)),
+        }
+    }
+}
+
+/// Command that moves an entity under a new parent, or to the scene root when
+/// `parent` is `None`, and records the move
+pub struct ReparentEntity {
+    pub entity: Entity,
+    pub parent: Option<Entity>,
+}
+
+impl Command for ReparentEntity {
+    fn apply(self, world: &mut World) {
+        if let Some(action) = set_parent(world, self.entity, self.parent) {
+            world.resource_mut::<EditorHistory>().push(action);
+        }
+    }
+}
+
+/// Spawn entities from serialized data under `parent`, or at the scene root, and
+/// record them as one undo step
+pub fn spawn_recorded(world: &mut World, entities: &[SerializableEntity], parent: Option<Entity>) -> Vec<Entity> {
+    let mut spawned = Vec::new();
+    let mut actions = Vec::new();
+
+    for data in entities {
+        let (entity, action) = spawn_serialized(world, data, parent);
+        spawned.push(entity);
+        actions.push(action);
+    }
+
+    match actions.len() {
+        0 => {}
+        1 => world.resource_mut::<EditorHistory>().push(actions.remove(0)),
+        _ => world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions)),
+    }
+
+    spawned
+}
+
+/// Spawn an entity and its children from serialized data under `parent`, returning
+/// it with the action that removes it again
+pub fn spawn_serialized(world: &mut World, data: &SerializableEntity, parent: Option<Entity>) -> (Entity, EditorAction) {
+    let record = respawn_entity(world, data, parent);
+    (record.root(), EditorAction::Spawn(record))
+}
+
+/// Despawn an entity with its children and return the action that restores them
+pub fn delete_entity(world: &mut World, entity: Entity) -> Option<EditorAction> {
+    despawn_entity(world, entity).map(EditorAction::Delete)
+}
+
+/// Remove a component from an entity and return the action that puts it back
+pub fn remove_component(world: &mut World, entity: Entity, type_id: TypeId) -> Option<EditorAction> {
+    let before = read_component(world, entity, type_id)?;
+    write_component(world, entity, type_id, None);
+
+    Some(EditorAction::SetComponent {
+        entity,
+        type_id,
+        before: Some(before),
+        after: None,
+    })
+}
+
+/// Write component values to an entity and return the action that reverts them
+///
+/// Values are applied through the type registry, so each component must be
+/// registered with `#[reflect(Component)]`.
+pub fn set_components(world: &mut World, entity: Entity, components: Vec<Box<dyn Reflect>>) -> Option<EditorAction> {
+    let mut actions = Vec::new();
+
+    for component in components {
+        let Some(type_id) = component.get_represented_type_info().map(|info| info.type_id()) else {
+            warn!("Cannot apply component without type info");
+            continue;
+        };
+
+        let before = read_component(world, entity, type_id);
+        write_component(world, entity, type_id, Some(&*component));
+        actions.push(EditorAction::SetComponent {
+            entity,
+            type_id,
+            before,
+            after: Some(component),
+        });
+    }
+
+    match actions.len() {
+        0 => None,
+        1 => actions.pop(),
+        _ => Some(EditorAction::Group(actions)),
+    }
+}
+
+/// Move an entity under a new parent and return the action that reverts the move
+///
+/// The entity keeps its place in the world, so its local transform is rewritten
+/// relative to the new parent. Moves that would make an entity its own ancestor
+/// are refused.
+pub fn set_parent(world: &mut World, entity: Entity, parent: Option<Entity>) -> Option<EditorAction> {
+    world.get_entity(entity)?;
+    if let Some(parent) = parent {
+        world.get_entity(parent)?;
+        if parent == entity || is_ancestor(world, entity, parent) {
+            warn!("Cannot move an entity under itself or one of its children");
+            return None;
+        }
+    }
+
+    let before = world.get::<Parent>(entity).map(Parent::get);
+    if before == parent {
+        return None;
+    }
+
+    let global_transform = world.get::<GlobalTransform>(entity).copied().unwrap_or_default();
+    let local_transform = match parent.and_then(|parent| world.get::<GlobalTransform>(parent)) {
+        Some(parent_transform) => global_transform.reparented_to(parent_transform),
+        None => global_transform.compute_transform(),
+    };
+
+    write_parent(world, entity, parent);
+
+    let mut actions = vec![EditorAction::SetParent { entity, before, after: parent }];
+    actions.extend(set_components(world, entity, vec![Box::new(local_transform)]));
+    Some(EditorAction::Group(actions))
+}
+
+/// Whether `ancestor` is above `entity` in the hierarchy
+fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool {
+    let mut current = entity;
+    while let Some(parent) = world.get::<Parent>(current) {
+        if parent.get() == ancestor {
+            return true;
+        }
+        current = parent.get();
+    }
+    false
+}
+
+/// Attach an entity to a parent, or detach it when the parent is `None` or gone
+fn write_parent(world: &mut World, entity: Entity, parent: Option<Entity>) {
+    if world.get_entity(entity).is_none() {
+        return;
+    }
+
+    match parent.filter(|&parent| world.get_entity(parent).is_some()) {
+        Some(parent) => {
+            world.entity_mut(parent).add_child(entity);
+        }
+        None => {
+            world.entity_mut(entity).remove_parent();
+        }
+    }
+}
+
+/// Revert an action, returning any entities that were respawned under a new id
+fn revert(action: &mut EditorAction, world: &mut World) -> Vec<(Entity, Entity)> {
+    match action {
+        EditorAction::Spawn(record) => {
+            if let Some(current) = despawn_entity(world, record.root()) {
+                *record = current;
+            }
+            Vec::new()
+        }
+        EditorAction::Delete(record) => {
+            let respawned = respawn_entity(world, &record.data, record.parent);
+            record.entities.iter().copied().zip(respawned.entities).collect()
+        }
+        EditorAction::SetParent { entity, before, .. } => {
+            write_parent(world, *entity, *before);
+            Vec::new()
+        }
+        EditorAction::SetComponent { entity, type_id, before, .. } => {
+            write_component(world, *entity, *type_id, before.as_deref());
+            Vec::new()
+        }
+        EditorAction::Group(actions) => {
+            let mut remaps = Vec::new();
+            for index in (0..actions.len()).rev() {
+                let new_remaps = revert(&mut actions[index], world);
+                for &(from, to) in &new_remaps {
+                    for action in actions.iter_mut() {
+                        action.remap_entity(from, to);
+                    }
+                }
+                remaps.extend(new_remaps);
+            }
+            remaps
+        }
+    }
+}
+
+/// Reapply an action, returning any entities that were respawned under a new id
+fn reapply(action: &mut EditorAction, world: &mut World) -> Vec<(Entity, Entity)> {
+    match action {
+        EditorAction::Spawn(record) => {
+            let respawned = respawn_entity(world, &record.data, record.parent);
+            record.entities.iter().copied().zip(respawned.entities).collect()
+        }
+        EditorAction::Delete(record) => {
+            if let Some(current) = despawn_entity(world, record.root()) {
+                *record = current;
+            }
+            Vec::new()
+        }
+        EditorAction::SetParent { entity, after, .. } => {
+            write_parent(world, *entity, *after);
+            Vec::new()
+        }
+        EditorAction::SetComponent { entity, type_id, after, .. } => {
+            write_component(world, *entity, *type_id, after.as_deref());
+            Vec::new()
+        }
+        EditorAction::Group(actions) => {
+            let mut remaps = Vec::new();
+            for index in 0..actions.len() {
+                let new_remaps = reapply(&mut actions[index], world);
+                for &(from, to) in &new_remaps {
+                    for action in actions.iter_mut() {
+                        action.remap_entity(from, to);
+                    }
+                }
+                remaps.extend(new_remaps);
+            }
+            remaps
+        }
+    }
+}
+
+/// Spawn an entity and its children from serialized data immediately, under
+/// `parent` if it still exists
+fn respawn_entity(world: &mut World, data: &SerializableEntity, parent: Option<Entity>) -> EntityRecord {
+    let entities = spawn_serialized_hierarchy(world, data);
+    write_parent(world, entities[0], parent);
+
+    EntityRecord {
+        entities,
+        parent,
+        data: data.clone(),
+    }
+}
+
+/// Capture an entity with its children and its place in the hierarchy
+fn capture_entity(world: &World, entity: Entity) -> Option<EntityRecord> {
+    Some(EntityRecord {
+        data: serialize_entity(world, entity)?,
+        entities: hierarchy_entities(world, entity),
+        parent: world.get::<Parent>(entity).map(Parent::get),
+    })
+}
+
+/// Despawn an entity with its children, returning their data and clearing them
+/// from the selection
+fn despawn_entity(world: &mut World, entity: Entity) -> Option<EntityRecord> {
+    let record = capture_entity(world, entity)?;
+    despawn_with_children_recursive(world, entity);
+
+    world
+        .resource_mut::<SelectedEntity>()
+        .retain(|selected| !record.entities.contains(selected));
+
+    Some(record)
+}
+
+/// Copy a component's current value out of the world
+fn read_component(world: &World, entity: Entity, type_id: TypeId) -> Option<Box<dyn Reflect>> {
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let reflect_component = type_registry.get_type_data::<ReflectComponent>(type_id)?;
+    let component = reflect_component.reflect(world.get_entity(entity)?)?;
+
+    // Prefer a concrete copy so it can be inserted again if the component is removed
+    let concrete = type_registry
+        .get_type_data::<ReflectFromReflect>(type_id)
+        .and_then(|from_reflect| from_reflect.from_reflect(component));
+    Some(concrete.unwrap_or_else(|| component.clone_value()))
+}
+
+/// Insert, update or remove a component through reflection
+fn write_component(world: &mut World, entity: Entity, type_id: TypeId, value: Option<&dyn Reflect>) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+
+    let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(type_id) else {
+        warn!("Component {:?} is not registered with #[reflect(Component)]", type_id);
+        return;
+    };
+
+    // The entity may have been despawned outside of the history
+    if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+        match value {
+            Some(value) => reflect_component.apply_or_insert(&mut entity_mut, value, &type_registry),
+            None => reflect_component.remove(&mut entity_mut),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::*;
+
+    fn history_world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Transform>();
+            registry.register::<Vec3>();
+            registry.register::<Quat>();
+            registry.register::<Health>();
+            registry.register::<EntityId>();
+        }
+
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.init_resource::<SelectedEntity>();
+        world.init_resource::<EditorHistory>();
+        world
+    }
+
+    fn health(current: f32) -> Health {
+        Health { current, max: 100.0 }
+    }
+
+    fn current_health(world: &mut World, id: u32) -> Option<f32> {
+        world
+            .query::<(&EntityId, &Health)>()
+            .iter(world)
+            .find(|(entity_id, _)| entity_id.0 == id)
+            .map(|(_, health)| health.current)
+    }
+
+    fn edit_health(world: &mut World, entity: Entity, current: f32, now: f64) {
+        let action = set_components(world, entity, vec![Box::new(health(current))]).unwrap();
+        world.resource_mut::<EditorHistory>().push_merged(action, now);
+    }
+
+    #[test]
+    fn edits_inside_the_merge_window_are_one_undo_step() {
+        let mut world = history_world();
+        let entity = world.spawn((EntityId(1), health(10.0))).id();
+
+        edit_health(&mut world, entity, 20.0, 0.0);
+        edit_health(&mut world, entity, 30.0, 0.2);
+        edit_health(&mut world, entity, 40.0, 0.3 + MERGE_WINDOW_SECONDS);
+
+        Undo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(30.0));
+        Undo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(10.0));
+        assert!(!world.resource::<EditorHistory>().can_undo());
+    }
+
+    #[test]
+    fn groups_undo_in_reverse_order() {
+        let mut world = history_world();
+        let entity = world.spawn((EntityId(1), health(10.0))).id();
+
+        let actions = vec![
+            set_components(&mut world, entity, vec![Box::new(health(20.0))]).unwrap(),
+            set_components(&mut world, entity, vec![Box::new(health(30.0))]).unwrap(),
+        ];
+        world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions));
+
+        Undo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(10.0));
+        Redo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(30.0));
+    }
+
+    #[test]
+    fn edits_follow_an_entity_restored_by_undo() {
+        let mut world = history_world();
+        let entity = world.spawn((EntityId(1), health(10.0), Transform::default())).id();
+
+        edit_health(&mut world, entity, 20.0, 0.0);
+        DeleteEntity { entity }.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), None);
+
+        // The restored entity has a new id, which the older edit has to follow
+        Undo.apply(&mut world);
+        Undo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(10.0));
+
+        Redo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(20.0));
+        Redo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), None);
+
+        Undo.apply(&mut world);
+        assert_eq!(current_health(&mut world, 1), Some(20.0));
+    }
+
+    #[test]
+    fn entities_cannot_move_under_themselves() {
+        let mut world = history_world();
+        let parent = world.spawn(TransformBundle::default()).id();
+        let child = world.spawn(TransformBundle::default()).id();
+        world.entity_mut(parent).add_child(child);
+
+        assert!(set_parent(&mut world, parent, Some(parent)).is_none());
+        assert!(set_parent(&mut world, parent, Some(child)).is_none());
+        assert_eq!(world.get::<Parent>(child).map(Parent::get), Some(parent));
+        assert!(world.get::<Parent>(parent).is_none());
+
+        assert!(set_parent(&mut world, child, None).is_some());
+        assert!(world.get::<Parent>(child).is_none());
+    }
+
+    #[test]
+    fn only_the_newest_actions_are_kept() {
+        let mut world = history_world();
+        let entity = world.spawn((EntityId(1), health(0.0))).id();
+        world.resource_mut::<EditorHistory>().set_max_depth(3);
+
+        for current in 1..=5 {
+            let action = set_components(&mut world, entity, vec![Box::new(health(current as f32))]).unwrap();
+            world.resource_mut::<EditorHistory>().push(action);
+        }
+
+        let mut undone = 0;
+        while world.resource::<EditorHistory>().can_undo() {
+            Undo.apply(&mut world);
+            undone += 1;
+        }
+        assert_eq!(undone, 3);
+        assert_eq!(current_health(&mut world, 1), Some(2.0));
+    }
+}
@@ -10,6 +10,7 @@ pub mod ui;
 pub mod scene;
 pub mod assets;
 pub mod utils;
+pub mod history;
 
 pub use components::*;
 pub use resources::*;
@@ -25,9 +26,17 @@ impl Plugin for GameEnginePlugin {
             .init_resource::<EditorSceneState>()
             .init_resource::<CameraController>()
             .init_resource::<SelectedEntity>()
+            .init_resource::<InspectorSnapshot>()
+            .init_resource::<HierarchyState>()
             .init_resource::<DragState>()
             .init_resource::<AssetImporter>()
             .init_resource::<AssetRegistry>()
+            .init_resource::<PrefabLibrary>()
+            .init_resource::<AtlasLibrary>()
+            .init_resource::<AnimationPreview>()
+            .init_resource::<TimelinePreview>()
+            .init_resource::<AudioSettings>()
+            .init_resource::<GameSounds>()
             .init_resource::<AssetBrowserState>()
             .init_resource::<ShootingStats>()
             .init_resource::<ProjectManager>()
@@ -37,15 +46,63 @@ impl Plugin for GameEnginePlugin {
             .init_resource::<BackgroundSettings>()
             .init_resource::<SceneManager>()
             .init_resource::<DockTree>()
+            .init_resource::<EditorViewports>()
             .init_resource::<LayoutManager>()
+            .init_resource::<history::EditorHistory>()
+            .init_resource::<ConsoleLog>()
+            
+            // Register components for reflection so the inspector can edit them and
+            // scenes can store them; custom components need the same registration
+            .register_type::<Player>()
+            .register_type::<Enemy>()
+            .register_type::<Projectile>()
+            .register_type::<Shooting>()
+            .register_type::<Health>()
+            .register_type::<Collision>()
+            .register_type::<SpriteAsset>()
+            .register_type::<AnimatedSprite>()
+            .register_type::<Timeline>()
+            .register_type::<SoundEmitter>()
+            .register_type::<EntityId>()
+            .register_type::<EntityLabel>()
+            .register_type::<PrefabInstance>()
+            .register_type::<GameCamera>()
+            
+            // Field types that stored components use but Bevy does not register
+            .register_type::<Option<Vec2>>()
+            .register_type::<Option<Rect>>()
+            .register_type::<Option<EntityId>>()
+            .register_type::<SpriteFrame>()
+            .register_type::<Option<SpriteFrame>>()
+            .register_type::<SpriteClip>()
+            .register_type::<AnimationPlayback>()
+            .register_type::<AnimationFrameEvent>()
+            .register_type::<TweenTrack>()
+            .register_type::<TweenKeyframe>()
+            .register_type::<TweenProperty>()
+            .register_type::<TimelineRepeat>()
+            .register_type::<Easing>()
+            .register_type::<AudioBus>()
+            .register_type::<[f32; 2]>()
+            .register_type::<[f32; 4]>()
+            .register_type::<Vec<String>>()
+            .register_type::<Vec<SpriteClip>>()
+            .register_type::<Vec<AnimationFrameEvent>>()
+            .register_type::<Vec<TweenTrack>>()
+            .register_type::<Vec<TweenKeyframe>>()
             
             // Add events
             .add_event::<MouseWheel>()
+            .add_event::<systems::camera::CameraShake>()
+            .add_event::<systems::animation::AnimationEvent>()
+            .add_event::<systems::tween::TimelineCompleted>()
+            .add_event::<systems::audio::PlaySound>()
             
             // Add startup systems
             .add_systems(Startup, (
                 systems::setup_engine,
                 assets::load_default_assets,
+                scene::prefab::load_prefab_library,
             ))
             
             // Add update systems - Input and Camera
@@ -56,6 +113,9 @@ impl Plugin for GameEnginePlugin {
                 systems::input::camera_controls,
                 systems::input::handle_mouse_wheel_zoom,
                 systems::camera::camera_movement,
+                systems::camera::game_camera_follow.before(systems::camera::sync_game_view_camera),
+                systems::camera::game_camera_shake.before(systems::camera::sync_game_view_camera),
+                systems::camera::sync_game_view_camera,
                 systems::camera::update_mouse_world_position,
             ))
             
@@ -70,24 +130,46 @@ impl Plugin for GameEnginePlugin {
                 systems::gameplay::enemy_color_change,
                 systems::game_controls::game_controls_system,
                 systems::game_controls::handle_play_mode_transition,
+                systems::animation::animate_sprites.before(assets::apply_sprite_asset_textures),
+                systems::animation::advance_animation_preview,
+                systems::tween::play_timelines.before(systems::rendering::sync_sprite_asset_tint),
+                systems::tween::preview_timeline,
+                systems::audio::play_sounds.after(systems::gameplay::player_shooting).after(systems::gameplay::collision_detection),
+                systems::audio::play_sound_emitters,
+                systems::audio::update_playing_sounds,
             ))
             
             // Add update systems - Rendering and Editor
             .add_systems(Update, (
                 systems::rendering::render_grid_overlay,
                 systems::rendering::update_background_image,
-                systems::rendering::update_selection_visuals,
+                systems::rendering::draw_selection_outlines,
+                systems::rendering::draw_selection_marquee,
+                systems::rendering::draw_game_camera_frames,
+                systems::transform_gizmo::draw_transform_gizmo,
+                systems::transform_gizmo::transform_tool_shortcuts,
+                systems::rendering::sync_sprite_asset_tint,
                 systems::editor::editor_update,
                 systems::editor::debug_info_system,
                 systems::editor::entity_spawn_system,
+                systems::editor::edit_shortcuts,
+                systems::editor::sync_selection_markers,
+                systems::editor::scene_file_system,
+                systems::editor::track_unsaved_changes,
+                systems::editor::track_prefab_overrides,
             ))
             
             // Add update systems - Assets and UI
             .add_systems(Update, (
                 assets::handle_asset_imports,
+                assets::apply_sprite_asset_textures.after(assets::handle_asset_imports),
                 assets::load_background_image,
+                systems::editor::snapshot_inspected_components.before(ui::dockable_ui_system),
                 ui::dockable_ui_system,
+                systems::camera::resize_viewport_images.after(ui::dockable_ui_system),
                 ui::asset_browser::apply_asset_to_entity_system,
+                systems::audio::preview_asset_sound.after(ui::dockable_ui_system),
+                systems::console::collect_console_logs.before(ui::dockable_ui_system),
             ));
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 //! GameMaker Rust - Main application entry point
 
 use bevy::prelude::*;
+use bevy::log::LogPlugin;
 use bevy_egui::EguiPlugin;
 
 use gamemaker_rust::GameEnginePlugin;
@@ -15,6 +16,10 @@ fn main() {
                 ..default()
             }),
             ..default()
+        }).set(LogPlugin {
+            // Show log output in the editor's console tab as well
+            update_subscriber: Some(gamemaker_rust::systems::console::console_subscriber),
+            ..default()
         }))
         
         // Add egui plugin for UI
@@ -1,11 +1,14 @@
 //! Game resources and state management
 
 use bevy::prelude::*;
-use std::collections::{HashMap, hash_map::DefaultHasher};
+use bevy::render::render_resource::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages};
+use std::collections::{BTreeMap, HashMap, VecDeque, hash_map::DefaultHasher};
 use std::hash::{Hash, Hasher};
 use egui_dock::DockState;
 
-use crate::components::EntityType;
+use crate::assets::atlas::{AtlasSlicing, SpriteAtlas};
+use crate::components::{AnimationState, AudioBus, EntityId, EntityType, SpriteFrame, TimelineState};
+use crate::scene::{prefab_name, Prefab, Scene, SceneMetadata};
 
 /// Main game state
 #[derive(Resource, Default)]
@@ -18,8 +21,9 @@ pub struct GameState {
 
 #[derive(Resource, Default)]
 pub struct EditorSceneState {
-    pub saved_scene_data: Option<String>, // RON serialized scene data
+    pub saved_scene: Option<Scene>, // Snapshot taken when entering play mode
     pub temp_scene_data: Option<String>,  // Temporary save when entering play mode
+    pub saved_entities: HashMap<EntityId, Entity>, // Scene entities by id when entering play mode
 }
 
 /// Camera controller resource
@@ -44,17 +48,149 @@ impl Default for CameraController {
     }
 }
 
-/// Selected entity resource
+/// Selected entities resource
+///
+/// `entity` is the primary selection: the entity clicked last, which the inspector
+/// shows in full. `entities` is the whole selection in the order it was made and
+/// always contains the primary entity. Change the selection through the methods
+/// below so the two stay in step.
 #[derive(Resource, Default)]
 pub struct SelectedEntity {
     pub entity: Option<Entity>,
+    pub entities: Vec<Entity>,
+}
+
+impl SelectedEntity {
+    /// Whether an entity is part of the selection
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Select only this entity
+    pub fn select(&mut self, entity: Entity) {
+        self.entities.clear();
+        self.entities.push(entity);
+        self.entity = Some(entity);
+    }
+
+    /// Replace the selection; the last entity becomes the primary one
+    pub fn set(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.entities.clear();
+        for entity in entities {
+            if !self.entities.contains(&entity) {
+                self.entities.push(entity);
+            }
+        }
+        self.entity = self.entities.last().copied();
+    }
+
+    /// Add an entity to the selection and make it the primary one
+    pub fn add(&mut self, entity: Entity) {
+        if !self.entities.contains(&entity) {
+            self.entities.push(entity);
+        }
+        self.entity = Some(entity);
+    }
+
+    /// Add an entity to the selection, or drop it if it is already selected
+    pub fn toggle(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            self.retain(|&selected| selected != entity);
+        } else {
+            self.add(entity);
+        }
+    }
+
+    /// Keep only the selected entities for which `keep` returns true, e.g. to drop
+    /// despawned ones
+    pub fn retain(&mut self, keep: impl FnMut(&Entity) -> bool) {
+        self.entities.retain(keep);
+        if self.entity.is_some_and(|entity| !self.entities.contains(&entity)) {
+            self.entity = self.entities.last().copied();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.entity = None;
+    }
+}
+
+/// Reflected copies of the selected entity's components, refreshed every frame
+/// so the inspector can edit them without exclusive world access
+#[derive(Resource, Default)]
+pub struct InspectorSnapshot {
+    pub entity: Option<Entity>,
+    pub components: Vec<Box<dyn Reflect>>,
+    /// The same copies for the rest of a multiple selection, so edits can be
+    /// applied to every selected entity
+    pub others: Vec<(Entity, Vec<Box<dyn Reflect>>)>,
+}
+
+/// What the hierarchy filter text is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HierarchyFilterMode {
+    #[default]
+    Name,
+    Tag,
+    Component,
+}
+
+/// Hierarchy panel state kept between frames
+#[derive(Resource, Default)]
+pub struct HierarchyState {
+    pub filter: String,
+    pub filter_mode: HierarchyFilterMode,
+    /// Entity being renamed in place and the name typed so far
+    pub renaming: Option<(Entity, String)>,
 }
 
 /// Drag state for entity manipulation
 #[derive(Resource, Default)]
 pub struct DragState {
     pub dragging: bool,
-    pub drag_offset: Vec2,
+    /// Gizmo handle being dragged; dragging an entity itself uses the centre handle
+    pub handle: Option<GizmoHandle>,
+    /// Mouse position in world space when the drag began
+    pub drag_origin: Vec2,
+    /// Centre of the transform gizmo when the drag began, which rotation and
+    /// scaling work around
+    pub pivot: Vec2,
+    /// Each dragged entity with its local and world transform when the drag began,
+    /// used to transform the selection together and record the change
+    pub drag_starts: Vec<(Entity, Transform, Transform)>,
+    /// Corner of the box selection being dragged out, in world space
+    pub marquee_start: Option<Vec2>,
+}
+
+/// Tool the viewport gizmo edits the selection with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransformTool {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// Part of the transform gizmo that can be dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GizmoHandle {
+    /// Move freely
+    Center,
+    /// Move along one world axis
+    Axis(Vec2),
+    /// Rotate around the gizmo's centre
+    Ring,
+    /// Scale from the gizmo's centre by dragging the corner in this direction
+    Corner(Vec2),
 }
 
 /// Asset metadata for loaded assets
@@ -68,12 +204,45 @@ pub struct AssetMetadata {
     pub last_modified: String,
 }
 
+/// Prefabs read from the prefab directory, by file path
+#[derive(Resource, Default)]
+pub struct PrefabLibrary {
+    pub prefabs: BTreeMap<String, Prefab>,
+}
+
+impl PrefabLibrary {
+    pub fn get(&self, path: &str) -> Option<&Prefab> {
+        self.prefabs.get(path)
+    }
+}
+
+/// Sprite sheet atlases read from the assets folder, by the path of their image
+#[derive(Resource, Default)]
+pub struct AtlasLibrary {
+    pub atlases: HashMap<String, SpriteAtlas>,
+}
+
+impl AtlasLibrary {
+    pub fn get(&self, image_path: &str) -> Option<&SpriteAtlas> {
+        self.atlases.get(image_path)
+    }
+    
+    /// Rectangle of a frame of an image's atlas, if the image has an atlas with that frame
+    pub fn frame_rect(&self, image_path: &str, frame: &SpriteFrame, image_size: UVec2) -> Option<URect> {
+        self.get(image_path)?.frame_rect(frame, image_size)
+    }
+}
+
 /// Asset registry for managing loaded assets
 #[derive(Resource, Default)]
 pub struct AssetRegistry {
     pub loaded_images: HashMap<String, Handle<Image>>,
     pub asset_metadata: HashMap<String, AssetMetadata>,
     pub loading_assets: HashMap<String, Handle<Image>>,
+    pub loaded_sounds: HashMap<String, Handle<AudioSource>>,
+    pub loading_sounds: HashMap<String, Handle<AudioSource>>,
+    /// Texture shown in place of images that are missing or failed to load
+    pub missing_texture: Handle<Image>,
 }
 
 impl AssetRegistry {
@@ -82,6 +251,9 @@ impl AssetRegistry {
             loaded_images: HashMap::new(),
             asset_metadata: HashMap::new(),
             loading_assets: HashMap::new(),
+            loaded_sounds: HashMap::new(),
+            loading_sounds: HashMap::new(),
+            missing_texture: Handle::default(),
         }
     }
     
@@ -90,6 +262,11 @@ impl AssetRegistry {
         self.asset_metadata.insert(path, metadata);
     }
     
+    pub fn register_sound(&mut self, path: String, handle: Handle<AudioSource>, metadata: AssetMetadata) {
+        self.loaded_sounds.insert(path.clone(), handle);
+        self.asset_metadata.insert(path, metadata);
+    }
+    
     pub fn get_image(&self, path: &str) -> Option<&Handle<Image>> {
         self.loaded_images.get(path)
     }
@@ -99,23 +276,38 @@ impl AssetRegistry {
     }
     
     pub fn is_loaded(&self, path: &str) -> bool {
-        self.loaded_images.contains_key(path)
+        self.loaded_images.contains_key(path) || self.loaded_sounds.contains_key(path)
     }
     
     pub fn is_loading(&self, path: &str) -> bool {
-        self.loading_assets.contains_key(path)
+        self.loading_assets.contains_key(path) || self.loading_sounds.contains_key(path)
+    }
+    
+    /// Handle of an image that is loaded or still loading
+    pub fn get_texture(&self, path: &str) -> Option<&Handle<Image>> {
+        self.loaded_images.get(path).or_else(|| self.loading_assets.get(path))
+    }
+    
+    /// Handle of a sound that is loaded or still loading
+    pub fn get_sound(&self, path: &str) -> Option<&Handle<AudioSource>> {
+        self.loaded_sounds.get(path).or_else(|| self.loading_sounds.get(path))
     }
     
     pub fn start_loading(&mut self, path: String, handle: Handle<Image>) {
         self.loading_assets.insert(path, handle);
     }
     
+    pub fn start_loading_sound(&mut self, path: String, handle: Handle<AudioSource>) {
+        self.loading_sounds.insert(path, handle);
+    }
+    
     pub fn finish_loading(&mut self, path: &str) {
         self.loading_assets.remove(path);
+        self.loading_sounds.remove(path);
     }
     
     pub fn get_loaded_paths(&self) -> Vec<String> {
-        self.loaded_images.keys().cloned().collect()
+        self.loaded_images.keys().chain(self.loaded_sounds.keys()).cloned().collect()
     }
 }
 
@@ -135,8 +327,8 @@ impl AssetImporter {
     }
     
     pub fn start_import(&mut self, path: String) {
-        if let Some(index) = self.import_queue.iter().position(|p| p == &path) {
-            self.import_queue.remove(index);
+        self.import_queue.retain(|p| p != &path);
+        if !self.pending_imports.contains(&path) {
             self.pending_imports.push(path);
         }
     }
@@ -199,6 +391,7 @@ pub struct EditorState {
     pub show_entity_spawner: bool,
     pub mouse_world_position: Vec2,
     pub window_layout_mode: WindowLayoutMode,
+    pub transform_tool: TransformTool,
 }
 
 impl Default for EditorState {
@@ -215,10 +408,96 @@ impl Default for EditorState {
             show_entity_spawner: false,
             mouse_world_position: Vec2::ZERO,
             window_layout_mode: WindowLayoutMode::default(),
+            transform_tool: TransformTool::default(),
+        }
+    }
+}
+
+/// Which view of the world a viewport tab shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewportKind {
+    /// The editor's view of the scene, with the grid and gizmos
+    Scene,
+    /// What the player sees, without editor overlays
+    Game,
+}
+
+/// Image a viewport camera renders into, and where its tab shows it
+pub struct ViewportTarget {
+    pub image: Handle<Image>,
+    /// Screen area of the tab in egui points, when it was shown last frame
+    pub rect: Option<Rect>,
+}
+
+/// Render targets of the viewport tabs
+///
+/// Cameras render into images instead of the window, and the viewport tabs show
+/// those images, so docked panels sit beside the game rather than over it.
+#[derive(Resource)]
+pub struct EditorViewports {
+    pub scene: ViewportTarget,
+    pub game: ViewportTarget,
+}
+
+impl EditorViewports {
+    pub fn get(&self, kind: ViewportKind) -> &ViewportTarget {
+        match kind {
+            ViewportKind::Scene => &self.scene,
+            ViewportKind::Game => &self.game,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ViewportKind) -> &mut ViewportTarget {
+        match kind {
+            ViewportKind::Scene => &mut self.scene,
+            ViewportKind::Game => &mut self.game,
+        }
+    }
+}
+
+impl FromWorld for EditorViewports {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        // Resized to fit the tabs once they are laid out
+        let size = UVec2::new(1280, 720);
+        Self {
+            scene: ViewportTarget {
+                image: images.add(viewport_image(size)),
+                rect: None,
+            },
+            game: ViewportTarget {
+                image: images.add(viewport_image(size)),
+                rect: None,
+            },
         }
     }
 }
 
+/// Blank image a camera can render into and egui can draw
+fn viewport_image(size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("viewport"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // Fills the image with zeroes
+    image.resize(size);
+    image
+}
+
 /// Grid settings resource
 #[derive(Resource)]
 pub struct GridSettings {
@@ -227,6 +506,11 @@ pub struct GridSettings {
     pub color: Color,
     pub opacity: f32,
     pub thickness: f32,
+    /// Snap dragged positions to multiples of `spacing`
+    pub snap_enabled: bool,
+    /// Snap gizmo rotations to multiples of `rotation_snap_degrees`
+    pub rotation_snap_enabled: bool,
+    pub rotation_snap_degrees: f32,
 }
 
 impl Default for GridSettings {
@@ -237,6 +521,9 @@ impl Default for GridSettings {
             color: Color::WHITE,
             opacity: 0.3,
             thickness: 1.0,
+            snap_enabled: false,
+            rotation_snap_enabled: false,
+            rotation_snap_degrees: 15.0,
         }
     }
 }
@@ -258,22 +545,25 @@ impl Hash for GridSettings {
 pub struct GridState {
     pub last_camera_position: Vec2,
     pub last_zoom: f32,
+    pub last_viewport_size: Vec2,
     pub last_settings_hash: u64,
 }
 
 impl GridState {
-    pub fn needs_update(&mut self, camera_pos: Vec2, zoom: f32, settings: &GridSettings) -> bool {
+    pub fn needs_update(&mut self, camera_pos: Vec2, zoom: f32, viewport_size: Vec2, settings: &GridSettings) -> bool {
         let mut hasher = DefaultHasher::new();
         settings.hash(&mut hasher);
         let current_hash = hasher.finish();
         
         let position_changed = (camera_pos - self.last_camera_position).length() > 10.0;
         let zoom_changed = (zoom - self.last_zoom).abs() > 0.01;
+        let viewport_changed = viewport_size != self.last_viewport_size;
         let settings_changed = current_hash != self.last_settings_hash;
         
-        if position_changed || zoom_changed || settings_changed {
+        if position_changed || zoom_changed || viewport_changed || settings_changed {
             self.last_camera_position = camera_pos;
             self.last_zoom = zoom;
+            self.last_viewport_size = viewport_size;
             self.last_settings_hash = current_hash;
             true
         } else {
@@ -310,17 +600,151 @@ pub struct AssetBrowserState {
     pub filter_text: String,
     pub show_import_dialog: bool,
     pub import_path: String,
+    /// Set by the browser to apply the selected asset to the selected entity
+    pub apply_to_selected: bool,
+    /// Slicing being edited for the selected asset, before it is saved as its atlas
+    pub atlas_draft: Option<AtlasSlicing>,
+    /// Atlas frame picked in the slicing editor, applied instead of the whole image
+    pub selected_frame: Option<SpriteFrame>,
+    /// Result of the last atlas save, shown in the slicing editor
+    pub atlas_message: Option<String>,
+    /// Sound the browser is previewing, played by the audio preview system
+    pub previewed_sound: Option<String>,
+}
+
+/// Volume of each audio bus, from 0 to 1
+///
+/// A sound plays at its own volume times its bus volume times the master volume.
+/// Changes apply to sounds that are already playing.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Volume a bus plays at, including the master volume
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        let bus_volume = match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Sfx => self.sfx,
+        };
+        self.master * bus_volume
+    }
+}
+
+/// Sounds the built-in gameplay plays, by their path in the assets folder
+///
+/// Paths whose file doesn't exist are skipped, so a game without these sounds
+/// simply stays quiet.
+#[derive(Resource, Clone, Debug)]
+pub struct GameSounds {
+    pub shoot: String,
+    pub hit: String,
+    pub destroy: String,
+}
+
+impl Default for GameSounds {
+    fn default() -> Self {
+        Self {
+            shoot: "sounds/shoot.wav".to_string(),
+            hit: "sounds/hit.wav".to_string(),
+            destroy: "sounds/destroy.wav".to_string(),
+        }
+    }
+}
+
+/// Playback of the animation preview tab, separate from the entity's own playback
+#[derive(Resource, Default)]
+pub struct AnimationPreview {
+    /// Entity being previewed
+    pub entity: Option<Entity>,
+    pub state: AnimationState,
+    pub playing: bool,
+}
+
+/// Playback of the timeline tab, which poses the entity in edit mode
+///
+/// The entity's transform and sprite color are kept in `original` while the
+/// preview is active, and put back once it stops or moves to another entity.
+/// Saving, copying, prefabs and edits of the entity end the preview first.
+#[derive(Resource, Default)]
+pub struct TimelinePreview {
+    /// Entity being previewed
+    pub entity: Option<Entity>,
+    pub state: TimelineState,
+    pub playing: bool,
+    /// Whether the entity is posed at the preview's time
+    pub active: bool,
+    /// The entity's transform and sprite color from before the preview
+    pub original: Option<(Entity, Transform, Color)>,
+}
+
+impl TimelinePreview {
+    /// Rewind and stop the preview, returning the pose the entity has to be put back to
+    pub fn stop(&mut self) -> Option<(Entity, Transform, Color)> {
+        self.state = TimelineState::default();
+        self.active = false;
+        self.playing = false;
+        self.original.take()
+    }
+}
+
+/// Maximum number of entries kept in the recent scenes list
+pub const MAX_RECENT_SCENES: usize = 10;
+
+/// File operation on the current scene, queued by the UI and carried out by the scene file system
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneOperation {
+    New,
+    Save(String),
+    Load(String),
+    /// Rewrite a scene file in the format of the destination's extension
+    Convert { from: String, to: String },
+}
+
+impl SceneOperation {
+    /// Whether the operation replaces the current scene, discarding unsaved work
+    pub fn replaces_scene(&self) -> bool {
+        matches!(self, SceneOperation::New | SceneOperation::Load(_))
+    }
 }
 
 /// Scene manager resource
 #[derive(Resource)]
 pub struct SceneManager {
+    /// Next stable [`EntityId`] to hand out; kept above every ID in the open scene
     pub next_id: u32,
     pub save_path: String,
     pub spawn_entity_type: EntityType,
+    /// Prefab file to spawn instead of `spawn_entity_type`
+    pub spawn_prefab: Option<String>,
     pub spawn_position: Vec2,
     pub spawn_z: f32,
     pub should_spawn: bool,
+    pub pending_operations: Vec<SceneOperation>,
+    /// Operation waiting for the user to decide what to do with unsaved changes
+    pub awaiting_confirmation: Option<SceneOperation>,
+    pub recent_scenes: Vec<String>,
+    pub status_message: Option<String>,
+    /// Metadata of the open scene, written back out when it is saved
+    pub metadata: SceneMetadata,
 }
 
 impl Default for SceneManager {
@@ -329,17 +753,86 @@ impl Default for SceneManager {
             next_id: 0,
             save_path: "scenes/default_scene.ron".to_string(),
             spawn_entity_type: EntityType::Player,
+            spawn_prefab: None,
             spawn_position: Vec2::ZERO,
             spawn_z: 0.0,
             should_spawn: false,
+            pending_operations: Vec::new(),
+            awaiting_confirmation: None,
+            recent_scenes: Vec::new(),
+            status_message: None,
+            metadata: SceneMetadata::default(),
+        }
+    }
+}
+
+impl SceneManager {
+    /// Name of what the spawner will create: a prefab or a built-in type
+    pub fn spawn_template_name(&self) -> String {
+        match &self.spawn_prefab {
+            Some(path) => prefab_name(path).to_string(),
+            None => format!("{:?}", self.spawn_entity_type),
         }
     }
+
+    /// Hand out a new stable entity ID
+    pub fn allocate_id(&mut self) -> EntityId {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Move a scene to the top of the recent scenes list
+    pub fn add_recent_scene(&mut self, path: &str) {
+        self.recent_scenes.retain(|recent| recent != path);
+        self.recent_scenes.insert(0, path.to_string());
+        self.recent_scenes.truncate(MAX_RECENT_SCENES);
+    }
+}
+
+/// Maximum number of log lines kept by the console
+pub const MAX_CONSOLE_ENTRIES: usize = 1000;
+
+/// A log line shown in the console tab
+#[derive(Clone, Debug)]
+pub struct ConsoleEntry {
+    /// Local time the line was logged, as `HH:MM:SS`
+    pub time: String,
+    pub level: bevy::log::Level,
+    /// Module that logged the line
+    pub target: String,
+    pub message: String,
+}
+
+/// Log lines shown in the console tab, oldest first
+#[derive(Resource, Default)]
+pub struct ConsoleLog {
+    pub entries: VecDeque<ConsoleEntry>,
+}
+
+impl ConsoleLog {
+    /// Add a line, dropping the oldest once the console is full
+    pub fn push(&mut self, entry: ConsoleEntry) {
+        if self.entries.len() >= MAX_CONSOLE_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// The log as plain text, one line per entry
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{} [{}] {}: {}\n", entry.time, entry.level, entry.target, entry.message))
+            .collect()
+    }
 }
 
 // Dockable UI System
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EditorTab {
     Viewport,
+    GameView,
     Inspector,
     Hierarchy,
     AssetBrowser,
@@ -350,12 +843,15 @@ pub enum EditorTab {
     AssetManager,
     GridSettings,
     BackgroundSettings,
+    AnimationPreview,
+    Timeline,
 }
 
 impl std::fmt::Display for EditorTab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             EditorTab::Viewport => write!(f, "Viewport"),
+            EditorTab::GameView => write!(f, "Game View"),
             EditorTab::Inspector => write!(f, "Inspector"),
             EditorTab::Hierarchy => write!(f, "Hierarchy"),
             EditorTab::AssetBrowser => write!(f, "Asset Browser"),
@@ -366,6 +862,8 @@ impl std::fmt::Display for EditorTab {
             EditorTab::AssetManager => write!(f, "Asset Manager"),
             EditorTab::GridSettings => write!(f, "Grid Settings"),
             EditorTab::BackgroundSettings => write!(f, "Background Settings"),
+            EditorTab::AnimationPreview => write!(f, "Animation"),
+            EditorTab::Timeline => write!(f, "Timeline"),
         }
     }
 }
@@ -387,20 +885,22 @@ impl DockTree {
         use egui_dock::*;
         
         // Create the main dock state starting with viewport as the center
-        let mut dock_state = DockState::new(vec![EditorTab::Viewport]);
+        let mut dock_state = DockState::new(vec![EditorTab::Viewport, EditorTab::GameView]);
         
         // Add essential panels in a logical order
         // Left side: Hierarchy and Entity Spawner
         dock_state.push_to_focused_leaf(EditorTab::Hierarchy);
         dock_state.push_to_focused_leaf(EditorTab::EntitySpawner);
         
-        // Right side: Inspector and Asset Browser
+        // Right side: Inspector, Asset Browser and Animation
         dock_state.push_to_focused_leaf(EditorTab::Inspector);
         dock_state.push_to_focused_leaf(EditorTab::AssetBrowser);
+        dock_state.push_to_focused_leaf(EditorTab::AnimationPreview);
         
-        // Bottom: Console and Game Controls
+        // Bottom: Console, Game Controls and Timeline
         dock_state.push_to_focused_leaf(EditorTab::Console);
         dock_state.push_to_focused_leaf(EditorTab::GameControls);
+        dock_state.push_to_focused_leaf(EditorTab::Timeline);
         
         // Additional panels as floating tabs
         dock_state.push_to_focused_leaf(EditorTab::SceneSettings);
@@ -415,7 +915,7 @@ impl DockTree {
     pub fn create_minimal_layout() -> Self {
         use egui_dock::*;
         
-        let mut dock_state = DockState::new(vec![EditorTab::Viewport]);
+        let mut dock_state = DockState::new(vec![EditorTab::Viewport, EditorTab::GameView]);
         dock_state.push_to_focused_leaf(EditorTab::Hierarchy);
         dock_state.push_to_focused_leaf(EditorTab::Inspector);
         dock_state.push_to_focused_leaf(EditorTab::Console);
@@ -427,7 +927,7 @@ impl DockTree {
     pub fn create_debug_layout() -> Self {
         use egui_dock::*;
         
-        let mut dock_state = DockState::new(vec![EditorTab::Viewport]);
+        let mut dock_state = DockState::new(vec![EditorTab::Viewport, EditorTab::GameView]);
         dock_state.push_to_focused_leaf(EditorTab::Hierarchy);
         dock_state.push_to_focused_leaf(EditorTab::Inspector);
         dock_state.push_to_focused_leaf(EditorTab::Console);
@@ -441,10 +941,12 @@ impl DockTree {
     pub fn create_scene_design_layout() -> Self {
         use egui_dock::*;
         
-        let mut dock_state = DockState::new(vec![EditorTab::Viewport]);
+        let mut dock_state = DockState::new(vec![EditorTab::Viewport, EditorTab::GameView]);
         dock_state.push_to_focused_leaf(EditorTab::Hierarchy);
         dock_state.push_to_focused_leaf(EditorTab::AssetBrowser);
         dock_state.push_to_focused_leaf(EditorTab::Inspector);
+        dock_state.push_to_focused_leaf(EditorTab::AnimationPreview);
+        dock_state.push_to_focused_leaf(EditorTab::Timeline);
         dock_state.push_to_focused_leaf(EditorTab::SceneSettings);
         dock_state.push_to_focused_leaf(EditorTab::GridSettings);
         dock_state.push_to_focused_leaf(EditorTab::BackgroundSettings);
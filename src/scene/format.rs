@@ -0,0 +1,367 @@
+//! On-disk scene formats
+//!
+//! Scenes can be stored as pretty RON for hand editing, JSON for external tools,
+//! or compact bincode for shipped builds. The format is picked from the file
+//! extension. RON files are upgraded from every older layout. JSON and binary
+//! scenes were introduced with format version 4; JSON files from then on are
+//! upgraded, while binary files have no field names to fall back on and must
+//! match the engine's version.
+
+use bevy::log::info;
+use bevy::reflect::{TypeRegistry, TypeRegistryArc};
+use bincode::Options;
+use serde::de::DeserializeSeed;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use super::migration::{self, parse_scene, SceneFormatError};
+use super::serialization::{SceneDeserializer, SceneSerializer};
+use super::Scene;
+
+/// File formats a scene can be saved in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneFileFormat {
+    Ron,
+    Json,
+    Binary,
+}
+
+impl SceneFileFormat {
+    pub const ALL: [SceneFileFormat; 3] = [SceneFileFormat::Ron, SceneFileFormat::Json, SceneFileFormat::Binary];
+
+    /// Pick the format from a file's extension, defaulting to RON
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("json") => SceneFileFormat::Json,
+            Some("bin") => SceneFileFormat::Binary,
+            _ => SceneFileFormat::Ron,
+        }
+    }
+
+    /// File extension written for this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            SceneFileFormat::Ron => "ron",
+            SceneFileFormat::Json => "json",
+            SceneFileFormat::Binary => "bin",
+        }
+    }
+
+    /// Name shown in file dialogs and menus
+    pub fn label(self) -> &'static str {
+        match self {
+            SceneFileFormat::Ron => "RON Scene",
+            SceneFileFormat::Json => "JSON Scene",
+            SceneFileFormat::Binary => "Binary Scene",
+        }
+    }
+}
+
+/// Encode a scene in the given format
+pub fn encode_scene(
+    scene: &Scene,
+    type_registry: &TypeRegistryArc,
+    format: SceneFileFormat,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let serializer = SceneSerializer::new(scene, type_registry);
+
+    let bytes = match format {
+        SceneFileFormat::Ron => ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())?.into_bytes(),
+        SceneFileFormat::Json => serde_json::to_vec_pretty(&serializer)?,
+        SceneFileFormat::Binary => bincode::DefaultOptions::new().serialize(&serializer)?,
+    };
+
+    Ok(bytes)
+}
+
+/// Decode a scene stored in the given format, upgrading older layouts where possible
+pub fn decode_scene(
+    bytes: &[u8],
+    type_registry: &TypeRegistry,
+    format: SceneFileFormat,
+) -> Result<Scene, Box<dyn std::error::Error>> {
+    let seed = SceneDeserializer { registry: type_registry };
+
+    let scene = match format {
+        SceneFileFormat::Ron => parse_scene(std::str::from_utf8(bytes)?, type_registry)?,
+        SceneFileFormat::Json => {
+            let header: VersionHeader = serde_json::from_slice(bytes)?;
+            check_version(header.format_version, FIRST_JSON_FORMAT)?;
+
+            let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+            let mut scene = seed.deserialize(&mut deserializer)?;
+            deserializer.end()?;
+
+            // Fields added since are optional, so only the number needs upgrading
+            scene.format_version = migration::CURRENT_SCENE_FORMAT;
+            scene
+        }
+        SceneFileFormat::Binary => {
+            // The version is the first field, so read it alone before the rest
+            let version: u32 = bincode::DefaultOptions::new().allow_trailing_bytes().deserialize(bytes)?;
+            check_version(version, migration::CURRENT_SCENE_FORMAT)?;
+
+            bincode::DefaultOptions::new().deserialize_seed(seed, bytes)?
+        }
+    };
+
+    Ok(scene)
+}
+
+/// Rewrite a scene file in the format matching the output path's extension
+pub fn convert_scene_file(
+    input_path: &str,
+    output_path: &str,
+    type_registry: &TypeRegistryArc,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scene = read_scene_file(input_path, &type_registry.read())?;
+    write_scene_file(&scene, output_path, type_registry)?;
+
+    info!("Converted scene {} to {}", input_path, output_path);
+    Ok(())
+}
+
+/// Read a scene file in the format given by its extension
+pub fn read_scene_file(path: &str, type_registry: &TypeRegistry) -> Result<Scene, Box<dyn std::error::Error>> {
+    let bytes = fs::read(path)?;
+    decode_scene(&bytes, type_registry, SceneFileFormat::from_path(path))
+}
+
+/// Write a scene file in the format given by its extension
+pub fn write_scene_file(
+    scene: &Scene,
+    path: &str,
+    type_registry: &TypeRegistryArc,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = encode_scene(scene, type_registry, SceneFileFormat::from_path(path))?;
+
+    // Ensure directory exists
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct VersionHeader {
+    format_version: u32,
+}
+
+/// Oldest format version that was ever written as JSON
+const FIRST_JSON_FORMAT: u32 = 4;
+
+/// Check that a JSON or binary file's version is between `oldest` and the current one
+fn check_version(version: u32, oldest: u32) -> Result<(), SceneFormatError> {
+    if version > migration::CURRENT_SCENE_FORMAT {
+        Err(SceneFormatError::NewerThanEngine { version })
+    } else if version < oldest {
+        Err(SceneFormatError::NotUpgradable { version })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::sprite::Anchor;
+
+    use super::*;
+    use crate::components::*;
+    use crate::resources::SceneManager;
+    use crate::scene::{restore_scene, snapshot_scene, Scene};
+
+    /// World with the scene types registered the way the engine plugin registers them
+    fn scene_world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Transform>();
+            registry.register::<Sprite>();
+            registry.register::<Anchor>();
+            registry.register::<Visibility>();
+            registry.register::<Color>();
+            registry.register::<Vec2>();
+            registry.register::<Vec3>();
+            registry.register::<Quat>();
+            registry.register::<Rect>();
+            registry.register::<Option<Vec2>>();
+            registry.register::<Option<Rect>>();
+            registry.register::<Option<String>>();
+            registry.register::<Player>();
+            registry.register::<Enemy>();
+            registry.register::<Projectile>();
+            registry.register::<Shooting>();
+            registry.register::<Health>();
+            registry.register::<Collision>();
+            registry.register::<SpriteAsset>();
+            registry.register::<SpriteFrame>();
+            registry.register::<Option<SpriteFrame>>();
+            registry.register::<EntityId>();
+            registry.register::<EntityLabel>();
+            registry.register::<Vec<String>>();
+            registry.register::<[f32; 2]>();
+            registry.register::<[f32; 4]>();
+        }
+
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.init_resource::<SceneManager>();
+        world
+    }
+
+    /// A player with a sprite-asset child, an enemy and a projectile in flight
+    fn spawn_test_scene(world: &mut World) {
+        let child = world
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(0.2, 0.4, 0.6, 0.8),
+                        flip_x: true,
+                        custom_size: Some(Vec2::new(12.0, 8.0)),
+                        anchor: Anchor::BottomCenter,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(10.0, 5.0, 1.0),
+                    ..default()
+                },
+                SpriteAsset {
+                    asset_path: Some("sprites/sword.png".to_string()),
+                    tint_color: [0.2, 0.4, 0.6, 0.8],
+                    scale: [1.5, 0.5],
+                    frame: Some(SpriteFrame::Name("swing_2".to_string())),
+                },
+                EntityId(3),
+                EntityLabel {
+                    name: "Sword".to_string(),
+                    tags: vec!["weapon".to_string()],
+                },
+            ))
+            .id();
+
+        world
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::BLUE,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-40.0, 20.0, 0.0)
+                        .with_rotation(Quat::from_rotation_z(0.5))
+                        .with_scale(Vec3::splat(50.0)),
+                    ..default()
+                },
+                Player,
+                Shooting { cooldown: 0.25 },
+                Health { current: 80.0, max: 100.0 },
+                Collision { radius: 25.0 },
+                EntityId(0),
+                EntityLabel::new("Player"),
+            ))
+            .add_child(child);
+
+        world.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::RED,
+                    ..default()
+                },
+                transform: Transform::from_xyz(100.0, 150.0, 0.0).with_scale(Vec3::splat(40.0)),
+                ..default()
+            },
+            Enemy,
+            Health { current: 50.0, max: 50.0 },
+            Collision { radius: 20.0 },
+            SpriteAsset {
+                asset_path: Some("sprites/enemy.png".to_string()),
+                frame: Some(SpriteFrame::Index(4)),
+                ..default()
+            },
+            EntityId(1),
+            EntityLabel::new("Enemy"),
+        ));
+
+        world.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::YELLOW,
+                    ..default()
+                },
+                transform: Transform::from_xyz(-40.0, 60.0, 0.0).with_scale(Vec3::new(5.0, 15.0, 1.0)),
+                ..default()
+            },
+            Projectile { velocity: Vec2::new(30.0, 400.0) },
+            Collision { radius: 5.0 },
+            EntityId(2),
+        ));
+    }
+
+    fn encode(world: &mut World, format: SceneFileFormat) -> Vec<u8> {
+        let scene = snapshot_scene(world);
+        encode_scene(&scene, &world.resource::<AppTypeRegistry>().0, format).unwrap()
+    }
+
+    fn decode(world: &World, bytes: &[u8], format: SceneFileFormat) -> Scene {
+        decode_scene(bytes, &world.resource::<AppTypeRegistry>().read(), format).unwrap()
+    }
+
+    #[test]
+    fn save_load_save_is_identical() {
+        for format in SceneFileFormat::ALL {
+            let mut world = scene_world();
+            spawn_test_scene(&mut world);
+
+            let saved = encode(&mut world, format);
+            let scene = decode(&world, &saved, format);
+            restore_scene(&mut world, &scene);
+            let resaved = encode(&mut world, format);
+
+            assert_eq!(saved, resaved, "{:?} scene changed on a save/load/save round trip", format);
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_sprite_assets_and_projectiles() {
+        for format in SceneFileFormat::ALL {
+            let mut world = scene_world();
+            spawn_test_scene(&mut world);
+
+            let saved = encode(&mut world, format);
+            let scene = decode(&world, &saved, format);
+            restore_scene(&mut world, &scene);
+
+            let mut projectiles = world.query::<(&Projectile, &Transform)>();
+            let (projectile, transform) = projectiles.single(&world);
+            assert_eq!(projectile.velocity, Vec2::new(30.0, 400.0), "{:?}", format);
+            assert_eq!(transform.scale, Vec3::new(5.0, 15.0, 1.0), "{:?}", format);
+
+            let mut sprite_assets = world.query::<(&SpriteAsset, &EntityId, Option<&Parent>)>();
+            let mut sprite_assets: Vec<_> = sprite_assets
+                .iter(&world)
+                .map(|(sprite_asset, id, parent)| (id.0, sprite_asset.clone(), parent.is_some()))
+                .collect();
+            sprite_assets.sort_by_key(|(id, ..)| *id);
+            assert_eq!(sprite_assets.len(), 2, "{:?}", format);
+
+            let (_, enemy_asset, _) = &sprite_assets[0];
+            assert_eq!(enemy_asset.asset_path.as_deref(), Some("sprites/enemy.png"));
+            assert_eq!(enemy_asset.frame, Some(SpriteFrame::Index(4)));
+
+            let (_, child_asset, has_parent) = &sprite_assets[1];
+            assert!(has_parent, "{:?} child lost its parent", format);
+            assert_eq!(child_asset.asset_path.as_deref(), Some("sprites/sword.png"));
+            assert_eq!(child_asset.tint_color, [0.2, 0.4, 0.6, 0.8]);
+            assert_eq!(child_asset.scale, [1.5, 0.5]);
+            assert_eq!(child_asset.frame, Some(SpriteFrame::Name("swing_2".to_string())));
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Scene format versions and the migrations that upgrade older scene files
+//!
+//! Every released layout of the scene file keeps a frozen copy of its types here.
+//! Loading detects the version of a file, parses it with the matching types and
+//! walks it through each migration until it reaches [`CURRENT_SCENE_FORMAT`].
+//!
+//! Versions:
+//! - 1: the original layout with `id`, `name`, `color` and `size` per entity
+//! - 2: entities as `SerializableEntity` plus `metadata`, without a format number
+//! - 3: adds the top-level `format_version`
+//! - 4: entities as lists of reflected components instead of a fixed set of fields
+//! - 5: entities carry their child entities, with transforms relative to the parent
+
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistry;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+use crate::components::*;
+use super::serialization::{from_ron_with_seed, SceneDeserializer};
+use super::{Scene, SceneMetadata, SerializableEntity};
+
+/// Scene format written by this engine
+pub const CURRENT_SCENE_FORMAT: u32 = 5;
+
+/// Errors for scene files this engine cannot upgrade
+#[derive(Debug)]
+pub enum SceneFormatError {
+    /// The file was written by a newer engine
+    NewerThanEngine { version: u32 },
+    /// The file is in an older layout that can only be upgraded from RON
+    NotUpgradable { version: u32 },
+}
+
+impl fmt::Display for SceneFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFormatError::NewerThanEngine { version } => write!(
+                f,
+                "scene format version {} is newer than this engine supports (up to {}); update the engine to open it",
+                version, CURRENT_SCENE_FORMAT
+            ),
+            SceneFormatError::NotUpgradable { version } => write!(
+                f,
+                "scene format version {} can only be upgraded from RON files; convert it from a RON copy instead",
+                version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneFormatError {}
+
+/// Parse scene RON of any supported format version, upgrading it to the current format
+pub fn parse_scene(ron_string: &str, type_registry: &TypeRegistry) -> Result<Scene, Box<dyn std::error::Error>> {
+    let version = detect_version(ron_string)?;
+
+    let scene = match version {
+        1 => migrate_v4_to_v5(migrate_v3_to_v4(migrate_v2_to_v3(migrate_v1_to_v2(ron::de::from_str(ron_string)?)))),
+        2 => migrate_v4_to_v5(migrate_v3_to_v4(migrate_v2_to_v3(ron::de::from_str(ron_string)?))),
+        3 => migrate_v4_to_v5(migrate_v3_to_v4(ron::de::from_str(ron_string)?)),
+        // Version 4 entities have no `children` field, which the current deserializer defaults
+        4 => migrate_v4_to_v5(from_ron_with_seed(ron_string, SceneDeserializer { registry: type_registry })?),
+        CURRENT_SCENE_FORMAT => from_ron_with_seed(ron_string, SceneDeserializer { registry: type_registry })?,
+        _ => return Err(Box::new(SceneFormatError::NewerThanEngine { version })),
+    };
+
+    if version != CURRENT_SCENE_FORMAT {
+        info!("Upgraded scene from format version {} to {}", version, CURRENT_SCENE_FORMAT);
+    }
+
+    Ok(scene)
+}
+
+/// Parse entities copied out of a version 2 or 3 scene file, as a list or a single entity
+pub fn parse_legacy_entities(text: &str) -> Option<Vec<SerializableEntity>> {
+    let entities = ron::de::from_str::<Vec<EntityV2>>(text)
+        .or_else(|_| ron::de::from_str::<EntityV2>(text).map(|entity| vec![entity]))
+        .ok()?;
+
+    Some(entities.into_iter().map(EntityV2::into_components).collect())
+}
+
+/// Work out which format version a scene file was written in
+fn detect_version(ron_string: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    let header: SceneHeader = ron::de::from_str(ron_string)?;
+
+    let version = if header.format_version > 0 {
+        header.format_version
+    } else if header.metadata.0 {
+        2
+    } else {
+        1
+    };
+
+    Ok(version)
+}
+
+/// Just enough of any scene file to identify its version
+#[derive(Deserialize)]
+struct SceneHeader {
+    #[serde(default)]
+    format_version: u32,
+    #[serde(default)]
+    metadata: FieldPresent,
+}
+
+/// Records whether a field was present, ignoring its contents
+#[derive(Default)]
+struct FieldPresent(bool);
+
+impl<'de> Deserialize<'de> for FieldPresent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        IgnoredAny::deserialize(deserializer)?;
+        Ok(FieldPresent(true))
+    }
+}
+
+// Version 1
+
+#[derive(Deserialize)]
+struct SceneV1 {
+    entities: Vec<EntityV1>,
+}
+
+#[derive(Deserialize)]
+struct EntityV1 {
+    id: u32,
+    name: String,
+    entity_type: EntityType,
+    transform: PositionV1,
+    color: ColorV1,
+    size: SizeV1,
+    texture_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PositionV1 {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[derive(Deserialize)]
+struct ColorV1 {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+#[derive(Deserialize)]
+struct SizeV1 {
+    width: f32,
+    height: f32,
+}
+
+// Version 2
+
+#[derive(Deserialize)]
+struct SceneV2 {
+    entities: Vec<EntityV2>,
+    metadata: SceneMetadata,
+}
+
+#[derive(Deserialize)]
+struct EntityV2 {
+    entity_type: EntityType,
+    transform: TransformV2,
+    health: Option<(f32, f32)>,
+    collision_radius: Option<f32>,
+    sprite_asset: Option<SpriteAsset>,
+    /// Version 1 ID and name, which version 2 files dropped
+    #[serde(skip)]
+    id: Option<u32>,
+    #[serde(skip)]
+    name: Option<String>,
+}
+
+/// Stored scale was never read back; sprites were sized from the sprite asset
+#[derive(Deserialize)]
+struct TransformV2 {
+    x: f32,
+    y: f32,
+    z: f32,
+    rotation: f32,
+}
+
+// Version 3
+
+#[derive(Deserialize)]
+struct SceneV3 {
+    entities: Vec<EntityV2>,
+    metadata: SceneMetadata,
+}
+
+/// Convert per-entity colors and sizes into sprite assets, keeping IDs and names
+/// for the stable IDs and labels of the current format
+fn migrate_v1_to_v2(scene: SceneV1) -> SceneV2 {
+    let entities = scene
+        .entities
+        .into_iter()
+        .map(|entity| {
+            // Sprite asset scale is relative to each type's default sprite size
+            let default_size = match entity.entity_type {
+                EntityType::Player => Vec2::splat(50.0),
+                EntityType::Enemy => Vec2::splat(40.0),
+                EntityType::Projectile => Vec2::new(5.0, 15.0),
+            };
+
+            EntityV2 {
+                entity_type: entity.entity_type,
+                transform: TransformV2 {
+                    x: entity.transform.x,
+                    y: entity.transform.y,
+                    z: entity.transform.z,
+                    rotation: 0.0,
+                },
+                health: None,
+                collision_radius: None,
+                sprite_asset: Some(SpriteAsset {
+                    asset_path: entity.texture_path,
+                    tint_color: [entity.color.r, entity.color.g, entity.color.b, entity.color.a],
+                    scale: [entity.size.width / default_size.x, entity.size.height / default_size.y],
+                    frame: None,
+                }),
+                id: Some(entity.id),
+                name: Some(entity.name).filter(|name| !name.is_empty()),
+            }
+        })
+        .collect();
+
+    SceneV2 {
+        entities,
+        metadata: Scene::default().metadata,
+    }
+}
+
+/// Add the explicit format number
+fn migrate_v2_to_v3(scene: SceneV2) -> SceneV3 {
+    SceneV3 {
+        entities: scene.entities,
+        metadata: scene.metadata,
+    }
+}
+
+/// Store each entity as the components the fixed-layout spawner used to attach
+fn migrate_v3_to_v4(scene: SceneV3) -> Scene {
+    Scene {
+        format_version: 4,
+        entities: scene.entities.into_iter().map(EntityV2::into_components).collect(),
+        metadata: scene.metadata,
+    }
+}
+
+/// Every entity in a version 4 scene is a root, so only the number changes
+fn migrate_v4_to_v5(scene: Scene) -> Scene {
+    Scene {
+        format_version: 5,
+        ..scene
+    }
+}
+
+impl EntityV2 {
+    fn into_components(self) -> SerializableEntity {
+        let (default_color, default_scale) = match self.entity_type {
+            EntityType::Player => (Color::BLUE, Vec3::splat(50.0)),
+            EntityType::Enemy => (Color::RED, Vec3::splat(40.0)),
+            EntityType::Projectile => (Color::YELLOW, Vec3::new(5.0, 15.0, 1.0)),
+        };
+
+        // The old spawner sized sprites from the asset scale, not the stored transform scale
+        let (color, scale) = match &self.sprite_asset {
+            Some(sprite_asset) => (sprite_asset.get_color(), default_scale * sprite_asset.get_scale().extend(1.0)),
+            None => (default_color, default_scale),
+        };
+
+        let mut entity = SerializableEntity::default();
+        entity.insert(Transform {
+            translation: Vec3::new(self.transform.x, self.transform.y, self.transform.z),
+            rotation: Quat::from_rotation_z(self.transform.rotation),
+            scale,
+        });
+        entity.insert(Sprite { color, ..default() });
+
+        match self.entity_type {
+            EntityType::Player => {
+                entity.insert(Player);
+                entity.insert(Shooting { cooldown: 0.0 });
+                let (current, max) = self.health.unwrap_or((100.0, 100.0));
+                entity.insert(Health { current, max });
+                entity.insert(Collision { radius: self.collision_radius.unwrap_or(25.0) });
+            }
+            EntityType::Enemy => {
+                entity.insert(Enemy);
+                let (current, max) = self.health.unwrap_or((50.0, 50.0));
+                entity.insert(Health { current, max });
+                entity.insert(Collision { radius: self.collision_radius.unwrap_or(20.0) });
+            }
+            EntityType::Projectile => {
+                entity.insert(Projectile { velocity: Vec2::new(0.0, 400.0) });
+                entity.insert(Collision { radius: self.collision_radius.unwrap_or(5.0) });
+            }
+        }
+
+        if let Some(sprite_asset) = self.sprite_asset {
+            entity.insert(sprite_asset);
+        }
+        if let Some(id) = self.id {
+            entity.insert(EntityId(id));
+        }
+        if let Some(name) = self.name {
+            entity.insert(EntityLabel::new(name));
+        }
+
+        entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_1_scene_keeps_ids_and_names() {
+        let ron_string = include_str!("../../scenes/default_scene.ron");
+        assert_eq!(detect_version(ron_string).unwrap(), 1);
+
+        let scene = parse_scene(ron_string, &TypeRegistry::default()).unwrap();
+        assert_eq!(scene.format_version, CURRENT_SCENE_FORMAT);
+
+        let entities: Vec<_> = scene
+            .entities
+            .iter()
+            .map(|entity| (entity.get::<EntityId>().map(|id| id.0), entity.display_name()))
+            .collect();
+        assert_eq!(
+            entities,
+            [
+                (Some(1), "Player".to_string()),
+                (Some(2), "Enemy 1".to_string()),
+                (Some(3), "Enemy 2".to_string()),
+                (Some(4), "Enemy 3".to_string()),
+                (Some(5), "Enemy 4".to_string()),
+                (Some(6), "Enemy 5".to_string()),
+            ]
+        );
+
+        // The old per-entity size becomes the sprite asset's scale
+        let player = &scene.entities[0];
+        assert!(player.contains::<Player>());
+        assert_eq!(player.get::<Transform>().unwrap().scale.truncate(), Vec2::splat(40.0));
+        assert_eq!(player.get::<SpriteAsset>().unwrap().get_scale(), Vec2::splat(0.8));
+    }
+}
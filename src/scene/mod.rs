@@ -1,15 +1,44 @@
 //! Scene management and serialization
+//!
+//! Scene entities are stored as lists of reflected components. Any component that
+//! derives `Reflect` with `#[reflect(Component)]` and is registered with the app
+//! (`app.register_type::<T>()`) is saved, loaded, copied and undone along with
+//! the built-in ones.
+//!
+//! Child entities are stored inside their parent with transforms relative to it,
+//! so a saved subtree comes back with the same shape wherever it is spawned.
 
 use bevy::prelude::*;
+use bevy::reflect::{TypeRegistry, TypeRegistryArc};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::fmt;
 
 use crate::components::*;
-use crate::resources::*;
+use crate::resources::SceneManager;
+
+pub mod format;
+pub mod migration;
+pub mod prefab;
+pub mod serialization;
+pub mod snapshot;
+
+pub use format::{convert_scene_file, SceneFileFormat};
+pub use migration::{parse_scene, SceneFormatError, CURRENT_SCENE_FORMAT};
+pub use prefab::{prefab_name, prefab_path, Prefab};
+pub use snapshot::{
+    clear_scene, hierarchy_entities, is_derived_component, restore_scene, serialize_entity, snapshot_entities,
+    snapshot_scene, spawn_scene, spawn_serialized_entity, spawn_serialized_hierarchy, top_level_entities,
+    SceneEntityFilter, SceneRootFilter,
+};
+use serialization::{from_ron_with_seed, EntityDeserializer, EntityListDeserializer, EntityListSerializer};
 
 /// Scene data structure for serialization
-#[derive(Serialize, Deserialize, Clone, Debug)]
+///
+/// Components are only known through the type registry, so scenes are written and
+/// read with the serializers in [`serialization`] rather than plain serde.
 pub struct Scene {
+    /// Layout version of the file, see [`migration`]
+    pub format_version: u32,
     pub entities: Vec<SerializableEntity>,
     pub metadata: SceneMetadata,
 }
@@ -23,280 +52,318 @@ pub struct SceneMetadata {
     pub last_modified: String,
 }
 
+impl Default for SceneMetadata {
+    fn default() -> Self {
+        Self {
+            name: "Untitled Scene".to_string(),
+            version: "1.0".to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_modified: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
 impl Default for Scene {
     fn default() -> Self {
         Self {
+            format_version: CURRENT_SCENE_FORMAT,
             entities: Vec::new(),
-            metadata: SceneMetadata {
-                name: "Untitled Scene".to_string(),
-                version: "1.0".to_string(),
-                created_at: chrono::Utc::now().to_rfc3339(),
-                last_modified: chrono::Utc::now().to_rfc3339(),
-            },
+            metadata: SceneMetadata::default(),
         }
     }
 }
 
-/// Save the current scene to a file
-pub fn save_scene(
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>, Option<&SpriteAsset>)>,
-    save_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut scene = Scene::default();
-    
-    // Collect all entities
-    for (_, transform, player, enemy, health, collision, sprite_asset) in entity_query.iter() {
-        let entity_type = if player.is_some() {
-            EntityType::Player
-        } else if enemy.is_some() {
-            EntityType::Enemy
+/// Serializable entity for scene saving/loading, stored as its reflected components
+#[derive(Default)]
+pub struct SerializableEntity {
+    pub components: Vec<Box<dyn Reflect>>,
+    /// Child entities in order; their transforms are relative to this entity
+    pub children: Vec<SerializableEntity>,
+}
+
+impl Clone for SerializableEntity {
+    fn clone(&self) -> Self {
+        Self {
+            components: self.components.iter().map(|component| component.clone_value()).collect(),
+            children: self.children.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for SerializableEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_paths: Vec<&str> = self
+            .components
+            .iter()
+            .map(|component| type_path_of(&**component).unwrap_or("?"))
+            .collect();
+
+        f.debug_struct("SerializableEntity")
+            .field("components", &type_paths)
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl SerializableEntity {
+    /// Typed copy of a stored component
+    pub fn get<T: FromReflect + TypePath>(&self) -> Option<T> {
+        self.find(T::type_path()).and_then(T::from_reflect)
+    }
+
+    /// Whether a component of this type is stored
+    pub fn contains<T: TypePath>(&self) -> bool {
+        self.find(T::type_path()).is_some()
+    }
+
+    /// Store a component, replacing any stored value of the same type
+    pub fn insert<T: Reflect + TypePath>(&mut self, component: T) {
+        self.remove::<T>();
+        self.components.push(Box::new(component));
+    }
+
+    /// Drop the stored component of this type, if any
+    pub fn remove<T: TypePath>(&mut self) {
+        self.components.retain(|stored| type_path_of(&**stored) != Some(T::type_path()));
+    }
+
+    /// Short name for menus: the entity's label, or its game marker if it has no name
+    pub fn display_name(&self) -> String {
+        if let Some(label) = self.get::<EntityLabel>().filter(|label| !label.name.is_empty()) {
+            return label.name;
+        }
+
+        let marker = if self.contains::<Player>() {
+            "Player"
+        } else if self.contains::<Enemy>() {
+            "Enemy"
+        } else if self.contains::<Projectile>() {
+            "Projectile"
         } else {
-            continue; // Skip non-game entities
-        };
-        
-        let serializable_entity = SerializableEntity {
-            entity_type,
-            transform: SerializableTransform::from(*transform),
-            health: health.map(|h| (h.current, h.max)),
-            collision_radius: collision.map(|c| c.radius),
-            sprite_asset: sprite_asset.cloned(),
+            "Entity"
         };
-        
-        scene.entities.push(serializable_entity);
+        marker.to_string()
     }
-    
+
+    /// Number of entities stored, counting this one and all of its descendants
+    pub fn entity_count(&self) -> usize {
+        1 + self.children.iter().map(SerializableEntity::entity_count).sum::<usize>()
+    }
+
+    fn find(&self, type_path: &str) -> Option<&dyn Reflect> {
+        self.components
+            .iter()
+            .map(|component| &**component)
+            .find(|component| type_path_of(*component) == Some(type_path))
+    }
+}
+
+fn type_path_of(component: &dyn Reflect) -> Option<&'static str> {
+    component.get_represented_type_info().map(|info| info.type_path())
+}
+
+/// Save the current scene to a file, in the format given by its extension
+pub fn save_scene(world: &mut World, save_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scene = snapshot_scene(world);
+
     // Update metadata
     scene.metadata.last_modified = chrono::Utc::now().to_rfc3339();
-    
-    // Serialize to RON format
-    let ron_string = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())?;
-    
-    // Ensure directory exists
-    if let Some(parent) = std::path::Path::new(save_path).parent() {
-        fs::create_dir_all(parent)?;
+
+    format::write_scene_file(&scene, save_path, &world.resource::<AppTypeRegistry>().0)?;
+
+    if let Some(mut scene_manager) = world.get_resource_mut::<SceneManager>() {
+        scene_manager.metadata = scene.metadata;
     }
-    
-    // Write to file
-    fs::write(save_path, ron_string)?;
-    
+
     println!("Scene saved to: {}", save_path);
     Ok(())
 }
 
-/// Load a scene from a file
-pub fn load_scene(
-    commands: &mut Commands,
-    load_path: &str,
-) -> Result<Scene, Box<dyn std::error::Error>> {
-    // Read file
-    let ron_string = fs::read_to_string(load_path)?;
-    
-    // Deserialize from RON format
-    let scene: Scene = ron::de::from_str(&ron_string)?;
-    
-    // Spawn entities from scene
-    for entity_data in &scene.entities {
-        spawn_entity_from_data(commands, entity_data);
+/// Load a scene from a file in any supported format, replacing the current scene
+///
+/// The file is parsed before anything is despawned, so a bad file leaves the
+/// current scene intact.
+pub fn load_scene(world: &mut World, load_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let scene = {
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        read_scene(load_path, &type_registry)?
+    };
+
+    restore_scene(world, &scene);
+    assign_entity_ids(world);
+
+    // Instances pick up changes made to their prefabs since the scene was saved
+    prefab::sync_all_prefab_instances(world);
+
+    if let Some(mut scene_manager) = world.get_resource_mut::<SceneManager>() {
+        scene_manager.metadata = scene.metadata;
     }
-    
+
     println!("Scene loaded from: {}", load_path);
-    Ok(scene)
+    Ok(())
+}
+
+/// Read and parse a scene file without spawning anything
+///
+/// The format is picked from the extension and older RON layouts are upgraded.
+pub fn read_scene(load_path: &str, type_registry: &TypeRegistry) -> Result<Scene, Box<dyn std::error::Error>> {
+    format::read_scene_file(load_path, type_registry)
 }
 
-/// Spawn an entity from serialized data
-pub fn spawn_entity_from_data(
+/// Give every scene entity without a stable ID a new one, and move
+/// `SceneManager::next_id` past every ID in use
+///
+/// Scenes saved before entities had IDs get them here when loaded.
+pub fn assign_entity_ids(world: &mut World) {
+    let mut query = world.query_filtered::<(Entity, Option<&EntityId>), SceneEntityFilter>();
+    let mut next_id = 0;
+    let mut missing = Vec::new();
+
+    for (entity, id) in query.iter(world) {
+        match id {
+            Some(id) => next_id = next_id.max(id.0 + 1),
+            None => missing.push(entity),
+        }
+    }
+
+    // Spawn order, so a scene gets the same IDs every time it is upgraded
+    missing.sort();
+    for entity in missing {
+        world.entity_mut(entity).insert(EntityId(next_id));
+        next_id += 1;
+    }
+
+    if let Some(mut scene_manager) = world.get_resource_mut::<SceneManager>() {
+        scene_manager.next_id = next_id;
+    }
+}
+
+/// Give entities about to be spawned as copies, and all their children, stable IDs of
+/// their own so no two entities share one
+pub fn assign_new_ids(world: &mut World, entities: &mut [SerializableEntity]) {
+    for data in entities {
+        let id = world.resource_mut::<SceneManager>().allocate_id();
+        data.insert(id);
+        assign_new_ids(world, &mut data.children);
+    }
+}
+
+/// Spawn a new entity at the specified position, named after its type and ID
+pub fn spawn_entity(
     commands: &mut Commands,
-    entity_data: &SerializableEntity,
-) {
-    let transform = Transform::from(entity_data.transform.clone());
-    
-    // Determine color and scale based on entity type and sprite asset
-    let (default_color, default_scale) = match entity_data.entity_type {
-        EntityType::Player => (Color::BLUE, Vec3::splat(50.0)),
-        EntityType::Enemy => (Color::RED, Vec3::splat(40.0)),
-        EntityType::Projectile => (Color::YELLOW, Vec3::new(5.0, 15.0, 1.0)),
-    };
-    
-    let sprite_color = if let Some(sprite_asset) = &entity_data.sprite_asset {
-        sprite_asset.get_color()
-    } else {
-        default_color
-    };
-    
-    let sprite_scale = if let Some(sprite_asset) = &entity_data.sprite_asset {
-        let asset_scale = sprite_asset.get_scale();
-        Vec3::new(default_scale.x * asset_scale.x, default_scale.y * asset_scale.y, default_scale.z)
-    } else {
-        default_scale
-    };
-    
-    match entity_data.entity_type {
-        EntityType::Player => {
-            let mut entity_commands = commands.spawn((
+    entity_type: EntityType,
+    id: EntityId,
+    position: Vec2,
+    z_position: Option<f32>,
+) -> Entity {
+    let z = z_position.unwrap_or(0.0);
+    let translation = Vec3::new(position.x, position.y, z);
+    let label = EntityLabel::new(format!("{:?} {}", entity_type, id.0));
+
+    let entity = match entity_type {
+        EntityType::Player => commands
+            .spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: sprite_color,
+                        color: Color::BLUE,
                         ..default()
                     },
-                    transform: transform.with_scale(sprite_scale),
+                    transform: Transform::from_translation(translation).with_scale(Vec3::splat(50.0)),
                     ..default()
                 },
                 Player,
                 Shooting { cooldown: 0.0 },
-            ));
-            
-            if let Some(sprite_asset) = &entity_data.sprite_asset {
-                entity_commands.insert(sprite_asset.clone());
-            }
-            
-            if let Some((current, max)) = entity_data.health {
-                entity_commands.insert(Health { current, max });
-            } else {
-                entity_commands.insert(Health { current: 100.0, max: 100.0 });
-            }
-            
-            if let Some(radius) = entity_data.collision_radius {
-                entity_commands.insert(Collision { radius });
-            } else {
-                entity_commands.insert(Collision { radius: 25.0 });
-            }
-        },
-        EntityType::Enemy => {
-            let mut entity_commands = commands.spawn((
+                Health { current: 100.0, max: 100.0 },
+                Collision { radius: 25.0 },
+            ))
+            .id(),
+        EntityType::Enemy => commands
+            .spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: sprite_color,
+                        color: Color::RED,
                         ..default()
                     },
-                    transform: transform.with_scale(sprite_scale),
+                    transform: Transform::from_translation(translation).with_scale(Vec3::splat(40.0)),
                     ..default()
                 },
                 Enemy,
-            ));
-            
-            if let Some(sprite_asset) = &entity_data.sprite_asset {
-                entity_commands.insert(sprite_asset.clone());
-            }
-            
-            if let Some((current, max)) = entity_data.health {
-                entity_commands.insert(Health { current, max });
-            } else {
-                entity_commands.insert(Health { current: 50.0, max: 50.0 });
-            }
-            
-            if let Some(radius) = entity_data.collision_radius {
-                entity_commands.insert(Collision { radius });
-            } else {
-                entity_commands.insert(Collision { radius: 20.0 });
-            }
-        },
-        EntityType::Projectile => {
-            let mut entity_commands = commands.spawn((
+                Health { current: 50.0, max: 50.0 },
+                Collision { radius: 20.0 },
+            ))
+            .id(),
+        EntityType::Projectile => commands
+            .spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: sprite_color,
+                        color: Color::YELLOW,
                         ..default()
                     },
-                    transform: transform.with_scale(sprite_scale),
+                    transform: Transform::from_translation(translation).with_scale(Vec3::new(5.0, 15.0, 1.0)),
                     ..default()
                 },
                 Projectile {
                     velocity: Vec2::new(0.0, 400.0),
                 },
-            ));
-            
-            if let Some(sprite_asset) = &entity_data.sprite_asset {
-                entity_commands.insert(sprite_asset.clone());
-            }
-            
-            if let Some(radius) = entity_data.collision_radius {
-                entity_commands.insert(Collision { radius });
-            } else {
-                entity_commands.insert(Collision { radius: 5.0 });
-            }
-        },
-    }
+                Collision { radius: 5.0 },
+            ))
+            .id(),
+    };
+
+    commands.entity(entity).insert((id, label));
+    entity
 }
 
-/// Spawn a new entity at the specified position
-pub fn spawn_entity(
-    commands: &mut Commands,
-    entity_type: EntityType,
-    position: Vec2,
-    z_position: Option<f32>,
-) {
-    let z = z_position.unwrap_or(0.0);
-    let transform = Transform::from_xyz(position.x, position.y, z);
-    
-    let entity_data = SerializableEntity {
-        entity_type,
-        transform: SerializableTransform::from(transform),
-        health: match entity_type {
-            EntityType::Player => Some((100.0, 100.0)),
-            EntityType::Enemy => Some((50.0, 50.0)),
-            EntityType::Projectile => None,
-        },
-        collision_radius: match entity_type {
-            EntityType::Player => Some(25.0),
-            EntityType::Enemy => Some(20.0),
-            EntityType::Projectile => Some(5.0),
-        },
-        sprite_asset: None, // Default to no custom sprite
-    };
-    
-    spawn_entity_from_data(commands, &entity_data);
+/// Spawn a game camera, the scene entity the game view and play mode look through
+pub fn spawn_game_camera(commands: &mut Commands, id: EntityId, position: Vec2, follow: Option<EntityId>) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            GameCamera {
+                follow,
+                ..default()
+            },
+            id,
+            EntityLabel::new("Main Camera"),
+        ))
+        .id()
 }
 
-/// Save the current scene to a RON string
-pub fn save_scene_to_string(
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Projectile>, Option<&Health>, Option<&Collision>, Option<&SpriteAsset>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+/// Serialize entities to RON text for the clipboard
+pub fn entities_to_ron(
+    entities: &[SerializableEntity],
+    type_registry: &TypeRegistryArc,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    let mut scene = Scene::default();
-    
-    // Collect all entities
-    for (_, transform, player, enemy, projectile, health, collision, sprite_asset) in entity_query.iter() {
-        let entity_type = if player.is_some() {
-            EntityType::Player
-        } else if enemy.is_some() {
-            EntityType::Enemy
-        } else if projectile.is_some() {
-            EntityType::Projectile
-        } else {
-            continue; // Skip non-game entities
-        };
-        
-        let serializable_entity = SerializableEntity {
-            entity_type,
-            transform: SerializableTransform::from(*transform),
-            health: health.map(|h| (h.current, h.max)),
-            collision_radius: collision.map(|c| c.radius),
-            sprite_asset: sprite_asset.cloned(),
-        };
-        
-        scene.entities.push(serializable_entity);
-    }
-    
-    // Update metadata
-    scene.metadata.last_modified = chrono::Utc::now().to_rfc3339();
-    
-    // Serialize to RON format
-    let ron_string = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())?;
-    
-    Ok(ron_string)
+    let serializer = EntityListSerializer {
+        entities,
+        registry: type_registry,
+    };
+    Ok(ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())?)
 }
 
-/// Load a scene from a RON string
-pub fn load_scene_from_string(
-    commands: &mut Commands,
-    ron_string: &str,
-) -> Result<Scene, Box<dyn std::error::Error>> {
-    // Deserialize from RON format
-    let scene: Scene = ron::de::from_str(ron_string)?;
-    
-    // Spawn entities from scene
-    for entity_data in &scene.entities {
-        spawn_entity_from_data(commands, entity_data);
+/// Parse entities from RON text
+///
+/// Accepts a list of entities, a single entity block copied out of a scene file,
+/// entities in the older fixed layout, or a whole scene file.
+pub fn entities_from_ron(
+    text: &str,
+    type_registry: &TypeRegistry,
+) -> Result<Vec<SerializableEntity>, Box<dyn std::error::Error>> {
+    // A block copied from inside a scene's entity list keeps its trailing comma
+    let text = text.trim().trim_end_matches(',');
+
+    if let Ok(entities) = from_ron_with_seed(text, EntityListDeserializer { registry: type_registry }) {
+        return Ok(entities);
     }
-    
-    Ok(scene)
-}
\ No newline at end of file
+
+    if let Ok(entity) = from_ron_with_seed(text, EntityDeserializer { registry: type_registry }) {
+        return Ok(vec![entity]);
+    }
+
+    if let Some(entities) = migration::parse_legacy_entities(text) {
+        return Ok(entities);
+    }
+
+    let scene = parse_scene(text, type_registry)?;
+    Ok(scene.entities)
+}
@@ -0,0 +1,644 @@
+//! Prefabs: reusable entities stored in their own files
+//!
+//! A prefab file holds one entity and its children as RON, in the same reflected
+//! layout scene files use. Instancing a prefab spawns a copy that carries a
+//! [`PrefabInstance`] link back to the file. When the prefab changes, every instance
+//! takes the new values except the components listed in its `overrides`, which are
+//! worked out after each edit by comparing the instance with its prefab.
+//!
+//! The root's translation, `EntityId` and `EntityLabel` belong to the instance and
+//! are never copied from the prefab, so instances keep their place and name. The
+//! rest of the root's `Transform` follows the prefab like any other component.
+
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use bevy::reflect::{TypeRegistry, TypeRegistryArc};
+use std::fs;
+use std::path::Path;
+
+use crate::components::*;
+use crate::history::{self, EditorAction, EditorHistory};
+use crate::resources::{PrefabLibrary, SceneManager, SelectedEntity};
+use crate::systems::tween::end_timeline_preview;
+use super::serialization::{from_ron_with_seed, PrefabDeserializer, PrefabSerializer};
+use super::{assign_new_ids, serialize_entity, type_path_of, SerializableEntity};
+
+/// Directory prefabs are saved to and listed from
+pub const PREFAB_DIRECTORY: &str = "prefabs";
+
+/// File name ending of prefab files
+pub const PREFAB_SUFFIX: &str = ".prefab.ron";
+
+/// Stands for an instance's child entities in [`PrefabInstance::overrides`]
+pub const CHILDREN_OVERRIDE: &str = "children";
+
+/// An entity and its children saved for reuse
+#[derive(Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    pub root: SerializableEntity,
+}
+
+/// File path a prefab with this name is saved to
+pub fn prefab_path(name: &str) -> String {
+    format!("{}/{}{}", PREFAB_DIRECTORY, name, PREFAB_SUFFIX)
+}
+
+/// Prefab name shown for a prefab file path
+pub fn prefab_name(path: &str) -> &str {
+    let file_name = Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path);
+    file_name.strip_suffix(PREFAB_SUFFIX).unwrap_or(file_name)
+}
+
+/// Read a prefab file
+pub fn read_prefab(path: &str, type_registry: &TypeRegistry) -> Result<Prefab, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)?;
+    Ok(from_ron_with_seed(&text, PrefabDeserializer { registry: type_registry })?)
+}
+
+/// Write a prefab file, creating the prefab directory if needed
+pub fn write_prefab(prefab: &Prefab, path: &str, type_registry: &TypeRegistryArc) -> Result<(), Box<dyn std::error::Error>> {
+    let serializer = PrefabSerializer {
+        prefab,
+        registry: type_registry,
+    };
+    let text = ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())?;
+
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Read every prefab in the prefab directory, skipping files that fail to parse
+pub fn read_prefab_directory(type_registry: &TypeRegistry) -> Vec<(String, Prefab)> {
+    let Ok(entries) = fs::read_dir(PREFAB_DIRECTORY) else {
+        return Vec::new();
+    };
+
+    let mut prefabs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(path) = path.to_str().filter(|path| path.ends_with(PREFAB_SUFFIX)) else {
+            continue;
+        };
+        // Keep paths in the same form as `prefab_path` so instances find their prefab
+        let path = path.replace('\\', "/");
+
+        match read_prefab(&path, type_registry) {
+            Ok(prefab) => prefabs.push((path, prefab)),
+            Err(e) => warn!("Failed to read prefab {}: {}", path, e),
+        }
+    }
+    prefabs
+}
+
+/// Turn a captured entity into prefab data, dropping everything that belongs to
+/// a particular instance
+pub fn prefab_template(mut data: SerializableEntity, name: &str) -> SerializableEntity {
+    data.remove::<EntityId>();
+    data.remove::<PrefabInstance>();
+
+    if let Some(mut transform) = data.get::<Transform>() {
+        transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+        data.insert(transform);
+    }
+
+    let mut label = data.get::<EntityLabel>().unwrap_or_default();
+    label.name = name.to_string();
+    data.insert(label);
+
+    for child in &mut data.children {
+        strip_ids(child);
+    }
+    data
+}
+
+fn strip_ids(data: &mut SerializableEntity) {
+    data.remove::<EntityId>();
+    data.remove::<PrefabInstance>();
+    for child in &mut data.children {
+        strip_ids(child);
+    }
+}
+
+/// Components of an instance root that the prefab never sets
+fn is_instance_owned(type_path: &str) -> bool {
+    type_path == EntityId::type_path()
+        || type_path == EntityLabel::type_path()
+        || type_path == PrefabInstance::type_path()
+}
+
+/// The prefab root's value of a component as an instance takes it
+///
+/// The instance keeps its own translation, so only the prefab's rotation and scale
+/// reach its `Transform`.
+fn prefab_component(instance: &SerializableEntity, prefab: &SerializableEntity, type_path: &str) -> Option<Box<dyn Reflect>> {
+    if type_path != Transform::type_path() {
+        return prefab.find(type_path).map(Reflect::clone_value);
+    }
+
+    let Some(mut transform) = prefab.get::<Transform>() else {
+        return instance.find(type_path).map(Reflect::clone_value);
+    };
+    if let Some(instance_transform) = instance.get::<Transform>() {
+        transform.translation = instance_transform.translation;
+    }
+    Some(Box::new(transform))
+}
+
+/// Which of the instance's components differ from the prefab, as listed in
+/// [`PrefabInstance::overrides`]
+pub fn prefab_overrides(instance: &SerializableEntity, prefab: &SerializableEntity) -> Vec<String> {
+    let mut overrides: Vec<String> = component_paths(instance, prefab)
+        .into_iter()
+        .filter(|type_path| !is_instance_owned(type_path))
+        .filter(|type_path| !same_component(instance.find(type_path), prefab_component(instance, prefab, type_path).as_deref()))
+        .map(str::to_string)
+        .collect();
+
+    if !same_entities(&instance.children, &prefab.children) {
+        overrides.push(CHILDREN_OVERRIDE.to_string());
+    }
+    overrides
+}
+
+/// Type paths of the components stored on either entity
+fn component_paths(a: &SerializableEntity, b: &SerializableEntity) -> Vec<&'static str> {
+    let mut type_paths: Vec<&'static str> = a
+        .components
+        .iter()
+        .chain(&b.components)
+        .filter_map(|component| type_path_of(&**component))
+        .collect();
+    type_paths.sort_unstable();
+    type_paths.dedup();
+    type_paths
+}
+
+fn same_component(a: Option<&dyn Reflect>, b: Option<&dyn Reflect>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.reflect_partial_eq(b).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Whether two child lists match, ignoring the IDs that every copy gets anew
+fn same_entities(a: &[SerializableEntity], b: &[SerializableEntity]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_entity(a, b))
+}
+
+fn same_entity(a: &SerializableEntity, b: &SerializableEntity) -> bool {
+    component_paths(a, b)
+        .into_iter()
+        .filter(|type_path| *type_path != EntityId::type_path() && *type_path != PrefabInstance::type_path())
+        .all(|type_path| same_component(a.find(type_path), b.find(type_path)))
+        && same_entities(&a.children, &b.children)
+}
+
+/// Bring one instance up to date with its prefab, leaving its overrides alone, and
+/// return the actions that undo the update
+pub fn sync_prefab_instance(world: &mut World, entity: Entity, prefab: &Prefab) -> Vec<EditorAction> {
+    let Some(instance) = serialize_entity(world, entity) else {
+        return Vec::new();
+    };
+    let overrides = world
+        .get::<PrefabInstance>(entity)
+        .map(|link| link.overrides.clone())
+        .unwrap_or_default();
+    let is_overridden = |type_path: &str| overrides.iter().any(|overridden| overridden == type_path);
+
+    let mut updated = Vec::new();
+    let mut removed = Vec::new();
+    {
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        for type_path in component_paths(&instance, &prefab.root) {
+            if is_instance_owned(type_path) || is_overridden(type_path) {
+                continue;
+            }
+
+            match (instance.find(type_path), prefab_component(&instance, &prefab.root, type_path)) {
+                (current, Some(value)) if !same_component(current, Some(&*value)) => updated.push(value),
+                (Some(_), None) => removed.extend(
+                    type_registry
+                        .get_with_type_path(type_path)
+                        .map(|registration| registration.type_id()),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut actions = Vec::new();
+    actions.extend(history::set_components(world, entity, updated));
+    for type_id in removed {
+        actions.extend(history::remove_component(world, entity, type_id));
+    }
+
+    // Children are replaced as a whole unless the instance changed them
+    if !is_overridden(CHILDREN_OVERRIDE) && !same_entities(&instance.children, &prefab.root.children) {
+        let children: Vec<Entity> = world
+            .get::<Children>(entity)
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+        for child in children {
+            actions.extend(history::delete_entity(world, child));
+        }
+
+        let mut new_children = prefab.root.children.clone();
+        assign_new_ids(world, &mut new_children);
+        for child in &new_children {
+            actions.push(history::spawn_serialized(world, child, Some(entity)).1);
+        }
+    }
+
+    actions
+}
+
+/// Bring every instance of a prefab up to date, returning the actions that undo it
+pub fn sync_prefab_instances(world: &mut World, path: &str) -> Vec<EditorAction> {
+    let Some(prefab) = world.resource::<PrefabLibrary>().get(path).cloned() else {
+        return Vec::new();
+    };
+
+    let mut query = world.query::<(Entity, &PrefabInstance)>();
+    let instances: Vec<Entity> = query
+        .iter(world)
+        .filter(|(_, link)| link.path == path)
+        .map(|(entity, _)| entity)
+        .collect();
+
+    let mut actions = Vec::new();
+    for entity in instances {
+        actions.extend(sync_prefab_instance(world, entity, &prefab));
+    }
+    actions
+}
+
+/// Bring every prefab instance in the world up to date with the prefab library
+pub fn sync_all_prefab_instances(world: &mut World) -> Vec<EditorAction> {
+    let paths: Vec<String> = world.resource::<PrefabLibrary>().prefabs.keys().cloned().collect();
+
+    let mut actions = Vec::new();
+    for path in paths {
+        actions.extend(sync_prefab_instances(world, &path));
+    }
+    actions
+}
+
+/// Recompute which components each prefab instance overrides
+pub fn refresh_prefab_overrides(world: &mut World) {
+    let mut query = world.query::<(Entity, &PrefabInstance)>();
+    let instances: Vec<(Entity, String)> = query
+        .iter(world)
+        .map(|(entity, link)| (entity, link.path.clone()))
+        .collect();
+
+    for (entity, path) in instances {
+        let Some(prefab) = world.resource::<PrefabLibrary>().get(&path) else {
+            continue; // Without the prefab there is nothing to compare against
+        };
+        let Some(data) = serialize_entity(world, entity) else {
+            continue;
+        };
+        let overrides = prefab_overrides(&data, &prefab.root);
+
+        if let Some(mut link) = world.get_mut::<PrefabInstance>(entity) {
+            if link.overrides != overrides {
+                link.overrides = overrides;
+            }
+        }
+    }
+}
+
+/// Fill the prefab library from the prefab directory
+pub fn load_prefab_library(world: &mut World) {
+    let prefabs = {
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        read_prefab_directory(&type_registry)
+    };
+    world.resource_mut::<PrefabLibrary>().prefabs = prefabs.into_iter().collect();
+}
+
+/// Save a prefab, update its instances and record the updates as one undo step
+fn save_prefab(world: &mut World, path: String, prefab: Prefab, mut actions: Vec<EditorAction>) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    if let Err(e) = write_prefab(&prefab, &path, &type_registry) {
+        error!("Failed to save prefab {}: {}", path, e);
+        world.resource_mut::<SceneManager>().status_message = Some(format!("Failed to save prefab {}: {}", path, e));
+        return;
+    }
+
+    let name = prefab.name.clone();
+    world.resource_mut::<PrefabLibrary>().prefabs.insert(path.clone(), prefab);
+
+    actions.extend(sync_prefab_instances(world, &path));
+    if !actions.is_empty() {
+        world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions));
+    }
+    refresh_prefab_overrides(world);
+
+    world.resource_mut::<SceneManager>().status_message = Some(format!("Saved prefab {}", name));
+}
+
+/// Command that saves an entity as a new prefab and turns the entity into its
+/// first instance
+pub struct CreatePrefab {
+    pub entity: Entity,
+    pub name: String,
+    /// Replace a prefab that already has this name; otherwise nothing is saved
+    pub replace: bool,
+}
+
+impl Command for CreatePrefab {
+    fn apply(self, world: &mut World) {
+        let name = self.name.trim();
+        if name.is_empty() {
+            warn!("Prefabs need a name");
+            return;
+        }
+        end_timeline_preview(world);
+        let Some(data) = serialize_entity(world, self.entity) else {
+            return;
+        };
+
+        let path = prefab_path(name);
+        let exists = world.resource::<PrefabLibrary>().get(&path).is_some() || Path::new(&path).exists();
+        if exists && !self.replace {
+            warn!("Prefab {} already exists", path);
+            world.resource_mut::<SceneManager>().status_message = Some(format!("A prefab named {} already exists", name));
+            return;
+        }
+
+        let prefab = Prefab {
+            name: name.to_string(),
+            root: prefab_template(data, name),
+        };
+
+        let link = PrefabInstance {
+            path: path.clone(),
+            overrides: Vec::new(),
+        };
+        let actions: Vec<EditorAction> = history::set_components(world, self.entity, vec![Box::new(link)])
+            .into_iter()
+            .collect();
+
+        save_prefab(world, path, prefab, actions);
+    }
+}
+
+/// Command that saves an instance's current values to its prefab and updates
+/// every other instance
+pub struct ApplyToPrefab {
+    pub entity: Entity,
+}
+
+impl Command for ApplyToPrefab {
+    fn apply(self, world: &mut World) {
+        let Some(path) = world.get::<PrefabInstance>(self.entity).map(|link| link.path.clone()) else {
+            return;
+        };
+        end_timeline_preview(world);
+        let Some(data) = serialize_entity(world, self.entity) else {
+            return;
+        };
+
+        let name = prefab_name(&path).to_string();
+        let prefab = Prefab {
+            root: prefab_template(data, &name),
+            name,
+        };
+
+        // The instance now matches its prefab, so nothing on it is an override any more
+        if let Some(mut link) = world.get_mut::<PrefabInstance>(self.entity) {
+            link.overrides.clear();
+        }
+
+        save_prefab(world, path, prefab, Vec::new());
+    }
+}
+
+/// Command that throws away one of an instance's overrides, taking the prefab's value
+pub struct RevertPrefabOverride {
+    pub entity: Entity,
+    pub type_path: String,
+}
+
+impl Command for RevertPrefabOverride {
+    fn apply(self, world: &mut World) {
+        let Some(path) = world.get::<PrefabInstance>(self.entity).map(|link| link.path.clone()) else {
+            return;
+        };
+        let Some(prefab) = world.resource::<PrefabLibrary>().get(&path).cloned() else {
+            warn!("Prefab {} is not loaded", path);
+            return;
+        };
+
+        if let Some(mut link) = world.get_mut::<PrefabInstance>(self.entity) {
+            link.overrides.retain(|overridden| *overridden != self.type_path);
+        }
+
+        let actions = sync_prefab_instance(world, self.entity, &prefab);
+        if !actions.is_empty() {
+            world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions));
+        }
+    }
+}
+
+/// Command that spawns an instance of a prefab at a position and selects it
+pub struct InstantiatePrefab {
+    pub path: String,
+    pub position: Vec3,
+}
+
+impl Command for InstantiatePrefab {
+    fn apply(self, world: &mut World) {
+        let Some(prefab) = world.resource::<PrefabLibrary>().get(&self.path).cloned() else {
+            warn!("Prefab {} is not loaded", self.path);
+            return;
+        };
+
+        let mut data = prefab.root;
+        let mut transform = data.get::<Transform>().unwrap_or_default();
+        transform.translation = self.position;
+        data.insert(transform);
+
+        assign_new_ids(world, std::slice::from_mut(&mut data));
+        let id = data.get::<EntityId>().unwrap_or_default();
+        let mut label = data.get::<EntityLabel>().unwrap_or_default();
+        label.name = format!("{} {}", prefab.name, id.0);
+        data.insert(label);
+        data.insert(PrefabInstance {
+            path: self.path,
+            overrides: Vec::new(),
+        });
+
+        let spawned = history::spawn_recorded(world, &[data], None);
+        if let Some(&entity) = spawned.first() {
+            world.resource_mut::<SelectedEntity>().select(entity);
+        }
+    }
+}
+
+/// Command that reads the prefab directory again and updates the instances of
+/// every prefab that changed on disk
+pub struct RefreshPrefabs;
+
+impl Command for RefreshPrefabs {
+    fn apply(self, world: &mut World) {
+        let prefabs = {
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            read_prefab_directory(&type_registry)
+        };
+
+        let changed: Vec<String> = {
+            let library = world.resource::<PrefabLibrary>();
+            prefabs
+                .iter()
+                .filter(|(path, prefab)| {
+                    library
+                        .get(path)
+                        .is_none_or(|known| !same_entity(&known.root, &prefab.root))
+                })
+                .map(|(path, _)| path.clone())
+                .collect()
+        };
+
+        world.resource_mut::<PrefabLibrary>().prefabs = prefabs.into_iter().collect();
+
+        let mut actions = Vec::new();
+        for path in &changed {
+            actions.extend(sync_prefab_instances(world, path));
+        }
+        if !actions.is_empty() {
+            world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions));
+        }
+        refresh_prefab_overrides(world);
+
+        let count = world.resource::<PrefabLibrary>().prefabs.len();
+        world.resource_mut::<SceneManager>().status_message = Some(format!("Found {} prefabs", count));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefab_world() -> World {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Transform>();
+            registry.register::<Vec3>();
+            registry.register::<Quat>();
+            registry.register::<String>();
+            registry.register::<Vec<String>>();
+            registry.register::<Health>();
+            registry.register::<Collision>();
+            registry.register::<EntityId>();
+            registry.register::<EntityLabel>();
+            registry.register::<PrefabInstance>();
+        }
+
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.init_resource::<SelectedEntity>();
+        world.init_resource::<EditorHistory>();
+        world.init_resource::<PrefabLibrary>();
+        world.init_resource::<SceneManager>();
+        world
+    }
+
+    /// Put a prefab with the given health and radius in the library under `path`
+    fn add_prefab(world: &mut World, path: &str, health: f32, radius: f32) {
+        let source = world
+            .spawn((Transform::default(), Health { current: health, max: 100.0 }, Collision { radius }))
+            .id();
+        let data = serialize_entity(world, source).unwrap();
+        world.despawn(source);
+
+        let prefab = Prefab {
+            name: prefab_name(path).to_string(),
+            root: prefab_template(data, prefab_name(path)),
+        };
+        world.resource_mut::<PrefabLibrary>().prefabs.insert(path.to_string(), prefab);
+    }
+
+    fn spawn_instance(world: &mut World, path: &str, id: u32, health: f32, radius: f32) -> Entity {
+        world
+            .spawn((
+                Transform::from_xyz(id as f32 * 100.0, 0.0, 0.0),
+                Health { current: health, max: 100.0 },
+                Collision { radius },
+                EntityId(id),
+                PrefabInstance {
+                    path: path.to_string(),
+                    overrides: Vec::new(),
+                },
+            ))
+            .id()
+    }
+
+    fn health_of(world: &World, entity: Entity) -> f32 {
+        world.get::<Health>(entity).unwrap().current
+    }
+
+    #[test]
+    fn overridden_fields_survive_prefab_changes() {
+        let mut world = prefab_world();
+        let path = prefab_path("crate");
+        add_prefab(&mut world, &path, 50.0, 20.0);
+        let instance = spawn_instance(&mut world, &path, 1, 10.0, 20.0);
+
+        refresh_prefab_overrides(&mut world);
+        let overrides = &world.get::<PrefabInstance>(instance).unwrap().overrides;
+        assert_eq!(overrides, &vec![Health::type_path().to_string()]);
+
+        add_prefab(&mut world, &path, 80.0, 30.0);
+        let prefab = world.resource::<PrefabLibrary>().get(&path).cloned().unwrap();
+        sync_prefab_instance(&mut world, instance, &prefab);
+
+        assert_eq!(health_of(&world, instance), 10.0);
+        assert_eq!(world.get::<Collision>(instance).unwrap().radius, 30.0);
+        assert_eq!(world.get::<Transform>(instance).unwrap().translation.x, 100.0);
+    }
+
+    #[test]
+    fn reverting_an_override_takes_the_prefab_value() {
+        let mut world = prefab_world();
+        let path = prefab_path("crate");
+        add_prefab(&mut world, &path, 50.0, 20.0);
+        let instance = spawn_instance(&mut world, &path, 1, 10.0, 20.0);
+        refresh_prefab_overrides(&mut world);
+
+        RevertPrefabOverride {
+            entity: instance,
+            type_path: Health::type_path().to_string(),
+        }
+        .apply(&mut world);
+
+        assert_eq!(health_of(&world, instance), 50.0);
+        assert!(world.get::<PrefabInstance>(instance).unwrap().overrides.is_empty());
+    }
+
+    #[test]
+    fn applying_an_instance_updates_the_prefab_and_other_instances() {
+        let mut world = prefab_world();
+        let directory = std::env::temp_dir().join(format!("prefab-apply-{}", std::process::id()));
+        let path = directory.join(format!("crate{}", PREFAB_SUFFIX)).to_string_lossy().replace('\\', "/");
+        add_prefab(&mut world, &path, 50.0, 20.0);
+        let edited = spawn_instance(&mut world, &path, 1, 10.0, 20.0);
+        let other = spawn_instance(&mut world, &path, 2, 50.0, 20.0);
+
+        ApplyToPrefab { entity: edited }.apply(&mut world);
+        let _ = fs::remove_dir_all(&directory);
+
+        let prefab = world.resource::<PrefabLibrary>().get(&path).cloned().unwrap();
+        assert_eq!(prefab.root.get::<Health>().map(|health| health.current), Some(10.0));
+        assert_eq!(health_of(&world, other), 10.0);
+        assert!(world.get::<PrefabInstance>(edited).unwrap().overrides.is_empty());
+    }
+}
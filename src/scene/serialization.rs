@@ -0,0 +1,392 @@
+//! Reflection-based serializers for scenes and entities
+//!
+//! The serializers work with any serde format; [`super::format`] uses them for
+//! RON, JSON and bincode.
+//!
+//! Components are only known at runtime through the type registry, so scenes are
+//! written and read through serde seeds that carry the registry, the same way
+//! Bevy's own `DynamicScene` serializers work. Each entity is written as a map from
+//! component type path to its reflected value:
+//!
+//! ```text
+//! (
+//!     components: {
+//!         "bevy_transform::components::transform::Transform": (translation: (x: 0.0, y: 0.0, z: 0.0), ...),
+//!         "gamemaker_rust::components::Player": (),
+//!     },
+//!     children: [
+//!         (components: { ... }, children: []),
+//!     ],
+//! )
+//! ```
+
+use bevy::reflect::{TypeRegistry, TypeRegistryArc};
+use bevy::scene::serde::{SceneMapDeserializer, SceneMapSerializer};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use super::migration::{SceneFormatError, CURRENT_SCENE_FORMAT};
+use super::{Prefab, Scene, SceneMetadata, SerializableEntity};
+
+/// Serializes a [`Scene`] with the components of its entities
+pub struct SceneSerializer<'a> {
+    pub scene: &'a Scene,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl<'a> SceneSerializer<'a> {
+    pub fn new(scene: &'a Scene, registry: &'a TypeRegistryArc) -> Self {
+        Self { scene, registry }
+    }
+}
+
+impl Serialize for SceneSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Scene", 3)?;
+        state.serialize_field("format_version", &self.scene.format_version)?;
+        state.serialize_field("metadata", &self.scene.metadata)?;
+        state.serialize_field(
+            "entities",
+            &EntityListSerializer {
+                entities: &self.scene.entities,
+                registry: self.registry,
+            },
+        )?;
+        state.end()
+    }
+}
+
+/// Serializes a list of entities, as found in scenes and on the clipboard
+pub struct EntityListSerializer<'a> {
+    pub entities: &'a [SerializableEntity],
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl Serialize for EntityListSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_seq(Some(self.entities.len()))?;
+        for entity in self.entities {
+            state.serialize_element(&EntitySerializer {
+                entity,
+                registry: self.registry,
+            })?;
+        }
+        state.end()
+    }
+}
+
+/// Serializes one entity as a map of component type paths to values, followed by its children
+pub struct EntitySerializer<'a> {
+    pub entity: &'a SerializableEntity,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl Serialize for EntitySerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Entity", 2)?;
+        state.serialize_field(
+            "components",
+            &SceneMapSerializer {
+                entries: &self.entity.components,
+                registry: self.registry,
+            },
+        )?;
+        state.serialize_field(
+            "children",
+            &EntityListSerializer {
+                entities: &self.entity.children,
+                registry: self.registry,
+            },
+        )?;
+        state.end()
+    }
+}
+
+/// Serializes a [`Prefab`] with its root entity, stamped with the current format version
+pub struct PrefabSerializer<'a> {
+    pub prefab: &'a Prefab,
+    pub registry: &'a TypeRegistryArc,
+}
+
+impl Serialize for PrefabSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Prefab", 3)?;
+        state.serialize_field("format_version", &CURRENT_SCENE_FORMAT)?;
+        state.serialize_field("name", &self.prefab.name)?;
+        state.serialize_field(
+            "root",
+            &EntitySerializer {
+                entity: &self.prefab.root,
+                registry: self.registry,
+            },
+        )?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum SceneField {
+    FormatVersion,
+    Metadata,
+    Entities,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum PrefabField {
+    FormatVersion,
+    Name,
+    Root,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum EntityField {
+    Components,
+    Children,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Deserializes a [`Scene`] in the current format
+pub struct SceneDeserializer<'a> {
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for SceneDeserializer<'_> {
+    type Value = Scene;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            "Scene",
+            &["format_version", "metadata", "entities"],
+            SceneVisitor { registry: self.registry },
+        )
+    }
+}
+
+struct SceneVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for SceneVisitor<'_> {
+    type Value = Scene;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("scene struct")
+    }
+
+    // Formats without field names, such as bincode, visit structs as sequences
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let format_version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"a scene with 3 fields"))?;
+        let metadata = seq
+            .next_element::<SceneMetadata>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &"a scene with 3 fields"))?;
+        let entities = seq
+            .next_element_seed(EntityListDeserializer { registry: self.registry })?
+            .ok_or_else(|| de::Error::invalid_length(2, &"a scene with 3 fields"))?;
+
+        Ok(Scene {
+            format_version,
+            entities,
+            metadata,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut format_version = None;
+        let mut metadata = None;
+        let mut entities = None;
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                SceneField::FormatVersion => format_version = Some(map.next_value()?),
+                SceneField::Metadata => metadata = Some(map.next_value::<SceneMetadata>()?),
+                SceneField::Entities => {
+                    entities = Some(map.next_value_seed(EntityListDeserializer { registry: self.registry })?);
+                }
+                SceneField::Unknown => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(Scene {
+            format_version: format_version.ok_or_else(|| de::Error::missing_field("format_version"))?,
+            entities: entities.ok_or_else(|| de::Error::missing_field("entities"))?,
+            metadata: metadata.ok_or_else(|| de::Error::missing_field("metadata"))?,
+        })
+    }
+}
+
+/// Deserializes a [`Prefab`], refusing files from a newer engine
+pub struct PrefabDeserializer<'a> {
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for PrefabDeserializer<'_> {
+    type Value = Prefab;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            "Prefab",
+            &["format_version", "name", "root"],
+            PrefabVisitor { registry: self.registry },
+        )
+    }
+}
+
+struct PrefabVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for PrefabVisitor<'_> {
+    type Value = Prefab;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("prefab struct")
+    }
+
+    // Prefab files are always RON, which names every field
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut format_version = None;
+        let mut name = None;
+        let mut root = None;
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                PrefabField::FormatVersion => format_version = Some(map.next_value::<u32>()?),
+                PrefabField::Name => name = Some(map.next_value()?),
+                PrefabField::Root => root = Some(map.next_value_seed(EntityDeserializer { registry: self.registry })?),
+                PrefabField::Unknown => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let version = format_version.ok_or_else(|| de::Error::missing_field("format_version"))?;
+        if version > CURRENT_SCENE_FORMAT {
+            return Err(de::Error::custom(SceneFormatError::NewerThanEngine { version }));
+        }
+
+        Ok(Prefab {
+            name: name.ok_or_else(|| de::Error::missing_field("name"))?,
+            root: root.ok_or_else(|| de::Error::missing_field("root"))?,
+        })
+    }
+}
+
+/// Deserializes a list of entities
+pub struct EntityListDeserializer<'a> {
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for EntityListDeserializer<'_> {
+    type Value = Vec<SerializableEntity>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(EntityListVisitor { registry: self.registry })
+    }
+}
+
+struct EntityListVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for EntityListVisitor<'_> {
+    type Value = Vec<SerializableEntity>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("list of entities")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut entities = Vec::new();
+        while let Some(entity) = seq.next_element_seed(EntityDeserializer { registry: self.registry })? {
+            entities.push(entity);
+        }
+        Ok(entities)
+    }
+}
+
+/// Deserializes one entity's components and children
+pub struct EntityDeserializer<'a> {
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for EntityDeserializer<'_> {
+    type Value = SerializableEntity;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            "Entity",
+            &["components", "children"],
+            EntityVisitor { registry: self.registry },
+        )
+    }
+}
+
+struct EntityVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for EntityVisitor<'_> {
+    type Value = SerializableEntity;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("entity struct")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let components = seq
+            .next_element_seed(SceneMapDeserializer { registry: self.registry })?
+            .ok_or_else(|| de::Error::invalid_length(0, &"an entity with 2 fields"))?;
+        let children = seq
+            .next_element_seed(EntityListDeserializer { registry: self.registry })?
+            .ok_or_else(|| de::Error::invalid_length(1, &"an entity with 2 fields"))?;
+
+        Ok(SerializableEntity { components, children })
+    }
+
+    // Entities written before the hierarchy existed have no `children` field
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut components = None;
+        let mut children = None;
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                EntityField::Components => {
+                    components = Some(map.next_value_seed(SceneMapDeserializer { registry: self.registry })?);
+                }
+                EntityField::Children => {
+                    children = Some(map.next_value_seed(EntityListDeserializer { registry: self.registry })?);
+                }
+                EntityField::Unknown => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(SerializableEntity {
+            components: components.ok_or_else(|| de::Error::missing_field("components"))?,
+            children: children.unwrap_or_default(),
+        })
+    }
+}
+
+/// Parse RON text with a deserializer seed, rejecting anything left over
+pub fn from_ron_with_seed<'a, S: DeserializeSeed<'a>>(text: &'a str, seed: S) -> Result<S::Value, ron::Error> {
+    let mut deserializer = ron::Deserializer::from_str(text)?;
+    let value = seed.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
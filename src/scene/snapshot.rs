@@ -0,0 +1,227 @@
+//! Capturing scene entities from the world and putting them back
+//!
+//! Disk saves, play-mode snapshots, the clipboard and undo history all capture and
+//! restore entities through these functions, so every path stores the same
+//! components in the same order.
+//!
+//! Capturing an entity captures its whole subtree, and spawning one rebuilds the
+//! subtree with the same `Parent`/`Children` links. Transforms are stored as they
+//! are on the entity, so children keep their position relative to the parent.
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use std::any::TypeId;
+
+use crate::components::*;
+use crate::resources::SceneManager;
+use super::{type_path_of, Scene, SerializableEntity};
+
+/// Entities that belong to the scene rather than to the editor or engine
+pub type SceneEntityFilter = (With<Transform>, Without<Camera>, Without<GridLine>, Without<BackgroundImage>);
+
+/// Scene entities at the top of the hierarchy
+pub type SceneRootFilter = (SceneEntityFilter, Without<Parent>);
+
+/// Components that the engine derives from others or that point at other entities;
+/// they are rebuilt on spawn instead of being stored
+pub fn is_derived_component(type_id: TypeId) -> bool {
+    type_id == TypeId::of::<GlobalTransform>()
+        || type_id == TypeId::of::<InheritedVisibility>()
+        || type_id == TypeId::of::<ViewVisibility>()
+        || type_id == TypeId::of::<Handle<Image>>()
+        || type_id == TypeId::of::<Aabb>()
+        || type_id == TypeId::of::<Parent>()
+        || type_id == TypeId::of::<Children>()
+}
+
+/// Capture every scene entity along with the open scene's metadata
+pub fn snapshot_scene(world: &mut World) -> Scene {
+    let mut query = world.query_filtered::<Entity, SceneRootFilter>();
+    let entities: Vec<Entity> = query.iter(world).collect();
+
+    let mut scene = Scene {
+        entities: snapshot_entities(world, &entities),
+        ..Scene::default()
+    };
+    if let Some(scene_manager) = world.get_resource::<SceneManager>() {
+        scene.metadata = scene_manager.metadata.clone();
+    }
+    scene
+}
+
+/// Capture the given entities with their children, skipping any that no longer exist
+///
+/// Entities whose ancestor is also in the list are left out, since they are already
+/// captured as part of that ancestor.
+pub fn snapshot_entities(world: &World, entities: &[Entity]) -> Vec<SerializableEntity> {
+    top_level_entities(world, entities)
+        .into_iter()
+        .filter_map(|entity| serialize_entity(world, entity))
+        .collect()
+}
+
+/// The entities in the list that do not have an ancestor in the list, in order
+///
+/// Moving, copying or deleting these covers the whole list, since the rest come
+/// along as their descendants.
+pub fn top_level_entities(world: &World, entities: &[Entity]) -> Vec<Entity> {
+    entities
+        .iter()
+        .copied()
+        .filter(|&entity| !has_ancestor_in(world, entity, entities))
+        .collect()
+}
+
+/// Capture a single entity and its children from the world as serializable data
+///
+/// Every registered component is stored except those in [`is_derived_component`],
+/// ordered by type path so the same entity always serializes the same way.
+pub fn serialize_entity(world: &World, entity: Entity) -> Option<SerializableEntity> {
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    let entity_ref = world.get_entity(entity)?;
+    let mut components = Vec::new();
+
+    for component_id in entity_ref.archetype().components() {
+        let Some(type_id) = world.components().get_info(component_id).and_then(|info| info.type_id()) else {
+            continue;
+        };
+        if is_derived_component(type_id) {
+            continue;
+        }
+
+        let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(type_id) else {
+            continue; // Not registered for reflection, so it cannot be stored
+        };
+        let Some(component) = reflect_component.reflect(entity_ref) else {
+            continue;
+        };
+
+        // Prefer a concrete copy so types with custom serde keep their format
+        let concrete = type_registry
+            .get_type_data::<ReflectFromReflect>(type_id)
+            .and_then(|from_reflect| from_reflect.from_reflect(component));
+        components.push(concrete.unwrap_or_else(|| component.clone_value()));
+    }
+
+    components.sort_by_key(|component| type_path_of(&**component));
+
+    let children = entity_ref
+        .get::<Children>()
+        .map(|children| {
+            children
+                .iter()
+                .filter_map(|&child| serialize_entity(world, child))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(SerializableEntity { components, children })
+}
+
+/// An entity followed by all of its descendants, in the order [`serialize_entity`]
+/// stores them and [`spawn_serialized_hierarchy`] spawns them
+pub fn hierarchy_entities(world: &World, root: Entity) -> Vec<Entity> {
+    let mut entities = vec![root];
+    if let Some(children) = world.get::<Children>(root) {
+        for &child in children.iter() {
+            entities.extend(hierarchy_entities(world, child));
+        }
+    }
+    entities
+}
+
+/// Whether any ancestor of `entity` is in `entities`
+fn has_ancestor_in(world: &World, entity: Entity, entities: &[Entity]) -> bool {
+    let mut current = entity;
+    while let Some(parent) = world.get::<Parent>(current) {
+        if entities.contains(&parent.get()) {
+            return true;
+        }
+        current = parent.get();
+    }
+    false
+}
+
+/// Replace every scene entity in the world with the entities of `scene`
+pub fn restore_scene(world: &mut World, scene: &Scene) -> Vec<Entity> {
+    clear_scene(world);
+    spawn_scene(world, scene)
+}
+
+/// Spawn every entity in a scene alongside the existing ones
+pub fn spawn_scene(world: &mut World, scene: &Scene) -> Vec<Entity> {
+    scene
+        .entities
+        .iter()
+        .map(|entity_data| spawn_serialized_entity(world, entity_data))
+        .collect()
+}
+
+/// Despawn every scene entity
+pub fn clear_scene(world: &mut World) {
+    let mut query = world.query_filtered::<Entity, SceneRootFilter>();
+    let entities: Vec<Entity> = query.iter(world).collect();
+
+    for entity in entities {
+        despawn_with_children_recursive(world, entity);
+    }
+}
+
+/// Spawn an entity and its children from serialized data, returning the root
+pub fn spawn_serialized_entity(world: &mut World, entity_data: &SerializableEntity) -> Entity {
+    spawn_serialized_hierarchy(world, entity_data)[0]
+}
+
+/// Spawn an entity and its children from serialized data
+///
+/// Returns the root followed by every descendant, in the same order as
+/// [`hierarchy_entities`].
+pub fn spawn_serialized_hierarchy(world: &mut World, entity_data: &SerializableEntity) -> Vec<Entity> {
+    let root = spawn_components(world, entity_data);
+    let mut entities = vec![root];
+
+    for child_data in &entity_data.children {
+        let child_entities = spawn_serialized_hierarchy(world, child_data);
+        world.entity_mut(root).add_child(child_entities[0]);
+        entities.extend(child_entities);
+    }
+
+    entities
+}
+
+/// Spawn a single entity from its stored components, without its children
+fn spawn_components(world: &mut World, entity_data: &SerializableEntity) -> Entity {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    let mut entity = world.spawn_empty();
+
+    for component in &entity_data.components {
+        let Some(type_info) = component.get_represented_type_info() else {
+            warn!("Skipping component without type info");
+            continue;
+        };
+        let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(type_info.type_id()) else {
+            warn!("Skipping component {} that is not registered with #[reflect(Component)]", type_info.type_path());
+            continue;
+        };
+        reflect_component.insert(&mut entity, &**component, &type_registry);
+    }
+
+    // Derived components are not stored, so restore the ones the stored components rely on
+    if entity.contains::<Transform>() && !entity.contains::<GlobalTransform>() {
+        entity.insert(GlobalTransform::default());
+    }
+
+    if entity.contains::<Sprite>() || entity.contains::<Visibility>() {
+        if !entity.contains::<Visibility>() {
+            entity.insert(Visibility::default());
+        }
+        entity.insert((InheritedVisibility::default(), ViewVisibility::default()));
+    }
+
+    if entity.contains::<Sprite>() && !entity.contains::<Handle<Image>>() {
+        entity.insert(Handle::<Image>::default());
+    }
+
+    entity.id()
+}
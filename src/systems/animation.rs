@@ -0,0 +1,105 @@
+//! Frame-based sprite animation
+
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::resources::*;
+
+/// Event fired when an animated sprite reaches a frame that has an event in its clip
+#[derive(Event, Debug, Clone)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub clip: String,
+    pub name: String,
+}
+
+/// Advance sprite animations while the game is playing, firing their frame events
+///
+/// Entities get their `AnimationState` the first time they're seen, and start over
+/// whenever their `AnimatedSprite` switches clip. The state only counts as changed
+/// when the frame on screen does, so still frames don't redraw their sprite.
+pub fn animate_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut animation_events: EventWriter<AnimationEvent>,
+    mut sprite_query: Query<(Entity, &AnimatedSprite, Option<&mut AnimationState>)>,
+    mut removed: RemovedComponents<AnimatedSprite>,
+) {
+    // Sprites that stop being animated go back to their sprite asset's own frame
+    for entity in removed.read() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<AnimationState>();
+        }
+    }
+    
+    let running = game_state.playing && !game_state.paused;
+    for (entity, animated, state) in sprite_query.iter_mut() {
+        let Some(clip) = animated.current_clip() else {
+            continue;
+        };
+        let Some(mut state) = state else {
+            commands.entity(entity).insert(AnimationState::start(clip));
+            continue;
+        };
+        
+        let mut next = if state.clip == clip.name {
+            state.clone()
+        } else {
+            let start = AnimationState::start(clip);
+            if running {
+                send_frame_events(&mut animation_events, entity, clip, &[0]);
+            }
+            start
+        };
+        if running && animated.playing {
+            let reached = next.advance(clip, time.delta_seconds() * animated.speed);
+            send_frame_events(&mut animation_events, entity, clip, &reached);
+        } else {
+            // Edits to a clip's frames still show up while paused
+            next.atlas_frame = clip.atlas_frame(next.step);
+        }
+        
+        if next.atlas_frame != state.atlas_frame || next.clip != state.clip {
+            *state = next;
+        } else {
+            *state.bypass_change_detection() = next;
+        }
+    }
+}
+
+/// Fire the events a clip has on the steps reached
+fn send_frame_events(animation_events: &mut EventWriter<AnimationEvent>, entity: Entity, clip: &SpriteClip, steps: &[usize]) {
+    for &step in steps {
+        for event in clip.events.iter().filter(|event| event.frame == step) {
+            animation_events.send(AnimationEvent {
+                entity,
+                clip: clip.name.clone(),
+                name: event.name.clone(),
+            });
+        }
+    }
+}
+
+/// Play the clip shown in the animation preview tab, whether or not the game is playing
+pub fn advance_animation_preview(
+    time: Res<Time>,
+    mut preview: ResMut<AnimationPreview>,
+    sprite_query: Query<&AnimatedSprite>,
+) {
+    let Some(animated) = preview.entity.and_then(|entity| sprite_query.get(entity).ok()) else {
+        return;
+    };
+    let Some(clip) = animated.get_clip(&preview.state.clip) else {
+        return;
+    };
+    if !preview.playing {
+        return;
+    }
+    
+    let seconds = time.delta_seconds() * animated.speed;
+    preview.state.advance(clip, seconds);
+    if preview.state.finished {
+        preview.playing = false;
+    }
+}
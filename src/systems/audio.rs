@@ -0,0 +1,360 @@
+//! Sound playback: one-shot sounds, sound emitters, volume buses and asset previews
+//!
+//! Every sound plays on an entity of its own carrying a [`SoundPlayback`], so bus
+//! volumes, pausing and stopping can reach it however it was started. Without an
+//! audio device Bevy plays nothing; such sounds are dropped once they have loaded,
+//! so the engine runs the same headless.
+
+use bevy::prelude::*;
+use bevy::audio::{PlaybackMode, SpatialScale, Volume};
+use bevy::ecs::system::SystemParam;
+
+use crate::components::*;
+use crate::resources::*;
+
+/// Scale from world units to the distances spatial sounds fade over
+const SPATIAL_SCALE: f32 = 1.0 / 100.0;
+
+/// Distance between the listener's ears, in world units
+pub const LISTENER_EAR_GAP: f32 = 400.0;
+
+/// Request to play a sound once, sent by gameplay code
+#[derive(Event, Debug, Clone)]
+pub struct PlaySound {
+    /// Path of the sound in the assets folder
+    pub sound: String,
+    pub bus: AudioBus,
+    pub volume: f32,
+    /// Where the sound comes from, or `None` to play it the same everywhere
+    pub position: Option<Vec2>,
+}
+
+impl PlaySound {
+    /// Sound effect played at full volume, the same everywhere
+    pub fn sfx(sound: impl Into<String>) -> Self {
+        Self {
+            sound: sound.into(),
+            bus: AudioBus::Sfx,
+            volume: 1.0,
+            position: None,
+        }
+    }
+
+    /// Play the sound from a position in the world
+    pub fn at(mut self, position: Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+/// Sends the built-in gameplay sounds, for the systems that trigger them
+#[derive(SystemParam)]
+pub struct SoundEffects<'w> {
+    sounds: Res<'w, GameSounds>,
+    events: EventWriter<'w, PlaySound>,
+}
+
+impl SoundEffects<'_> {
+    /// Play one of the gameplay sounds from a position in the world
+    pub fn play_at(&mut self, sound: impl FnOnce(&GameSounds) -> &str, position: Vec2) {
+        let event = PlaySound::sfx(sound(&self.sounds)).at(position);
+        self.events.send(event);
+    }
+}
+
+/// A playing sound with its settings and whichever sink is playing it
+type PlayingSoundData = (
+    Entity,
+    &'static mut SoundPlayback,
+    &'static Handle<AudioSource>,
+    &'static mut PlaybackSettings,
+    Option<&'static AudioSink>,
+    Option<&'static SpatialAudioSink>,
+);
+
+/// Play the sounds requested through `PlaySound` events while the game is playing
+///
+/// Sounds the registry doesn't know yet are imported if their file exists, and
+/// play from the next request on.
+pub fn play_sounds(
+    mut commands: Commands,
+    mut play_events: EventReader<PlaySound>,
+    asset_registry: Res<AssetRegistry>,
+    mut asset_importer: ResMut<AssetImporter>,
+    audio_settings: Res<AudioSettings>,
+    game_state: Res<GameState>,
+) {
+    if !game_state.playing {
+        play_events.clear();
+        return;
+    }
+
+    for event in play_events.read() {
+        let Some(source) = sound_source(&asset_registry, &mut asset_importer, &event.sound) else {
+            continue;
+        };
+        let playback = SoundPlayback {
+            bus: event.bus,
+            volume: event.volume,
+            emitter: None,
+            preview: false,
+            stalled: false,
+        };
+        spawn_sound(&mut commands, source, playback, event.position, false, &audio_settings);
+    }
+}
+
+/// Start the sounds of `SoundEmitter`s when the game starts, and keep spatial
+/// sounds at their emitter's position
+pub fn play_sound_emitters(
+    mut commands: Commands,
+    asset_registry: Res<AssetRegistry>,
+    mut asset_importer: ResMut<AssetImporter>,
+    audio_settings: Res<AudioSettings>,
+    game_state: Res<GameState>,
+    emitter_query: Query<(Entity, &SoundEmitter, &GlobalTransform, Option<&EmitterPlayback>)>,
+    mut playback_query: Query<&mut GlobalTransform, (With<SoundPlayback>, Without<SoundEmitter>)>,
+) {
+    if !game_state.playing || game_state.paused {
+        return;
+    }
+
+    for (entity, emitter, transform, playback) in emitter_query.iter() {
+        if let Some(EmitterPlayback(playback_entity)) = playback {
+            if emitter.spatial {
+                if let Ok(mut playback_transform) = playback_query.get_mut(*playback_entity) {
+                    if *playback_transform != *transform {
+                        *playback_transform = *transform;
+                    }
+                }
+            }
+            continue;
+        }
+
+        let Some(path) = emitter.sound.as_deref().filter(|_| emitter.autoplay) else {
+            continue;
+        };
+        let Some(source) = sound_source(&asset_registry, &mut asset_importer, path) else {
+            continue;
+        };
+        let playback = SoundPlayback {
+            bus: emitter.bus,
+            volume: emitter.volume,
+            emitter: Some(entity),
+            preview: false,
+            stalled: false,
+        };
+        let position = emitter.spatial.then(|| transform.translation().truncate());
+        let playback_entity = spawn_sound(&mut commands, source, playback, position, emitter.looping, &audio_settings);
+        commands.entity(entity).insert(EmitterPlayback(playback_entity));
+    }
+}
+
+/// Keep playing sounds in line with the bus volumes and the game state
+///
+/// Game sounds pause with the game and stop when it stops, as do the sounds of
+/// emitters that are despawned. Sounds that loaded a frame ago without starting
+/// have no audio output to play on, and are dropped.
+pub fn update_playing_sounds(
+    mut commands: Commands,
+    audio_settings: Res<AudioSettings>,
+    game_state: Res<GameState>,
+    audio_sources: Res<Assets<AudioSource>>,
+    asset_server: Res<AssetServer>,
+    mut sound_query: Query<PlayingSoundData>,
+    emitter_query: Query<(), With<SoundEmitter>>,
+) {
+    for (entity, mut playback, source, mut settings, sink, spatial_sink) in sound_query.iter_mut() {
+        let emitter_gone = playback.emitter.is_some_and(|emitter| !emitter_query.contains(emitter));
+        if (!playback.preview && !game_state.playing) || emitter_gone {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let sink: Option<&dyn AudioSinkPlayback> = match (sink, spatial_sink) {
+            (Some(sink), _) => Some(sink),
+            (None, Some(sink)) => Some(sink),
+            (None, None) => None,
+        };
+        let volume = audio_settings.volume(playback.bus) * playback.volume;
+
+        let Some(sink) = sink else {
+            let failed = matches!(asset_server.get_load_state(source), Some(bevy::asset::LoadState::Failed));
+            if failed || playback.stalled {
+                commands.entity(entity).despawn();
+            } else if audio_sources.contains(source) {
+                playback.stalled = true;
+            }
+            if audio_settings.is_changed() {
+                settings.volume = Volume::new(volume);
+            }
+            continue;
+        };
+
+        if audio_settings.is_changed() {
+            sink.set_volume(volume);
+        }
+        if !playback.preview && game_state.is_changed() {
+            if game_state.paused {
+                sink.pause();
+            } else {
+                sink.play();
+            }
+        }
+    }
+}
+
+/// Play or stop the sound the asset browser is previewing, in edit mode or play mode
+///
+/// The preview plays at the master volume only, and the browser's request is
+/// cleared once the sound has played through.
+pub fn preview_asset_sound(
+    mut commands: Commands,
+    mut browser_state: ResMut<AssetBrowserState>,
+    asset_registry: Res<AssetRegistry>,
+    audio_settings: Res<AudioSettings>,
+    mut playing: Local<Option<(String, Entity)>>,
+    preview_query: Query<(), With<SoundPlayback>>,
+) {
+    // The preview entity despawns itself once the sound ends
+    if let Some((_, entity)) = &*playing {
+        if !preview_query.contains(*entity) {
+            *playing = None;
+            browser_state.previewed_sound = None;
+        }
+    }
+
+    if playing.as_ref().map(|(path, _)| path) == browser_state.previewed_sound.as_ref() {
+        return;
+    }
+    if let Some((_, entity)) = playing.take() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.despawn();
+        }
+    }
+
+    let Some(path) = browser_state.previewed_sound.clone() else {
+        return;
+    };
+    let Some(source) = asset_registry.get_sound(&path).cloned() else {
+        browser_state.previewed_sound = None;
+        return;
+    };
+    let playback = SoundPlayback {
+        bus: AudioBus::Sfx,
+        volume: 1.0,
+        emitter: None,
+        preview: true,
+        stalled: false,
+    };
+    let mut preview_settings = audio_settings.clone();
+    preview_settings.sfx = 1.0;
+    let entity = spawn_sound(&mut commands, source, playback, None, false, &preview_settings);
+    *playing = Some((path, entity));
+}
+
+/// Source of a sound by its asset path, starting an import if the file exists but
+/// isn't known to the registry yet
+fn sound_source(asset_registry: &AssetRegistry, asset_importer: &mut AssetImporter, path: &str) -> Option<Handle<AudioSource>> {
+    if let Some(handle) = asset_registry.get_sound(path) {
+        return Some(handle.clone());
+    }
+
+    let failed = asset_importer.failed_imports.iter().any(|(failed, _)| failed == path);
+    if !failed && std::path::Path::new(&format!("assets/{}", path)).exists() {
+        asset_importer.queue_import(path.to_string());
+    }
+    None
+}
+
+/// Spawn the entity a sound plays on, from a position in the world if it has one
+fn spawn_sound(
+    commands: &mut Commands,
+    source: Handle<AudioSource>,
+    playback: SoundPlayback,
+    position: Option<Vec2>,
+    looping: bool,
+    audio_settings: &AudioSettings,
+) -> Entity {
+    let settings = PlaybackSettings {
+        mode: if looping { PlaybackMode::Loop } else { PlaybackMode::Despawn },
+        volume: Volume::new(audio_settings.volume(playback.bus) * playback.volume),
+        spatial: position.is_some(),
+        spatial_scale: Some(SpatialScale::new_2d(SPATIAL_SCALE)),
+        ..default()
+    };
+    let transform = GlobalTransform::from_translation(position.unwrap_or_default().extend(0.0));
+    commands
+        .spawn((AudioBundle { source, settings }, transform, playback))
+        .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const SOUND: &str = "sounds/shoot.ogg";
+
+    /// Headless app playing sounds without an audio device, so no sink is ever added
+    fn sound_app(audio_settings: AudioSettings) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<AudioSource>()
+            .add_event::<PlaySound>()
+            .insert_resource(GameState { playing: true, ..default() })
+            .insert_resource(audio_settings)
+            .init_resource::<AssetImporter>()
+            .add_systems(Update, (play_sounds, update_playing_sounds).chain());
+
+        let mut asset_registry = AssetRegistry::new();
+        let handle = app.world.resource_mut::<Assets<AudioSource>>().add(AudioSource { bytes: Arc::from([]) });
+        asset_registry.loaded_sounds.insert(SOUND.to_string(), handle);
+        app.insert_resource(asset_registry);
+        app
+    }
+
+    fn playing_sounds(app: &mut App) -> Vec<(Entity, f32)> {
+        app.world
+            .query_filtered::<(Entity, &PlaybackSettings), With<SoundPlayback>>()
+            .iter(&app.world)
+            .map(|(entity, settings)| (entity, settings.volume.get()))
+            .collect()
+    }
+
+    #[test]
+    fn sounds_play_at_their_bus_volume() {
+        let mut app = sound_app(AudioSettings { master: 0.5, sfx: 0.8, ..default() });
+        app.world.send_event(PlaySound { volume: 0.5, ..PlaySound::sfx(SOUND) });
+        app.update();
+
+        let sounds = playing_sounds(&mut app);
+        assert_eq!(sounds.len(), 1);
+        assert!((sounds[0].1 - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sounds_stop_with_the_game() {
+        let mut app = sound_app(AudioSettings::default());
+        app.world.send_event(PlaySound::sfx(SOUND));
+        app.update();
+        assert_eq!(playing_sounds(&mut app).len(), 1);
+
+        app.world.resource_mut::<GameState>().playing = false;
+        app.update();
+        assert!(playing_sounds(&mut app).is_empty());
+    }
+
+    #[test]
+    fn sounds_without_audio_output_are_dropped() {
+        let mut app = sound_app(AudioSettings::default());
+        app.world.send_event(PlaySound::sfx(SOUND));
+        app.update();
+        assert_eq!(playing_sounds(&mut app).len(), 1);
+
+        // Loaded but never given a sink: stalled on one frame, despawned on the next
+        app.update();
+        app.update();
+        assert!(playing_sounds(&mut app).is_empty());
+    }
+}
@@ -1,23 +1,40 @@
 //! Camera control and positioning systems
 
 use bevy::prelude::*;
+use bevy::render::render_resource::Extent3d;
+use bevy::utils::HashMap;
 use bevy::window::PrimaryWindow;
+use bevy_egui::EguiSettings;
 
 use crate::components::*;
 use crate::resources::*;
 
-/// Handle camera movement and following
+/// How fast shakes wobble, in cycles per second
+const SHAKE_FREQUENCY: f32 = 15.0;
+
+/// Event that shakes every game camera, adding `trauma` to its shake strength
+///
+/// Gameplay systems send this on hits, explosions and the like.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+/// Handle editor camera movement and following
+///
+/// Only the scene view's camera is driven here; the game view looks through the
+/// scene's game camera instead.
 pub fn camera_movement(
-    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<EditorCamera>>,
     mut camera_controller: ResMut<CameraController>,
-    entity_query: Query<&Transform, (Without<Camera>, With<Player>)>,
+    entity_query: Query<&GlobalTransform, Without<Camera>>,
     time: Res<Time>,
 ) {
     for (mut camera_transform, mut projection) in camera_query.iter_mut() {
         // Handle entity following
         if let Some(following_entity) = camera_controller.following_entity {
             if let Ok(entity_transform) = entity_query.get(following_entity) {
-                camera_controller.target_position = entity_transform.translation.truncate();
+                camera_controller.target_position = entity_transform.translation().truncate();
             } else {
                 // Entity no longer exists, stop following
                 camera_controller.following_entity = None;
@@ -48,19 +65,190 @@ pub fn camera_movement(
     }
 }
 
+/// Move game cameras after their targets in play mode and keep them inside their bounds
+///
+/// The camera moves only as far as it takes to bring the target, led by its
+/// velocity times `look_ahead`, back inside the dead zone, and eases there over
+/// `smoothing` seconds. Follow writes the camera's own transform, so a camera
+/// that follows should sit at the top of the hierarchy.
+pub fn game_camera_follow(
+    mut camera_query: Query<(Entity, &mut Transform, &GameCamera)>,
+    target_query: Query<(&EntityId, &GlobalTransform)>,
+    view_camera_query: Query<&Camera, With<GameViewCamera>>,
+    game_state: Res<GameState>,
+    time: Res<Time>,
+    mut last_target_positions: Local<HashMap<Entity, Vec2>>,
+) {
+    if !game_state.playing {
+        last_target_positions.clear();
+        return;
+    }
+    let delta = time.delta_seconds();
+    if game_state.paused || delta <= 0.0 {
+        return;
+    }
+    let viewport_size = view_camera_query.iter().find_map(|camera| camera.logical_viewport_size());
+    
+    for (camera_entity, mut transform, game_camera) in camera_query.iter_mut() {
+        let mut position = transform.translation.truncate();
+        
+        let target = game_camera.follow.and_then(|follow| {
+            target_query
+                .iter()
+                .find(|(id, _)| **id == follow)
+                .map(|(_, global_transform)| global_transform.translation().truncate())
+        });
+        if let Some(target) = target {
+            let velocity = last_target_positions
+                .insert(camera_entity, target)
+                .map_or(Vec2::ZERO, |last| (target - last) / delta);
+            let goal = target + velocity * game_camera.look_ahead;
+            
+            let half_dead_zone = (game_camera.dead_zone * 0.5).max(Vec2::ZERO);
+            let offset = goal - position;
+            let desired = position + offset - offset.clamp(-half_dead_zone, half_dead_zone);
+            
+            position = if game_camera.smoothing > 0.0 {
+                position.lerp(desired, 1.0 - (-delta / game_camera.smoothing).exp())
+            } else {
+                desired
+            };
+        }
+        
+        if let (Some(bounds), Some(viewport_size)) = (game_camera.bounds, viewport_size) {
+            position = clamp_view_to_bounds(position, viewport_size * game_camera.zoom, bounds);
+        }
+        
+        if position != transform.translation.truncate() {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
+
+/// Keep a view of `view_size` centred on `position` inside `bounds`, centring it on
+/// any axis where the bounds are smaller than the view
+fn clamp_view_to_bounds(position: Vec2, view_size: Vec2, bounds: Rect) -> Vec2 {
+    let min = bounds.min + view_size * 0.5;
+    let max = bounds.max - view_size * 0.5;
+    let center = bounds.center();
+    Vec2::new(
+        if min.x <= max.x { position.x.clamp(min.x, max.x) } else { center.x },
+        if min.y <= max.y { position.y.clamp(min.y, max.y) } else { center.y },
+    )
+}
+
+/// Add shake requested through `CameraShake` events and let it die down over time
+pub fn game_camera_shake(
+    mut shake_events: EventReader<CameraShake>,
+    mut camera_query: Query<&mut GameCamera>,
+    game_state: Res<GameState>,
+    time: Res<Time>,
+) {
+    if !game_state.playing {
+        shake_events.clear();
+        return;
+    }
+    
+    for shake in shake_events.read() {
+        for mut game_camera in camera_query.iter_mut() {
+            game_camera.add_trauma(shake.trauma);
+        }
+    }
+    
+    if game_state.paused {
+        return;
+    }
+    for mut game_camera in camera_query.iter_mut() {
+        if game_camera.trauma > 0.0 {
+            game_camera.trauma = (game_camera.trauma - game_camera.shake_decay * time.delta_seconds()).max(0.0);
+        }
+    }
+}
+
+/// Smooth noise from -1 to 1, different for each `seed`
+fn shake_noise(time: f32, seed: f32) -> f32 {
+    let phase = time * SHAKE_FREQUENCY * std::f32::consts::TAU;
+    ((phase + seed * 12.9898).sin() + 0.5 * (phase * 2.3 + seed * 78.233).sin()) / 1.5
+}
+
+/// Frame the game view through the scene's game camera, shaken by its trauma
+///
+/// Without a game camera in the scene, the game view looks at the origin.
+pub fn sync_game_view_camera(
+    game_camera_query: Query<(&GlobalTransform, &GameCamera)>,
+    mut view_camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<GameViewCamera>>,
+    time: Res<Time>,
+) {
+    let (position, rotation, zoom) = match game_camera_query.iter().next() {
+        Some((global_transform, game_camera)) => {
+            let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+            
+            let shake = game_camera.trauma * game_camera.trauma;
+            let elapsed = time.elapsed_seconds();
+            let offset = Vec2::new(shake_noise(elapsed, 0.0), shake_noise(elapsed, 1.0)) * game_camera.shake_offset * shake;
+            let angle = shake_noise(elapsed, 2.0) * game_camera.shake_angle * shake;
+            
+            (translation.truncate() + offset, rotation * Quat::from_rotation_z(angle), game_camera.zoom)
+        }
+        None => (Vec2::ZERO, Quat::IDENTITY, 1.0),
+    };
+    
+    for (mut transform, mut projection) in view_camera_query.iter_mut() {
+        // Keep the camera's own depth so it still sees every sprite
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        transform.rotation = rotation;
+        projection.scale = zoom;
+    }
+}
+
 /// Update mouse world position for editor interactions
+///
+/// The editor camera renders into the scene view tab, so the cursor is mapped
+/// through the tab's area rather than the whole window.
 pub fn update_mouse_world_position(
     mut editor_state: ResMut<EditorState>,
+    viewports: Res<EditorViewports>,
+    egui_settings: Res<EguiSettings>,
     window_query: Query<&Window, With<PrimaryWindow>>,
-    camera_query: Query<(&Camera, &GlobalTransform), With<Camera>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<EditorCamera>>,
 ) {
+    let Some(viewport_rect) = viewports.scene.rect else {
+        return;
+    };
     if let Ok(window) = window_query.get_single() {
         if let Ok((camera, camera_transform)) = camera_query.get_single() {
             if let Some(cursor_position) = window.cursor_position() {
-                if let Some(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) {
+                // Tab areas are in egui points, which egui scales from window pixels
+                let viewport_position = cursor_position / egui_settings.scale_factor - viewport_rect.min;
+                if let Some(world_position) = camera.viewport_to_world_2d(camera_transform, viewport_position) {
                     editor_state.mouse_world_position = world_position;
                 }
             }
         }
     }
+}
+
+/// Resize each viewport's image to the tab showing it, so the world is drawn at
+/// one image pixel per egui point
+pub fn resize_viewport_images(
+    viewports: Res<EditorViewports>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for target in [&viewports.scene, &viewports.game] {
+        let Some(rect) = target.rect else {
+            continue; // Tab hidden, keep the last size
+        };
+        let size = rect.size().max(Vec2::ONE).as_uvec2();
+        if images.get(&target.image).is_some_and(|image| image.size() != size) {
+            if let Some(image) = images.get_mut(&target.image) {
+                image.resize(Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                });
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+//! Log capture for the console tab
+//!
+//! Bevy's log subscriber is extended with a layer that keeps every log line that
+//! passes the log filter; the lines are handed to the [`ConsoleLog`] resource each
+//! frame. Layers are installed before the app has a world, so the lines wait in a
+//! static buffer until then.
+
+use std::fmt::{Debug, Write};
+use std::sync::Mutex;
+
+use bevy::log::tracing_subscriber::{layer::Context, prelude::*, Layer};
+use bevy::log::BoxedSubscriber;
+use bevy::prelude::*;
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::{Event, Subscriber};
+
+use crate::resources::*;
+
+/// Log lines captured since the console last collected them
+static PENDING_LOGS: Mutex<Vec<ConsoleEntry>> = Mutex::new(Vec::new());
+
+/// Add the console's log capture to Bevy's log subscriber
+///
+/// Pass this as `LogPlugin::update_subscriber`.
+pub fn console_subscriber(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    Box::new(subscriber.with(ConsoleLayer))
+}
+
+/// Move the captured log lines into the console
+pub fn collect_console_logs(mut console_log: ResMut<ConsoleLog>) {
+    let entries = match PENDING_LOGS.lock() {
+        Ok(mut pending) if !pending.is_empty() => std::mem::take(&mut *pending),
+        _ => return,
+    };
+    for entry in entries {
+        console_log.push(entry);
+    }
+}
+
+/// Tracing layer copying log events into the pending buffer
+struct ConsoleLayer;
+
+impl<S: Subscriber> Layer<S> for ConsoleLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let entry = ConsoleEntry {
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        };
+
+        // Without the console collecting them, keep only the newest lines
+        if let Ok(mut pending) = PENDING_LOGS.lock() {
+            if pending.len() >= MAX_CONSOLE_ENTRIES {
+                pending.remove(0);
+            }
+            pending.push(entry);
+        }
+    }
+}
+
+/// Formats an event's message followed by its other fields
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            let fields = std::mem::take(&mut self.message);
+            let _ = write!(self.message, "{:?}{}", value, fields);
+        } else {
+            let _ = write!(self.message, " {}={:?}", field.name(), value);
+        }
+    }
+}
@@ -1,10 +1,16 @@
 //! Editor-specific systems for development tools
 
 use bevy::prelude::*;
+use bevy_egui::EguiContexts;
 
 use crate::components::*;
 use crate::resources::*;
-use crate::scene::spawn_entity;
+use crate::history::{DeleteEntities, EditorHistory, RecordSpawn, Redo, Undo};
+use crate::scene::{clear_scene, convert_scene_file, load_scene, save_scene, spawn_entity, SceneMetadata};
+use crate::scene::prefab::{refresh_prefab_overrides, InstantiatePrefab};
+use crate::systems::tween::end_timeline_preview;
+use crate::ui::clipboard::{CopySelection, DuplicateSelection, PasteClipboard};
+use crate::ui::inspector::is_hidden_component;
 
 /// Handle editor-specific functionality
 pub fn editor_update(
@@ -58,12 +64,273 @@ pub fn entity_spawn_system(
     mut scene_manager: ResMut<SceneManager>,
 ) {
     if scene_manager.should_spawn {
-        spawn_entity(
+        scene_manager.should_spawn = false;
+
+        if let Some(path) = scene_manager.spawn_prefab.clone() {
+            let position = scene_manager.spawn_position.extend(scene_manager.spawn_z);
+            commands.add(InstantiatePrefab { path, position });
+            return;
+        }
+
+        let id = scene_manager.allocate_id();
+        let entity = spawn_entity(
             &mut commands,
             scene_manager.spawn_entity_type,
+            id,
             scene_manager.spawn_position,
             Some(scene_manager.spawn_z),
         );
-        scene_manager.should_spawn = false;
+        commands.add(RecordSpawn { entity });
+    }
+}
+
+/// Carry out scene file operations queued by the UI
+///
+/// Runs with exclusive world access because saving and loading go through reflection.
+pub fn scene_file_system(world: &mut World) {
+    if world.resource::<SceneManager>().awaiting_confirmation.is_some() {
+        return;
+    }
+    
+    let operations = std::mem::take(&mut world.resource_mut::<SceneManager>().pending_operations);
+    // Saving would write the game's live state, and loading would be undone when play stops
+    if !operations.is_empty() && world.resource::<GameState>().playing {
+        warn!("Scene operations are not available while the game is playing");
+        world.resource_mut::<SceneManager>().status_message = Some("Stop the game before saving or loading scenes".to_string());
+        return;
+    }
+    // Scene files get entities as authored, not posed by the timeline preview
+    if operations.iter().any(|operation| matches!(operation, SceneOperation::Save(_))) {
+        end_timeline_preview(world);
+    }
+    let mut operations = operations.into_iter();
+    while let Some(operation) = operations.next() {
+        // Ask before throwing away unsaved work, holding back anything queued after
+        if operation.replaces_scene() && world.resource::<ProjectManager>().unsaved_changes {
+            let mut scene_manager = world.resource_mut::<SceneManager>();
+            scene_manager.awaiting_confirmation = Some(operation);
+            scene_manager.pending_operations.extend(operations);
+            return;
+        }
+        
+        match operation {
+            SceneOperation::Save(path) => match save_scene(world, &path) {
+                Ok(()) => {
+                    let mut scene_manager = world.resource_mut::<SceneManager>();
+                    scene_manager.save_path = path.clone();
+                    scene_manager.add_recent_scene(&path);
+                    scene_manager.status_message = Some(format!("Saved {}", path));
+                    world.resource_mut::<ProjectManager>().unsaved_changes = false;
+                }
+                Err(e) => {
+                    error!("Failed to save scene to {}: {}", path, e);
+                    world.resource_mut::<SceneManager>().status_message = Some(format!("Failed to save {}: {}", path, e));
+                    // Do not carry on with anything that relied on the save
+                    return;
+                }
+            },
+            SceneOperation::Load(path) => {
+                // A bad file is reported without touching the current scene
+                if let Err(e) = load_scene(world, &path) {
+                    error!("Failed to load scene {}: {}", path, e);
+                    world.resource_mut::<SceneManager>().status_message = Some(format!("Failed to load {}: {}", path, e));
+                    return;
+                }
+                reset_editor_state(world);
+                
+                let mut scene_manager = world.resource_mut::<SceneManager>();
+                scene_manager.save_path = path.clone();
+                scene_manager.add_recent_scene(&path);
+                scene_manager.status_message = Some(format!("Loaded {}", path));
+                world.resource_mut::<ProjectManager>().unsaved_changes = false;
+            }
+            SceneOperation::Convert { from, to } => {
+                let type_registry = world.resource::<AppTypeRegistry>().clone();
+                let status = match convert_scene_file(&from, &to, &type_registry) {
+                    Ok(()) => format!("Converted {} to {}", from, to),
+                    Err(e) => {
+                        error!("Failed to convert scene {}: {}", from, e);
+                        format!("Failed to convert {}: {}", from, e)
+                    }
+                };
+                world.resource_mut::<SceneManager>().status_message = Some(status);
+            }
+            SceneOperation::New => {
+                clear_scene(world);
+                reset_editor_state(world);
+                let mut scene_manager = world.resource_mut::<SceneManager>();
+                scene_manager.metadata = SceneMetadata::default();
+                scene_manager.next_id = 0;
+                scene_manager.save_path = "scenes/untitled_scene.ron".to_string();
+                scene_manager.status_message = Some("New scene".to_string());
+                world.resource_mut::<ProjectManager>().unsaved_changes = false;
+            }
+        }
+    }
+}
+
+/// Drop the selection, edit history and play mode snapshot, which refer to the
+/// previous scene
+fn reset_editor_state(world: &mut World) {
+    world.resource_mut::<SelectedEntity>().clear();
+    world.resource_mut::<EditorHistory>().clear();
+    let mut editor_scene_state = world.resource_mut::<EditorSceneState>();
+    editor_scene_state.saved_scene = None;
+    editor_scene_state.saved_entities.clear();
+}
+
+/// Flag the project as modified whenever the edit history changes
+pub fn track_unsaved_changes(
+    history: Res<EditorHistory>,
+    mut project_manager: ResMut<ProjectManager>,
+    mut last_revision: Local<u64>,
+) {
+    if history.revision() != *last_revision {
+        *last_revision = history.revision();
+        project_manager.unsaved_changes = true;
+    }
+}
+
+/// Keep the `Selected` marker on exactly the selected entities
+pub fn sync_selection_markers(
+    mut commands: Commands,
+    selected_entity: Res<SelectedEntity>,
+    marked_query: Query<Entity, With<Selected>>,
+) {
+    if !selected_entity.is_changed() {
+        return;
+    }
+    
+    for entity in marked_query.iter() {
+        if !selected_entity.contains(entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
     }
-}
\ No newline at end of file
+    
+    for &entity in &selected_entity.entities {
+        if !marked_query.contains(entity) {
+            if let Some(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.insert(Selected);
+            }
+        }
+    }
+}
+
+/// Recompute prefab overrides whenever the edit history changes
+///
+/// Every edit goes through the history, so this catches inspector edits, drags,
+/// undo and redo alike.
+pub fn track_prefab_overrides(world: &mut World, mut last_revision: Local<u64>) {
+    let revision = world.resource::<EditorHistory>().revision();
+    if revision != *last_revision {
+        *last_revision = revision;
+        refresh_prefab_overrides(world);
+    }
+}
+
+/// Handle editing shortcuts: undo/redo, deleting the selection and the entity clipboard
+///
+/// Play mode is thrown away when it stops, so edits made during it would be lost.
+pub fn edit_shortcuts(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    editor_state: Res<EditorState>,
+    selected_entity: Res<SelectedEntity>,
+    mut scene_manager: ResMut<SceneManager>,
+) {
+    // Nothing is edited in play mode, and text fields keep their own undo and clipboard
+    if game_state.playing || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    
+    // Delete removes every selected entity as one undo step
+    if keyboard_input.just_pressed(KeyCode::Delete) && !selected_entity.is_empty() {
+        commands.add(DeleteEntities { entities: selected_entity.entities.clone() });
+    }
+    
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !ctrl {
+        return;
+    }
+    
+    // Ctrl+Z / Ctrl+Shift+Z (or Ctrl+Y) for the editor history
+    if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        if shift {
+            commands.add(Redo);
+        } else {
+            commands.add(Undo);
+        }
+    } else if keyboard_input.just_pressed(KeyCode::KeyY) {
+        commands.add(Redo);
+    }
+    
+    // Ctrl+C / Ctrl+X / Ctrl+V / Ctrl+D for the entity clipboard
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        commands.add(CopySelection { cut: false });
+    } else if keyboard_input.just_pressed(KeyCode::KeyX) {
+        commands.add(CopySelection { cut: true });
+    } else if keyboard_input.just_pressed(KeyCode::KeyV) {
+        commands.add(PasteClipboard { position: editor_state.mouse_world_position });
+    } else if keyboard_input.just_pressed(KeyCode::KeyD) {
+        commands.add(DuplicateSelection);
+    }
+    
+    // Ctrl+S saves the current scene
+    if keyboard_input.just_pressed(KeyCode::KeyS) {
+        let path = scene_manager.save_path.clone();
+        scene_manager.pending_operations.push(SceneOperation::Save(path));
+    }
+}
+
+/// Snapshot the selected entities' reflectable components for the inspector
+pub fn snapshot_inspected_components(world: &mut World) {
+    let selected = world.resource::<SelectedEntity>().entity;
+    let components = selected.map(|entity| reflect_inspected_components(world, entity)).unwrap_or_default();
+    let others = world
+        .resource::<SelectedEntity>()
+        .entities
+        .iter()
+        .filter(|&&entity| Some(entity) != selected)
+        .map(|&entity| (entity, reflect_inspected_components(world, entity)))
+        .collect();
+    
+    let mut snapshot = world.resource_mut::<InspectorSnapshot>();
+    snapshot.entity = selected;
+    snapshot.components = components;
+    snapshot.others = others;
+}
+
+/// Reflected copies of the components the inspector edits generically
+fn reflect_inspected_components(world: &World, entity: Entity) -> Vec<Box<dyn Reflect>> {
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+    
+    let mut components = Vec::new();
+    if let Some(entity_ref) = world.get_entity(entity) {
+        for component_id in entity_ref.archetype().components() {
+            let Some(type_id) = world.components().get_info(component_id).and_then(|info| info.type_id()) else {
+                continue;
+            };
+            
+            if is_hidden_component(type_id) {
+                continue;
+            }
+            
+            let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(type_id) else {
+                continue;
+            };
+            
+            if let Some(component) = reflect_component.reflect(entity_ref) {
+                // Prefer a concrete copy so typed widgets (colors, vectors) can downcast it
+                let concrete = type_registry
+                    .get_type_data::<ReflectFromReflect>(type_id)
+                    .and_then(|from_reflect| from_reflect.from_reflect(component));
+                components.push(concrete.unwrap_or_else(|| component.clone_value()));
+            }
+        }
+    }
+    
+    components
+}
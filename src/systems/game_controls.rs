@@ -1,13 +1,13 @@
 use bevy::prelude::*;
-use crate::resources::{GameState, EditorSceneState};
-use crate::components::*;
-use crate::scene::{save_scene_to_string, load_scene_from_string};
+use crate::components::EntityId;
+use crate::resources::{GameState, EditorSceneState, SelectedEntity};
+use crate::scene::restore_scene;
+use crate::history::EditorHistory;
 
 pub fn game_controls_system(
     mut game_state: ResMut<GameState>,
     mut editor_scene_state: ResMut<EditorSceneState>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Projectile>, Option<&Health>, Option<&Collision>, Option<&SpriteAsset>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
 ) {
     // Handle keyboard shortcuts
     if keyboard_input.just_pressed(KeyCode::KeyP) {
@@ -30,41 +30,33 @@ fn toggle_pause(game_state: &mut GameState) {
     }
 }
 
-fn save_scene_state(
-    editor_scene_state: &mut EditorSceneState,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Projectile>, Option<&Health>, Option<&Collision>, Option<&SpriteAsset>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-) {
-    // Save the current scene state as RON string
-    if let Ok(scene_data) = save_scene_to_string(entity_query) {
-        editor_scene_state.saved_scene_data = Some(scene_data);
-    } else {
-        warn!("Failed to save scene state");
-    }
-}
-
 pub fn handle_play_mode_transition(
     mut commands: Commands,
     game_state: Res<GameState>,
     mut editor_scene_state: ResMut<EditorSceneState>,
-    entity_query: Query<Entity, Or<(With<Player>, With<Enemy>, With<Projectile>)>>,
 ) {
     // Handle stopping play mode and restoring scene state
     if !game_state.playing && game_state.editor_mode {
-        if let Some(saved_data) = &editor_scene_state.saved_scene_data {
-            // Remove all current game entities
-            for entity in entity_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            
-            // Restore the saved scene
-            if let Err(e) = load_scene_from_string(&mut commands, saved_data) {
-                error!("Failed to restore scene: {}", e);
-            } else {
+        if let Some(saved_scene) = editor_scene_state.saved_scene.take() {
+            commands.add(move |world: &mut World| {
+                // Replace everything spawned or changed during play with the snapshot
+                restore_scene(world, &saved_scene);
+                world.resource_mut::<SelectedEntity>().clear();
                 info!("Scene state restored from saved data");
-            }
-            
-            // Clear saved data
-            editor_scene_state.saved_scene_data = None;
+                
+                // Restored entities are respawned, so point recorded actions at them by
+                // their stable ids
+                let saved_entities = std::mem::take(&mut world.resource_mut::<EditorSceneState>().saved_entities);
+                let remaps: Vec<(Entity, Entity)> = world
+                    .query::<(Entity, &EntityId)>()
+                    .iter(world)
+                    .filter_map(|(entity, id)| saved_entities.get(id).map(|&saved| (saved, entity)))
+                    .collect();
+                let mut history = world.resource_mut::<EditorHistory>();
+                for (from, to) in remaps {
+                    history.remap_entity(from, to);
+                }
+            });
         }
     }
-}
\ No newline at end of file
+}
@@ -4,6 +4,8 @@ use bevy::prelude::*;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::systems::audio::SoundEffects;
+use crate::systems::camera::CameraShake;
 
 /// Handle player shooting
 pub fn player_shooting(
@@ -13,6 +15,7 @@ pub fn player_shooting(
     mut shooting_stats: ResMut<ShootingStats>,
     time: Res<Time>,
     game_state: Res<GameState>,
+    mut sound_effects: SoundEffects,
 ) {
     // Only run when game is playing and not paused
     if !game_state.playing || game_state.paused {
@@ -46,6 +49,7 @@ pub fn player_shooting(
             
             shooting.cooldown = 0.3; // 300ms cooldown
             shooting_stats.shots_fired += 1;
+            sound_effects.play_at(|sounds| &sounds.shoot, transform.translation.truncate());
         }
     }
 }
@@ -104,6 +108,8 @@ pub fn collision_detection(
     mut enemy_query: Query<(Entity, &Transform, &Collision, &mut Health), With<Enemy>>,
     mut shooting_stats: ResMut<ShootingStats>,
     game_state: Res<GameState>,
+    mut camera_shake: EventWriter<CameraShake>,
+    mut sound_effects: SoundEffects,
 ) {
     // Only run when game is playing and not paused
     if !game_state.playing || game_state.paused {
@@ -122,9 +128,15 @@ pub fn collision_detection(
                 // Remove projectile
                 commands.entity(projectile_entity).despawn();
                 
-                // Remove enemy if health <= 0
+                // Remove enemy if health <= 0, shaking the camera harder for a kill
+                let position = enemy_transform.translation.truncate();
                 if enemy_health.current <= 0.0 {
                     commands.entity(enemy_entity).despawn();
+                    camera_shake.send(CameraShake { trauma: 0.5 });
+                    sound_effects.play_at(|sounds| &sounds.destroy, position);
+                } else {
+                    camera_shake.send(CameraShake { trauma: 0.25 });
+                    sound_effects.play_at(|sounds| &sounds.hit, position);
                 }
                 
                 break; // Projectile can only hit one enemy
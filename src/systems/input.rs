@@ -1,10 +1,21 @@
 //! Input handling systems
 
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::{MouseWheel, MouseScrollUnit};
+use bevy_egui::EguiContexts;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::history::{EditorAction, EditorHistory};
+use crate::scene::SceneEntityFilter;
+use crate::systems::picking::{pointer_over_ui, SpriteBounds};
+use crate::systems::transform_gizmo::{drag_handle, DragSnap, GizmoLayout};
+use crate::systems::tween::{is_previewing, EndTimelinePreview};
+
+/// How close to the last click, in pixels, a click has to be to cycle through
+/// the entities under it
+const CLICK_CYCLE_DISTANCE: f32 = 3.0;
 
 /// Handle player movement input
 pub fn player_movement(
@@ -41,75 +52,261 @@ pub fn player_movement(
 }
 
 /// Handle mouse interaction for entity selection and manipulation
+///
+/// Clicking a handle of the transform gizmo drags it. Otherwise clicking an entity
+/// selects the topmost one whose sprite is under the cursor and starts dragging the
+/// whole selection, and clicking the same spot again selects the next entity
+/// underneath. Shift- or Ctrl-clicking adds an entity to or removes it from the
+/// selection. Dragging from empty space draws a box that selects every entity whose
+/// centre is inside. Clicks on egui panels are left to egui.
+///
+/// Dragging the entity posed by the timeline preview ends the preview, and the
+/// drag starts from where the entity was before it.
 pub fn mouse_interaction(
-    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut commands: Commands,
+    pointer: ScenePointer,
     mut selected_entity: ResMut<SelectedEntity>,
     mut drag_state: ResMut<DragState>,
-    mut commands: Commands,
-    entity_query: Query<(Entity, &Transform, Option<&Selected>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    editor_state: Res<EditorState>,
+    targets: DragTargets,
+    mut history: ResMut<EditorHistory>,
+    mut last_click: Local<Option<Vec2>>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Left) {
-        let mouse_pos = editor_state.mouse_world_position;
-        let mut closest_entity = None;
-        let mut closest_distance = f32::INFINITY;
-        
-        // Find the closest entity to the mouse cursor
-        for (entity, transform, _) in entity_query.iter() {
-            let distance = transform.translation.truncate().distance(mouse_pos);
-            let entity_size = transform.scale.x.max(transform.scale.y) * 0.5;
-            
-            if distance < entity_size && distance < closest_distance {
-                closest_distance = distance;
-                closest_entity = Some(entity);
-            }
+    let ScenePointer {
+        mouse_input,
+        keyboard_input,
+        mut contexts,
+        editor_state,
+        viewports,
+        camera_controller,
+        game_state,
+    } = pointer;
+    let DragTargets {
+        entity_query,
+        sprite_bounds,
+        timeline_preview,
+        ..
+    } = &targets;
+    let mouse_pos = editor_state.mouse_world_position;
+    let additive = keyboard_input.any_pressed([
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+    ]);
+    
+    if mouse_input.just_pressed(MouseButton::Left) && !pointer_over_ui(contexts.ctx_mut(), viewports.scene.rect) {
+        // Gizmo handles sit on top of the entities, so they are picked first; play
+        // mode hides the gizmo, so its handles can't be grabbed then
+        let selection = selected_entity
+            .entities
+            .iter()
+            .filter_map(|&entity| sprite_bounds.world_bounds(entity));
+        let layout = GizmoLayout::new(selection, camera_controller.zoom);
+        let gizmo_handle = layout
+            .as_ref()
+            .filter(|_| !additive && !game_state.playing)
+            .and_then(|layout| layout.pick(editor_state.transform_tool, mouse_pos));
+        if let (Some(layout), Some(handle)) = (&layout, gizmo_handle) {
+            drag_state.dragging = true;
+            drag_state.handle = Some(handle);
+            drag_state.drag_origin = mouse_pos;
+            drag_state.pivot = layout.center();
+            drag_state.drag_starts = targets.drag_starts(&selected_entity.entities);
+            end_dragged_preview(&mut commands, &drag_state, timeline_preview);
+            *last_click = None;
+            return;
         }
         
+        // Entities under the cursor, topmost first, in world space so children count too
+        let mut hits: Vec<(Entity, f32)> = entity_query
+            .iter()
+            .filter(|&(entity, ..)| sprite_bounds.hit(entity, mouse_pos))
+            .map(|(entity, _, global_transform)| (entity, global_transform.translation().z))
+            .collect();
+        hits.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        
+        // Clicking the same spot again moves on to the next entity underneath
+        let repeated = last_click.is_some_and(|last| last.distance(mouse_pos) <= CLICK_CYCLE_DISTANCE * camera_controller.zoom);
+        let clicked_entity = match selected_entity.entity.and_then(|entity| hits.iter().position(|&(hit, _)| hit == entity)) {
+            Some(index) if repeated && !additive => Some(hits[(index + 1) % hits.len()].0),
+            _ => hits.first().map(|&(entity, _)| entity),
+        };
+        *last_click = Some(mouse_pos);
+        
         // Update selection
-        if let Some(new_selected) = closest_entity {
-            // Remove Selected component from previously selected entity
-            if let Some(old_selected) = selected_entity.entity {
-                commands.entity(old_selected).remove::<Selected>();
+        match clicked_entity {
+            Some(clicked) if additive => {
+                selected_entity.toggle(clicked);
             }
-            
-            // Add Selected component to new entity
-            commands.entity(new_selected).insert(Selected);
-            selected_entity.entity = Some(new_selected);
-            
-            // Start dragging
-            drag_state.dragging = true;
-            if let Ok((_, transform, _)) = entity_query.get(new_selected) {
-                drag_state.drag_offset = transform.translation.truncate() - mouse_pos;
+            Some(clicked) => {
+                // Clicking inside the selection keeps it, so the whole group can be dragged
+                if selected_entity.contains(clicked) {
+                    selected_entity.add(clicked);
+                } else {
+                    selected_entity.select(clicked);
+                }
+                
+                // Dragging an entity moves the selection like the gizmo's centre handle
+                drag_state.dragging = true;
+                drag_state.handle = Some(GizmoHandle::Center);
+                drag_state.drag_origin = mouse_pos;
+                drag_state.pivot = mouse_pos;
+                drag_state.drag_starts = targets.drag_starts(&selected_entity.entities);
+                end_dragged_preview(&mut commands, &drag_state, timeline_preview);
             }
-        } else {
-            // Deselect if clicking on empty space
-            if let Some(old_selected) = selected_entity.entity {
-                commands.entity(old_selected).remove::<Selected>();
+            None => {
+                // Start a box selection from empty space
+                if !additive {
+                    selected_entity.clear();
+                }
+                drag_state.dragging = false;
+                drag_state.marquee_start = Some(mouse_pos);
             }
-            selected_entity.entity = None;
-            drag_state.dragging = false;
         }
     }
     
     if mouse_input.just_released(MouseButton::Left) {
         drag_state.dragging = false;
+        drag_state.handle = None;
+        
+        // Record the whole drag as a single move of every dragged entity
+        let mut moves: Vec<EditorAction> = drag_state
+            .drag_starts
+            .drain(..)
+            .filter_map(|(entity, start, _)| {
+                let (_, transform, _) = entity_query.get(entity).ok()?;
+                (*transform != start).then(|| EditorAction::set_component(entity, Some(start), Some(*transform)))
+            })
+            .collect();
+        match moves.len() {
+            0 => {}
+            1 => history.push(moves.remove(0)),
+            _ => history.push(EditorAction::Group(moves)),
+        }
+        
+        // Finish a box selection
+        if let Some(start) = drag_state.marquee_start.take() {
+            let min = start.min(mouse_pos);
+            let max = start.max(mouse_pos);
+            let inside = entity_query
+                .iter()
+                .filter(|(_, _, global_transform)| {
+                    let position = global_transform.translation().truncate();
+                    position.cmpge(min).all() && position.cmple(max).all()
+                })
+                .map(|(entity, _, _)| entity);
+            
+            if additive {
+                for entity in inside {
+                    selected_entity.add(entity);
+                }
+            } else {
+                selected_entity.set(inside);
+            }
+        }
     }
 }
 
-/// Handle entity dragging
+/// Input and editor state the scene view's mouse handling reads
+#[derive(SystemParam)]
+pub struct ScenePointer<'w, 's> {
+    mouse_input: Res<'w, ButtonInput<MouseButton>>,
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    contexts: EguiContexts<'w, 's>,
+    editor_state: Res<'w, EditorState>,
+    viewports: Res<'w, EditorViewports>,
+    camera_controller: Res<'w, CameraController>,
+    game_state: Res<'w, GameState>,
+}
+
+/// Scene entities that can be clicked and dragged, with what a drag needs to know
+/// about them
+#[derive(SystemParam)]
+pub struct DragTargets<'w, 's> {
+    entity_query: Query<'w, 's, (Entity, &'static Transform, &'static GlobalTransform), SceneEntityFilter>,
+    parent_query: Query<'w, 's, &'static Parent>,
+    sprite_bounds: SpriteBounds<'w, 's>,
+    timeline_preview: Res<'w, TimelinePreview>,
+}
+
+impl DragTargets<'_, '_> {
+    /// Local and world transforms of the selected entities at the start of a drag
+    ///
+    /// Children follow their parent, so only the top of each selected subtree is
+    /// dragged. The world transform of an entity without a parent is its own
+    /// transform, which spares it rounding errors. An entity posed by the timeline
+    /// preview starts from its transform before the preview.
+    fn drag_starts(&self, selected: &[Entity]) -> Vec<(Entity, Transform, Transform)> {
+        selected
+            .iter()
+            .filter(|&&entity| !has_ancestor_in(entity, selected, &self.parent_query))
+            .filter_map(|&entity| self.entity_query.get(entity).ok())
+            .map(|(entity, transform, global_transform)| {
+                let original = self
+                    .timeline_preview
+                    .original
+                    .filter(|&(previewed, _, _)| previewed == entity)
+                    .map(|(_, original, _)| original);
+                let world = match (original, self.parent_query.get(entity)) {
+                    (None, Ok(_)) => global_transform.compute_transform(),
+                    (None, Err(_)) => *transform,
+                    (Some(original), Ok(parent)) => match self.entity_query.get(parent.get()) {
+                        Ok((_, _, parent_transform)) => parent_transform.mul_transform(original).compute_transform(),
+                        Err(_) => original,
+                    },
+                    (Some(original), Err(_)) => original,
+                };
+                (entity, original.unwrap_or(*transform), world)
+            })
+            .collect()
+    }
+}
+
+/// End the timeline preview if the drag that just started moves the previewed entity
+fn end_dragged_preview(commands: &mut Commands, drag_state: &DragState, timeline_preview: &TimelinePreview) {
+    if drag_state.drag_starts.iter().any(|&(entity, _, _)| is_previewing(timeline_preview, entity)) {
+        commands.add(EndTimelinePreview);
+    }
+}
+
+/// Whether any ancestor of `entity` is in `entities`
+fn has_ancestor_in(entity: Entity, entities: &[Entity], parent_query: &Query<&Parent>) -> bool {
+    parent_query.iter_ancestors(entity).any(|ancestor| entities.contains(&ancestor))
+}
+
+/// Handle entity dragging, applying the dragged gizmo handle to every dragged entity
+///
+/// Holding Shift while dragging a scale handle scales both axes evenly. Positions and
+/// rotations snap as set in the grid settings.
 pub fn entity_dragging(
     drag_state: Res<DragState>,
-    selected_entity: Res<SelectedEntity>,
-    mut entity_query: Query<&mut Transform>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut entity_query: Query<(&mut Transform, Option<&Parent>)>,
+    global_transform_query: Query<&GlobalTransform>,
     editor_state: Res<EditorState>,
+    grid_settings: Res<GridSettings>,
 ) {
-    if drag_state.dragging {
-        if let Some(entity) = selected_entity.entity {
-            if let Ok(mut transform) = entity_query.get_mut(entity) {
-                let new_position = editor_state.mouse_world_position + drag_state.drag_offset;
-                transform.translation.x = new_position.x;
-                transform.translation.y = new_position.y;
-            }
+    let Some(handle) = drag_state.handle.filter(|_| drag_state.dragging) else {
+        return;
+    };
+    // A click without movement leaves the entities untouched
+    let mouse_pos = editor_state.mouse_world_position;
+    if mouse_pos == drag_state.drag_origin {
+        return;
+    }
+    let uniform = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let drag_snap = DragSnap::from_settings(&grid_settings);
+    
+    for (entity, _, world_start) in &drag_state.drag_starts {
+        let world = drag_handle(handle, world_start, drag_state.pivot, drag_state.drag_origin, mouse_pos, uniform, drag_snap);
+        
+        if let Ok((mut transform, parent)) = entity_query.get_mut(*entity) {
+            // The gizmo works in world space, while a child's transform is relative to its parent
+            let parent_transform = parent.and_then(|parent| global_transform_query.get(parent.get()).ok());
+            *transform = match parent_transform {
+                Some(parent_transform) => GlobalTransform::from(world).reparented_to(parent_transform),
+                None => world,
+            };
         }
     }
 }
@@ -117,11 +314,13 @@ pub fn entity_dragging(
 /// Handle camera controls with right-click and drag
 pub fn camera_controls(
     mouse_input: Res<ButtonInput<MouseButton>>,
+    mut contexts: EguiContexts,
     mut camera_controller: ResMut<CameraController>,
     editor_state: Res<EditorState>,
+    viewports: Res<EditorViewports>,
     mut last_mouse_pos: Local<Option<Vec2>>,
 ) {
-    if mouse_input.just_pressed(MouseButton::Right) {
+    if mouse_input.just_pressed(MouseButton::Right) && !pointer_over_ui(contexts.ctx_mut(), viewports.scene.rect) {
         *last_mouse_pos = Some(editor_state.mouse_world_position);
     }
     
@@ -144,11 +343,17 @@ pub fn camera_controls(
     }
 }
 
-/// Handle mouse wheel zoom
+/// Handle mouse wheel zoom, unless the wheel is scrolling an egui panel
 pub fn handle_mouse_wheel_zoom(
     mut scroll_events: EventReader<MouseWheel>,
+    mut contexts: EguiContexts,
     mut camera_controller: ResMut<CameraController>,
+    viewports: Res<EditorViewports>,
 ) {
+    if pointer_over_ui(contexts.ctx_mut(), viewports.scene.rect) {
+        scroll_events.clear();
+        return;
+    }
     for event in scroll_events.read() {
         let zoom_delta = match event.unit {
             MouseScrollUnit::Line => event.y * 0.1,
@@ -1,6 +1,8 @@
 //! Game systems organized by functionality
 
 use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::view::RenderLayers;
 
 pub mod input;
 pub mod gameplay;
@@ -8,52 +10,69 @@ pub mod game_controls;
 pub mod camera;
 pub mod rendering;
 pub mod editor;
+pub mod transform_gizmo;
+pub mod picking;
+pub mod animation;
+pub mod tween;
+pub mod audio;
+pub mod console;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::scene::{spawn_entity, spawn_game_camera};
 
 /// Setup the game engine with initial entities and camera
 pub fn setup_engine(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut scene_manager: ResMut<SceneManager>,
+    viewports: Res<EditorViewports>,
+    mut gizmo_config_store: ResMut<GizmoConfigStore>,
 ) {
-    // Spawn camera
-    commands.spawn(Camera2dBundle::default());
-
-    // Spawn player
+    // Spawn the cameras, each rendering into its viewport tab's image
     commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::BLUE,
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(viewports.scene.image.clone()),
                 ..default()
             },
-            transform: Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::splat(50.0)),
             ..default()
         },
-        Player,
-        Health { current: 100.0, max: 100.0 },
-        Shooting { cooldown: 0.0 },
-        Collision { radius: 25.0 },
+        RenderLayers::default().with(EDITOR_RENDER_LAYER),
+        EditorCamera,
     ));
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(viewports.game.image.clone()),
+                ..default()
+            },
+            ..default()
+        },
+        GameViewCamera,
+        // The player hears the game from where the game view looks
+        SpatialListener::new(audio::LISTENER_EAR_GAP),
+    ));
+    
+    // Gizmos are editor overlays, so the game view leaves them out
+    let (gizmo_config, _) = gizmo_config_store.config_mut::<DefaultGizmoConfigGroup>();
+    gizmo_config.render_layers = RenderLayers::layer(EDITOR_RENDER_LAYER);
+
+    // Spawn player
+    let player_id = scene_manager.allocate_id();
+    spawn_entity(&mut commands, EntityType::Player, player_id, Vec2::ZERO, None);
+
+    // Spawn the scene's game camera, following the player
+    let id = scene_manager.allocate_id();
+    spawn_game_camera(&mut commands, id, Vec2::ZERO, Some(player_id));
 
     // Spawn some enemies
     for i in 0..5 {
         let x = (i as f32 - 2.0) * 150.0;
         let y = 200.0;
         
-        commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::RED,
-                    ..default()
-                },
-                transform: Transform::from_xyz(x, y, 0.0).with_scale(Vec3::splat(40.0)),
-                ..default()
-            },
-            Enemy,
-            Health { current: 50.0, max: 50.0 },
-            Collision { radius: 20.0 },
-        ));
+        let id = scene_manager.allocate_id();
+        spawn_entity(&mut commands, EntityType::Enemy, id, Vec2::new(x, y), None);
     }
 }
\ No newline at end of file
@@ -0,0 +1,136 @@
+//! Picking entities in the editor viewport by the area their sprites cover
+//!
+//! A sprite covers its custom size, its texture rectangle or its texture's size,
+//! placed around its anchor and then scaled, rotated and moved by its world
+//! transform. Entities without a sprite cover a unit square, so their scale alone
+//! gives their size.
+
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy_egui::egui;
+
+/// Area a sprite covers before its transform is applied
+pub fn sprite_rect(sprite: Option<&Sprite>, texture: Option<&Handle<Image>>, images: &Assets<Image>) -> Rect {
+    let Some(sprite) = sprite else {
+        return Rect::from_center_size(Vec2::ZERO, Vec2::ONE);
+    };
+    let size = sprite
+        .custom_size
+        .or_else(|| sprite.rect.map(|rect| rect.size()))
+        .or_else(|| texture.and_then(|texture| images.get(texture)).map(|image| image.size_f32()))
+        .unwrap_or(Vec2::ONE);
+
+    // Bevy places the quad so the anchor point sits on the entity's position
+    Rect::from_center_size(-sprite.anchor.as_vec() * size, size)
+}
+
+/// Corners of a local rectangle moved into world space
+fn world_corners(global_transform: &GlobalTransform, local: Rect) -> [Vec2; 4] {
+    [
+        Vec2::new(local.min.x, local.min.y),
+        Vec2::new(local.max.x, local.min.y),
+        Vec2::new(local.max.x, local.max.y),
+        Vec2::new(local.min.x, local.max.y),
+    ]
+    .map(|corner| global_transform.transform_point(corner.extend(0.0)).truncate())
+}
+
+/// Axis-aligned world-space bounds of a local rectangle under a world transform
+pub fn entity_bounds(global_transform: &GlobalTransform, local: Rect) -> Rect {
+    let corners = world_corners(global_transform, local);
+    let min = corners.iter().copied().reduce(Vec2::min).unwrap_or_default();
+    let max = corners.iter().copied().reduce(Vec2::max).unwrap_or_default();
+    Rect::from_corners(min, max)
+}
+
+/// Whether a world-space point is inside a local rectangle under a world transform
+///
+/// The point is moved into the entity's own space, so rotated entities are picked
+/// by their actual outline rather than their bounds.
+pub fn contains_point(global_transform: &GlobalTransform, local: Rect, point: Vec2) -> bool {
+    let affine = global_transform.affine();
+    if affine.matrix3.determinant() == 0.0 {
+        return false; // Scaled down to nothing
+    }
+    let local_point = affine.inverse().transform_point3(point.extend(global_transform.translation().z));
+    local.contains(local_point.truncate())
+}
+
+/// World-space bounds of an entity, for code working on the world directly
+pub fn world_entity_bounds(world: &World, entity: Entity) -> Option<Rect> {
+    let global_transform = world.get::<GlobalTransform>(entity)?;
+    let local = sprite_rect(
+        world.get::<Sprite>(entity),
+        world.get::<Handle<Image>>(entity),
+        world.resource::<Assets<Image>>(),
+    );
+    Some(entity_bounds(global_transform, local))
+}
+
+/// Placement, size and visibility of an entity's sprite
+type SpriteData = (
+    &'static GlobalTransform,
+    Option<&'static Sprite>,
+    Option<&'static Handle<Image>>,
+    Option<&'static InheritedVisibility>,
+);
+
+/// Sprite sizes and visibility needed to pick entities and lay out gizmos
+#[derive(SystemParam)]
+pub struct SpriteBounds<'w, 's> {
+    sprites: Query<'w, 's, SpriteData>,
+    images: Res<'w, Assets<Image>>,
+}
+
+impl SpriteBounds<'_, '_> {
+    /// Area the entity's sprite covers before its transform is applied
+    pub fn local_rect(&self, entity: Entity) -> Option<Rect> {
+        let (_, sprite, texture, _) = self.sprites.get(entity).ok()?;
+        Some(sprite_rect(sprite, texture, &self.images))
+    }
+
+    /// Axis-aligned world-space bounds of the entity
+    pub fn world_bounds(&self, entity: Entity) -> Option<Rect> {
+        let (global_transform, ..) = self.sprites.get(entity).ok()?;
+        Some(entity_bounds(global_transform, self.local_rect(entity)?))
+    }
+
+    /// Corners of the area the entity covers in world space, following its rotation
+    pub fn world_outline(&self, entity: Entity) -> Option<[Vec2; 4]> {
+        let (global_transform, ..) = self.sprites.get(entity).ok()?;
+        Some(world_corners(global_transform, self.local_rect(entity)?))
+    }
+
+    /// Whether a visible entity covers a world-space point
+    pub fn hit(&self, entity: Entity, point: Vec2) -> bool {
+        let Ok((global_transform, sprite, texture, visibility)) = self.sprites.get(entity) else {
+            return false;
+        };
+        if visibility.is_some_and(|visibility| !visibility.get()) {
+            return false;
+        }
+        contains_point(global_transform, sprite_rect(sprite, texture, &self.images), point)
+    }
+}
+
+/// Whether the pointer is over egui rather than the game world
+///
+/// The scene view tab is part of egui's background layer but shows the world, so
+/// the pointer counts as over the world there unless a window or popup covers it.
+pub fn pointer_over_ui(ctx: &egui::Context, viewport_rect: Option<Rect>) -> bool {
+    if ctx.is_using_pointer() {
+        return true;
+    }
+    if !ctx.is_pointer_over_area() {
+        return false;
+    }
+    let Some(pos) = ctx.pointer_interact_pos() else {
+        return false;
+    };
+
+    let in_viewport = viewport_rect.is_some_and(|rect| rect.contains(Vec2::new(pos.x, pos.y)));
+    let on_background = ctx
+        .layer_id_at(pos)
+        .is_none_or(|layer| layer.order == egui::Order::Background);
+    !(in_viewport && on_background)
+}
@@ -1,18 +1,21 @@
 //! Rendering systems for visual effects and overlays
 
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
+use bevy::render::view::RenderLayers;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::systems::picking::SpriteBounds;
+
+/// Color of the outline around selected entities
+const SELECTION_OUTLINE_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
 
 /// Render grid overlay
 pub fn render_grid_overlay(
     mut commands: Commands,
     grid_settings: Res<GridSettings>,
     mut grid_state: ResMut<GridState>,
-    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &Transform, &OrthographicProjection), With<EditorCamera>>,
     grid_line_query: Query<Entity, With<GridLine>>,
 ) {
     if !grid_settings.enabled {
@@ -25,13 +28,13 @@ pub fn render_grid_overlay(
         return;
     }
     
-    if let Ok((camera_transform, projection)) = camera_query.get_single() {
-        if let Ok(window) = window_query.get_single() {
+    if let Ok((camera, camera_transform, projection)) = camera_query.get_single() {
+        if let Some(viewport_size) = camera.logical_viewport_size() {
             let camera_pos = camera_transform.translation.truncate();
             let zoom = projection.scale;
             
             // Check if we need to update the grid
-            let needs_update = grid_state.needs_update(camera_pos, zoom, &grid_settings);
+            let needs_update = grid_state.needs_update(camera_pos, zoom, viewport_size, &grid_settings);
             let has_no_grid_lines = grid_line_query.is_empty();
             
             // Force update if grid is enabled but no lines exist
@@ -42,8 +45,7 @@ pub fn render_grid_overlay(
                 }
                 
                 // Calculate visible area
-                let window_size = Vec2::new(window.width(), window.height());
-                let visible_size = window_size * zoom;
+                let visible_size = viewport_size * zoom;
                 let half_visible = visible_size * 0.5;
                 
                 let min_x = camera_pos.x - half_visible.x;
@@ -69,6 +71,7 @@ pub fn render_grid_overlay(
                             ..default()
                         },
                         GridLine,
+                        RenderLayers::layer(EDITOR_RENDER_LAYER),
                     ));
                     x += spacing;
                 }
@@ -88,6 +91,7 @@ pub fn render_grid_overlay(
                             ..default()
                         },
                         GridLine,
+                        RenderLayers::layer(EDITOR_RENDER_LAYER),
                     ));
                     y += spacing;
                 }
@@ -112,33 +116,68 @@ pub fn update_background_image(
     }
 }
 
-/// Update visual indicators for selected entities
-pub fn update_selection_visuals(
-    mut selected_query: Query<&mut Sprite, (With<Selected>, Without<GridLine>, Without<BackgroundImage>)>,
-    mut unselected_query: Query<&mut Sprite, (Without<Selected>, Without<GridLine>, Without<BackgroundImage>, Or<(With<Player>, With<Enemy>)>)>,
+/// Outline the selected entities in the scene view
+///
+/// Drawn with gizmos rather than by brightening the sprite, since sprite colors
+/// are saved with the scene.
+pub fn draw_selection_outlines(
+    selected_query: Query<Entity, With<Selected>>,
+    sprite_bounds: SpriteBounds,
+    mut gizmos: Gizmos,
 ) {
-    // Highlight selected entities
-    for mut sprite in selected_query.iter_mut() {
-        // Add a slight brightness to selected entities
-        let current_color = sprite.color;
-        sprite.color = Color::rgb(
-            (current_color.r() + 0.2).min(1.0),
-            (current_color.g() + 0.2).min(1.0),
-            (current_color.b() + 0.2).min(1.0),
-        );
+    for entity in selected_query.iter() {
+        if let Some([a, b, c, d]) = sprite_bounds.world_outline(entity) {
+            gizmos.linestrip_2d([a, b, c, d, a], SELECTION_OUTLINE_COLOR);
+        }
     }
+}
+
+/// Outline what each game camera in the scene sees in the scene view, along with
+/// its dead zone and bounds
+pub fn draw_game_camera_frames(
+    game_state: Res<GameState>,
+    game_camera_query: Query<(&GlobalTransform, &GameCamera)>,
+    view_camera_query: Query<&Camera, With<GameViewCamera>>,
+    mut gizmos: Gizmos,
+) {
+    if game_state.playing {
+        return;
+    }
+    let Some(viewport_size) = view_camera_query.iter().find_map(|camera| camera.logical_viewport_size()) else {
+        return;
+    };
     
-    // Reset unselected entities to normal colors
-    for mut sprite in unselected_query.iter_mut() {
-        // This would need to store original colors to properly reset
-        // For now, we'll just ensure they're not overly bright
-        let current_color = sprite.color;
-        if current_color.r() > 0.8 && current_color.g() > 0.8 && current_color.b() > 0.8 {
-            sprite.color = Color::rgb(
-                (current_color.r() - 0.2).max(0.0),
-                (current_color.g() - 0.2).max(0.0),
-                (current_color.b() - 0.2).max(0.0),
-            );
+    for (global_transform, game_camera) in game_camera_query.iter() {
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let angle = rotation.to_euler(EulerRot::ZYX).0;
+        gizmos.rect_2d(translation.truncate(), angle, viewport_size * game_camera.zoom, Color::rgb(0.9, 0.9, 0.9));
+        
+        if game_camera.dead_zone.cmpgt(Vec2::ZERO).any() {
+            gizmos.rect_2d(translation.truncate(), 0.0, game_camera.dead_zone, Color::rgba(0.9, 0.9, 0.9, 0.4));
+        }
+        if let Some(bounds) = game_camera.bounds {
+            gizmos.rect_2d(bounds.center(), 0.0, bounds.size(), Color::rgb(0.9, 0.6, 0.2));
         }
     }
-}
\ No newline at end of file
+}
+
+/// Draw the box selection while it is being dragged out
+pub fn draw_selection_marquee(
+    drag_state: Res<DragState>,
+    editor_state: Res<EditorState>,
+    mut gizmos: Gizmos,
+) {
+    if let Some(start) = drag_state.marquee_start {
+        let end = editor_state.mouse_world_position;
+        gizmos.rect_2d((start + end) * 0.5, 0.0, (end - start).abs(), Color::rgb(0.4, 0.6, 1.0));
+    }
+}
+
+/// Keep sprite colors in sync with edited sprite asset tints
+pub fn sync_sprite_asset_tint(
+    mut sprite_query: Query<(&SpriteAsset, &mut Sprite), Changed<SpriteAsset>>,
+) {
+    for (sprite_asset, mut sprite) in sprite_query.iter_mut() {
+        sprite.color = sprite_asset.get_color();
+    }
+}
@@ -0,0 +1,254 @@
+//! Translate, rotate and scale gizmo drawn over the selection in the editor viewport
+//!
+//! The gizmo sits at the centre of the selection's bounds. Handle sizes are given in
+//! screen pixels and multiplied by the camera zoom, so the gizmo keeps its size on
+//! screen however far the view is zoomed.
+
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::resources::*;
+use crate::systems::picking::SpriteBounds;
+
+/// Length of the move arrows, in pixels
+const AXIS_LENGTH: f32 = 70.0;
+/// Half the size of the free-move square at the centre, in pixels
+const CENTER_HALF_SIZE: f32 = 7.0;
+/// Radius of the rotation ring, in pixels
+const RING_RADIUS: f32 = 60.0;
+/// Half the size of the corner scale handles, in pixels
+const CORNER_HALF_SIZE: f32 = 5.0;
+/// How far from a handle a click still grabs it, in pixels
+const PICK_TOLERANCE: f32 = 6.0;
+/// Smallest scale factor a drag can apply, which keeps entities from collapsing or flipping
+const MIN_SCALE_FACTOR: f32 = 0.01;
+
+const X_AXIS_COLOR: Color = Color::rgb(0.9, 0.25, 0.25);
+const Y_AXIS_COLOR: Color = Color::rgb(0.3, 0.85, 0.3);
+const HANDLE_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const RING_COLOR: Color = Color::rgb(0.35, 0.55, 1.0);
+const ACTIVE_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+/// Snapping applied while dragging gizmo handles
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DragSnap {
+    /// Grid spacing that moved positions snap to
+    pub grid: Option<f32>,
+    /// Step that rotations snap to, in radians
+    pub angle: Option<f32>,
+}
+
+impl DragSnap {
+    /// Snapping as set in the grid settings
+    pub fn from_settings(grid_settings: &GridSettings) -> Self {
+        Self {
+            grid: (grid_settings.snap_enabled && grid_settings.spacing > 0.0).then_some(grid_settings.spacing),
+            angle: (grid_settings.rotation_snap_enabled && grid_settings.rotation_snap_degrees > 0.0)
+                .then_some(grid_settings.rotation_snap_degrees.to_radians()),
+        }
+    }
+}
+
+fn snap(value: f32, step: Option<f32>) -> f32 {
+    match step {
+        Some(step) => (value / step).round() * step,
+        None => value,
+    }
+}
+
+/// Where the gizmo is drawn for the current selection
+pub struct GizmoLayout {
+    /// Bounds of the selected entities in world space
+    pub bounds: Rect,
+    /// World units per screen pixel
+    pub pixel: f32,
+}
+
+impl GizmoLayout {
+    /// Lay the gizmo out around entities given by their world-space bounds
+    pub fn new(entity_bounds: impl IntoIterator<Item = Rect>, zoom: f32) -> Option<Self> {
+        let bounds = entity_bounds
+            .into_iter()
+            .reduce(|bounds, rect| bounds.union(rect))?;
+
+        Some(Self { bounds, pixel: zoom })
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.bounds.center()
+    }
+
+    /// Corners of the bounds with the direction each one scales in
+    fn corners(&self) -> [(Vec2, Vec2); 4] {
+        let (min, max) = (self.bounds.min, self.bounds.max);
+        [
+            (Vec2::new(min.x, min.y), Vec2::new(-1.0, -1.0)),
+            (Vec2::new(max.x, min.y), Vec2::new(1.0, -1.0)),
+            (Vec2::new(max.x, max.y), Vec2::new(1.0, 1.0)),
+            (Vec2::new(min.x, max.y), Vec2::new(-1.0, 1.0)),
+        ]
+    }
+
+    /// The handle of `tool` under a point in world space, if any
+    pub fn pick(&self, tool: TransformTool, point: Vec2) -> Option<GizmoHandle> {
+        let center = self.center();
+        let offset = point - center;
+        let tolerance = PICK_TOLERANCE * self.pixel;
+
+        match tool {
+            TransformTool::Translate => {
+                let axis_length = AXIS_LENGTH * self.pixel;
+                if offset.abs().max_element() <= CENTER_HALF_SIZE * self.pixel + tolerance {
+                    Some(GizmoHandle::Center)
+                } else if offset.y.abs() <= tolerance && (0.0..=axis_length).contains(&offset.x) {
+                    Some(GizmoHandle::Axis(Vec2::X))
+                } else if offset.x.abs() <= tolerance && (0.0..=axis_length).contains(&offset.y) {
+                    Some(GizmoHandle::Axis(Vec2::Y))
+                } else {
+                    None
+                }
+            }
+            TransformTool::Rotate => {
+                ((offset.length() - RING_RADIUS * self.pixel).abs() <= tolerance).then_some(GizmoHandle::Ring)
+            }
+            TransformTool::Scale => self
+                .corners()
+                .into_iter()
+                .find(|(corner, _)| (point - *corner).abs().max_element() <= CORNER_HALF_SIZE * self.pixel + tolerance)
+                .map(|(_, direction)| GizmoHandle::Corner(direction)),
+        }
+    }
+}
+
+/// Where a world transform ends up when `handle` is dragged from `origin` to `mouse`
+///
+/// Rotation and scaling work around `pivot`. Scaling stretches each axis by how
+/// far the corner moved along it, or both axes by the same amount when `uniform`.
+/// Moved positions snap to the grid, only along the dragged axis for an axis
+/// handle, and rotations turn in whole snap steps.
+pub fn drag_handle(
+    handle: GizmoHandle,
+    start: &Transform,
+    pivot: Vec2,
+    origin: Vec2,
+    mouse: Vec2,
+    uniform: bool,
+    drag_snap: DragSnap,
+) -> Transform {
+    let mut transform = *start;
+
+    match handle {
+        GizmoHandle::Center => {
+            let position = start.translation.truncate() + mouse - origin;
+            transform.translation.x = snap(position.x, drag_snap.grid);
+            transform.translation.y = snap(position.y, drag_snap.grid);
+        }
+        GizmoHandle::Axis(axis) => {
+            transform.translation += (axis * (mouse - origin).dot(axis)).extend(0.0);
+            if axis.x != 0.0 {
+                transform.translation.x = snap(transform.translation.x, drag_snap.grid);
+            } else {
+                transform.translation.y = snap(transform.translation.y, drag_snap.grid);
+            }
+        }
+        GizmoHandle::Ring => {
+            let from = origin - pivot;
+            let to = mouse - pivot;
+            let angle = snap(from.perp_dot(to).atan2(from.dot(to)), drag_snap.angle);
+            transform.rotate_around(pivot.extend(start.translation.z), Quat::from_rotation_z(angle));
+        }
+        GizmoHandle::Corner(_) => {
+            let from = origin - pivot;
+            let to = mouse - pivot;
+            let factor = if uniform {
+                Vec2::splat(if from.length_squared() > 0.0 { to.dot(from) / from.length_squared() } else { 1.0 })
+            } else {
+                Vec2::new(
+                    if from.x != 0.0 { to.x / from.x } else { 1.0 },
+                    if from.y != 0.0 { to.y / from.y } else { 1.0 },
+                )
+            };
+            let factor = factor.max(Vec2::splat(MIN_SCALE_FACTOR));
+
+            let position = pivot + (start.translation.truncate() - pivot) * factor;
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+            transform.scale.x *= factor.x;
+            transform.scale.y *= factor.y;
+        }
+    }
+
+    transform
+}
+
+/// Switch transform tools with W (move), E (rotate) and R (scale)
+pub fn transform_tool_shortcuts(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
+    game_state: Res<GameState>,
+    mut editor_state: ResMut<EditorState>,
+) {
+    // W moves the player in play mode, and text fields need the letters
+    if game_state.playing || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyW) {
+        editor_state.transform_tool = TransformTool::Translate;
+    } else if keyboard_input.just_pressed(KeyCode::KeyE) {
+        editor_state.transform_tool = TransformTool::Rotate;
+    } else if keyboard_input.just_pressed(KeyCode::KeyR) {
+        editor_state.transform_tool = TransformTool::Scale;
+    }
+}
+
+/// Draw the gizmo of the current transform tool around the selection
+pub fn draw_transform_gizmo(
+    editor_state: Res<EditorState>,
+    game_state: Res<GameState>,
+    camera_controller: Res<CameraController>,
+    selected_entity: Res<SelectedEntity>,
+    drag_state: Res<DragState>,
+    sprite_bounds: SpriteBounds,
+    mut gizmos: Gizmos,
+) {
+    if game_state.playing {
+        return;
+    }
+    let selection = selected_entity
+        .entities
+        .iter()
+        .filter_map(|&entity| sprite_bounds.world_bounds(entity));
+    let Some(layout) = GizmoLayout::new(selection, camera_controller.zoom) else {
+        return;
+    };
+
+    let center = layout.center();
+    let pixel = layout.pixel;
+    let active = drag_state.handle.filter(|_| drag_state.dragging);
+    let color = |handle: GizmoHandle, idle: Color| if active == Some(handle) { ACTIVE_COLOR } else { idle };
+
+    match editor_state.transform_tool {
+        TransformTool::Translate => {
+            gizmos.arrow_2d(center, center + Vec2::X * AXIS_LENGTH * pixel, color(GizmoHandle::Axis(Vec2::X), X_AXIS_COLOR));
+            gizmos.arrow_2d(center, center + Vec2::Y * AXIS_LENGTH * pixel, color(GizmoHandle::Axis(Vec2::Y), Y_AXIS_COLOR));
+            gizmos.rect_2d(center, 0.0, Vec2::splat(CENTER_HALF_SIZE * 2.0 * pixel), color(GizmoHandle::Center, HANDLE_COLOR));
+        }
+        TransformTool::Rotate => {
+            gizmos.circle_2d(center, RING_RADIUS * pixel, color(GizmoHandle::Ring, RING_COLOR)).segments(64);
+            if active == Some(GizmoHandle::Ring) {
+                gizmos.line_2d(center, editor_state.mouse_world_position, ACTIVE_COLOR);
+            }
+        }
+        TransformTool::Scale => {
+            gizmos.rect_2d(center, 0.0, layout.bounds.size(), HANDLE_COLOR.with_a(0.4));
+            for (corner, direction) in layout.corners() {
+                let handle = GizmoHandle::Corner(direction);
+                gizmos.rect_2d(corner, 0.0, Vec2::splat(CORNER_HALF_SIZE * 2.0 * pixel), color(handle, HANDLE_COLOR));
+            }
+        }
+    }
+}
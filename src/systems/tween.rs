@@ -0,0 +1,270 @@
+//! Keyframe timelines tweening transforms and sprite colors
+
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+
+use crate::components::*;
+use crate::resources::*;
+
+/// Event fired when a timeline plays to its end, or finishes another loop
+#[derive(Event, Debug, Clone)]
+pub struct TimelineCompleted {
+    pub entity: Entity,
+    pub name: String,
+}
+
+/// Callback run whenever the entity's timeline completes, for gameplay code that
+/// would rather not read [`TimelineCompleted`] events
+///
+/// Callbacks aren't stored in scene files, so they have to be added from code.
+#[derive(Component)]
+pub struct OnTimelineCompleted(pub TimelineCallback);
+
+/// Function run with the entity whose timeline completed
+pub type TimelineCallback = Box<dyn Fn(&mut Commands, Entity) + Send + Sync>;
+
+/// An entity's timeline with everything it poses and its completion callback
+type TimelineData = (
+    Entity,
+    &'static Timeline,
+    Option<&'static mut TimelineState>,
+    &'static mut Transform,
+    Option<&'static mut Sprite>,
+    Option<&'static mut SpriteAsset>,
+    Option<&'static OnTimelineCompleted>,
+);
+
+/// Play entity timelines while the game is playing
+///
+/// Entities get their `TimelineState` the first time they're seen, which is when
+/// relative timelines take their starting transform. Finished timelines stop
+/// posing the entity, so gameplay can take over from where they ended.
+pub fn play_timelines(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut completed_events: EventWriter<TimelineCompleted>,
+    mut timeline_query: Query<TimelineData>,
+) {
+    if !game_state.playing || game_state.paused {
+        return;
+    }
+
+    for (entity, timeline, state, mut transform, sprite, sprite_asset, callback) in timeline_query.iter_mut() {
+        let Some(mut state) = state else {
+            commands.entity(entity).insert(TimelineState::start(*transform));
+            continue;
+        };
+        if !timeline.playing || state.finished {
+            continue;
+        }
+
+        if state.advance(timeline, time.delta_seconds() * timeline.speed) {
+            completed_events.send(TimelineCompleted {
+                entity,
+                name: timeline.name.clone(),
+            });
+            if let Some(OnTimelineCompleted(callback)) = callback {
+                callback(&mut commands, entity);
+            }
+        }
+
+        let mut color = sprite_color(sprite.as_deref(), sprite_asset.as_deref());
+        apply_timeline(timeline, state.time, &state.start_transform, &mut transform, &mut color);
+        if animates_color(timeline) {
+            // Sprite assets own the tint of their sprite, which follows it
+            match (sprite_asset, sprite) {
+                (Some(mut sprite_asset), _) => sprite_asset.set_color(color),
+                (None, Some(mut sprite)) => sprite.color = color,
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+/// Pose the entity in the timeline tab at the preview's time, playing it if asked
+///
+/// The preview only poses the sprite, not its sprite asset, so the tint stored in
+/// the scene is never touched.
+pub fn preview_timeline(
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    mut preview: ResMut<TimelinePreview>,
+    mut entity_query: Query<(Option<&Timeline>, &mut Transform, Option<&mut Sprite>)>,
+) {
+    // Play mode runs the real timelines, and its snapshot must not catch a pose
+    if game_state.playing {
+        preview.active = false;
+        preview.playing = false;
+    }
+
+    // Put the entity back once the preview ends or moves to another entity
+    if let Some((entity, transform, color)) = preview.original {
+        if !preview.active || preview.entity != Some(entity) {
+            if let Ok((_, mut posed_transform, sprite)) = entity_query.get_mut(entity) {
+                *posed_transform = transform;
+                if let Some(mut sprite) = sprite {
+                    sprite.color = color;
+                }
+            }
+            preview.original = None;
+        }
+    }
+    if !preview.active {
+        return;
+    }
+
+    let previewed = match preview.entity {
+        Some(entity) => entity_query.get_mut(entity).ok().map(|components| (entity, components)),
+        None => None,
+    };
+    let Some((entity, (Some(timeline), mut transform, sprite))) = previewed else {
+        preview.active = false;
+        preview.playing = false;
+        return;
+    };
+
+    let mut color = sprite.as_ref().map(|sprite| sprite.color).unwrap_or(Color::WHITE);
+    let (start_transform, start_color) = match preview.original {
+        Some((_, transform, color)) => (transform, color),
+        None => {
+            preview.original = Some((entity, *transform, color));
+            (*transform, color)
+        }
+    };
+    preview.state.start_transform = start_transform;
+
+    if preview.playing {
+        let seconds = time.delta_seconds() * timeline.speed;
+        preview.state.advance(timeline, seconds);
+        if preview.state.finished {
+            preview.playing = false;
+        }
+    }
+
+    // Tracks the timeline doesn't have leave the entity as it was
+    *transform = start_transform;
+    color = start_color;
+    apply_timeline(timeline, preview.state.time, &start_transform, &mut transform, &mut color);
+    if let Some(mut sprite) = sprite {
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// Stop the timeline preview and put the previewed entity back right away
+///
+/// Anything that reads or edits the previewed entity's transform or color, like
+/// saving, copying or the inspector, calls this first so it never sees the pose.
+pub fn end_timeline_preview(world: &mut World) {
+    let Some((entity, transform, color)) = world.get_resource_mut::<TimelinePreview>().and_then(|mut preview| preview.stop()) else {
+        return;
+    };
+    let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+        return;
+    };
+    if let Some(mut posed_transform) = entity_mut.get_mut::<Transform>() {
+        *posed_transform = transform;
+    }
+    if let Some(mut sprite) = entity_mut.get_mut::<Sprite>() {
+        sprite.color = color;
+    }
+}
+
+/// Command ending the timeline preview, for systems without world access
+pub struct EndTimelinePreview;
+
+impl Command for EndTimelinePreview {
+    fn apply(self, world: &mut World) {
+        end_timeline_preview(world);
+    }
+}
+
+/// Whether the timeline preview is posing `entity`
+pub fn is_previewing(preview: &TimelinePreview, entity: Entity) -> bool {
+    preview.original.is_some_and(|(previewed, _, _)| previewed == entity)
+}
+
+/// Put a timeline's values at a time onto a transform and color
+///
+/// `start` is the transform relative timelines build on. Opacity is applied after
+/// the tint, so a timeline can fade a tinted sprite.
+pub fn apply_timeline(timeline: &Timeline, time: f32, start: &Transform, transform: &mut Transform, color: &mut Color) {
+    let mut samples = timeline.sample(time);
+    samples.sort_by_key(|(property, _)| *property == TweenProperty::Opacity);
+
+    for (property, value) in samples {
+        match property {
+            TweenProperty::Position => {
+                let mut position = Vec2::new(value.x, value.y);
+                if timeline.relative {
+                    position += start.translation.truncate();
+                }
+                transform.translation = position.extend(transform.translation.z);
+            }
+            TweenProperty::Rotation => {
+                let rotation = Quat::from_rotation_z(value.x.to_radians());
+                transform.rotation = if timeline.relative { start.rotation * rotation } else { rotation };
+            }
+            TweenProperty::Scale => {
+                let mut scale = Vec2::new(value.x, value.y);
+                if timeline.relative {
+                    scale *= start.scale.truncate();
+                }
+                transform.scale = scale.extend(transform.scale.z);
+            }
+            TweenProperty::Tint => *color = Color::rgba(value.x, value.y, value.z, value.w),
+            TweenProperty::Opacity => {
+                color.set_a(value.x);
+            }
+        }
+    }
+}
+
+/// Whether a timeline has keyframes for the sprite color
+fn animates_color(timeline: &Timeline) -> bool {
+    [TweenProperty::Tint, TweenProperty::Opacity]
+        .into_iter()
+        .any(|property| timeline.get_track(property).is_some_and(|track| !track.keyframes.is_empty()))
+}
+
+/// Color a timeline starts from: the sprite asset's tint, or the sprite's own color
+fn sprite_color(sprite: Option<&Sprite>, sprite_asset: Option<&SpriteAsset>) -> Color {
+    match (sprite_asset, sprite) {
+        (Some(sprite_asset), _) => sprite_asset.get_color(),
+        (None, Some(sprite)) => sprite.color,
+        (None, None) => Color::WHITE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ending_the_preview_puts_the_entity_back() {
+        let mut world = World::new();
+        let original = Transform::from_xyz(10.0, 20.0, 1.0);
+        let entity = world
+            .spawn((
+                Transform::from_xyz(50.0, 0.0, 1.0).with_scale(Vec3::splat(2.0)),
+                Sprite { color: Color::RED.with_a(0.5), ..default() },
+            ))
+            .id();
+        world.insert_resource(TimelinePreview {
+            entity: Some(entity),
+            playing: true,
+            active: true,
+            original: Some((entity, original, Color::RED)),
+            ..default()
+        });
+
+        end_timeline_preview(&mut world);
+
+        assert_eq!(*world.get::<Transform>(entity).unwrap(), original);
+        assert_eq!(world.get::<Sprite>(entity).unwrap().color, Color::RED);
+        let preview = world.resource::<TimelinePreview>();
+        assert!(!preview.active && !preview.playing && preview.original.is_none());
+    }
+}
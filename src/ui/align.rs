@@ -0,0 +1,160 @@
+//! Align and distribute tools for multi-entity selections
+//!
+//! Entities are lined up by their sprite bounds in world space, so children line
+//! up with top-level entities. Only the top of each selected subtree moves, and its
+//! children follow.
+
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use bevy_egui::egui;
+
+use crate::resources::*;
+use crate::history::{self, EditorAction, EditorHistory};
+use crate::scene::top_level_entities;
+use crate::systems::picking::world_entity_bounds;
+
+/// How `AlignSelection` lines up the selected entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignMode {
+    Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom,
+    /// Space the centres evenly between the leftmost and rightmost entity
+    DistributeX,
+    /// Space the centres evenly between the lowest and highest entity
+    DistributeY,
+}
+
+impl AlignMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            AlignMode::Left => "Align Left",
+            AlignMode::CenterX => "Align Center",
+            AlignMode::Right => "Align Right",
+            AlignMode::Top => "Align Top",
+            AlignMode::CenterY => "Align Middle",
+            AlignMode::Bottom => "Align Bottom",
+            AlignMode::DistributeX => "Distribute Horizontally",
+            AlignMode::DistributeY => "Distribute Vertically",
+        }
+    }
+
+    /// Fewest entities the mode does anything with
+    pub fn min_entities(self) -> usize {
+        match self {
+            AlignMode::DistributeX | AlignMode::DistributeY => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// Command that aligns or distributes the selected entities as one undoable step
+pub struct AlignSelection {
+    pub mode: AlignMode,
+}
+
+impl Command for AlignSelection {
+    fn apply(self, world: &mut World) {
+        let selected = world.resource::<SelectedEntity>().entities.clone();
+        let entities = top_level_entities(world, &selected);
+        let bounds: Vec<(Entity, Rect)> = entities
+            .into_iter()
+            .filter_map(|entity| world_entity_bounds(world, entity).map(|bounds| (entity, bounds)))
+            .collect();
+        if bounds.len() < self.mode.min_entities() {
+            return;
+        }
+
+        let mut actions = Vec::new();
+        for (entity, offset) in align_offsets(self.mode, &bounds) {
+            if offset == Vec2::ZERO {
+                continue;
+            }
+            let Some(mut transform) = world.get::<Transform>(entity).copied() else {
+                continue;
+            };
+
+            // Offsets are in world space, while a child's transform is relative to its parent
+            let parent_transform = world
+                .get::<Parent>(entity)
+                .and_then(|parent| world.get::<GlobalTransform>(parent.get()));
+            transform.translation += match parent_transform {
+                Some(parent_transform) => parent_transform.affine().inverse().transform_vector3(offset.extend(0.0)),
+                None => offset.extend(0.0),
+            };
+
+            actions.extend(history::set_components(world, entity, vec![Box::new(transform)]));
+        }
+
+        if !actions.is_empty() {
+            world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions));
+        }
+    }
+}
+
+/// How far each entity moves in world space to line up as `mode` asks
+fn align_offsets(mode: AlignMode, bounds: &[(Entity, Rect)]) -> Vec<(Entity, Vec2)> {
+    let Some(all) = bounds.iter().map(|(_, rect)| *rect).reduce(|all, rect| all.union(rect)) else {
+        return Vec::new();
+    };
+
+    match mode {
+        AlignMode::DistributeX | AlignMode::DistributeY => {
+            let axis = if mode == AlignMode::DistributeX { Vec2::X } else { Vec2::Y };
+            let mut sorted = bounds.to_vec();
+            sorted.sort_by(|(_, a), (_, b)| a.center().dot(axis).total_cmp(&b.center().dot(axis)));
+
+            let first = sorted[0].1.center().dot(axis);
+            let last = sorted[sorted.len() - 1].1.center().dot(axis);
+            let step = (last - first) / (sorted.len() - 1) as f32;
+            sorted
+                .into_iter()
+                .enumerate()
+                .map(|(index, (entity, rect))| (entity, axis * (first + step * index as f32 - rect.center().dot(axis))))
+                .collect()
+        }
+        _ => bounds
+            .iter()
+            .map(|&(entity, rect)| {
+                let offset = match mode {
+                    AlignMode::Left => Vec2::new(all.min.x - rect.min.x, 0.0),
+                    AlignMode::CenterX => Vec2::new(all.center().x - rect.center().x, 0.0),
+                    AlignMode::Right => Vec2::new(all.max.x - rect.max.x, 0.0),
+                    AlignMode::Top => Vec2::new(0.0, all.max.y - rect.max.y),
+                    AlignMode::CenterY => Vec2::new(0.0, all.center().y - rect.center().y),
+                    AlignMode::Bottom => Vec2::new(0.0, all.min.y - rect.min.y),
+                    AlignMode::DistributeX | AlignMode::DistributeY => unreachable!(),
+                };
+                (entity, offset)
+            })
+            .collect(),
+    }
+}
+
+/// Render align and distribute buttons for a selection of `count` entities
+pub fn render_align_tools(ui: &mut egui::Ui, count: usize, commands: &mut Commands) {
+    let rows = [
+        [AlignMode::Left, AlignMode::CenterX, AlignMode::Right],
+        [AlignMode::Top, AlignMode::CenterY, AlignMode::Bottom],
+    ];
+    for row in rows {
+        ui.horizontal(|ui| {
+            for mode in row {
+                if ui.add_enabled(count >= mode.min_entities(), egui::Button::new(mode.label())).clicked() {
+                    commands.add(AlignSelection { mode });
+                }
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        for mode in [AlignMode::DistributeX, AlignMode::DistributeY] {
+            if ui.add_enabled(count >= mode.min_entities(), egui::Button::new(mode.label())).clicked() {
+                commands.add(AlignSelection { mode });
+            }
+        }
+    });
+}
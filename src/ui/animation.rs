@@ -0,0 +1,269 @@
+//! Animation preview tab: plays an entity's sprite animation clips and edits them
+//!
+//! The preview keeps its own playback position, so clips can be watched and
+//! scrubbed in edit mode without touching the entity's own animation.
+
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy_egui::egui;
+
+use crate::components::*;
+use crate::resources::*;
+use crate::ui::inspector::ApplyReflectedComponents;
+
+/// Size of the square the preview draws frames in
+const PREVIEW_SIZE: f32 = 160.0;
+
+/// An entity's clips with the sprite sheet they play from
+type AnimatedSpriteData = (
+    Option<&'static AnimatedSprite>,
+    Option<&'static SpriteAsset>,
+    Option<&'static Handle<Image>>,
+);
+
+/// World access needed by the animation preview tab
+#[derive(SystemParam)]
+pub struct AnimationPanelParams<'w, 's> {
+    pub sprite_query: Query<'w, 's, AnimatedSpriteData>,
+    pub preview: ResMut<'w, AnimationPreview>,
+}
+
+/// Render the animation preview and clip editor for the selected entity
+///
+/// `texture` is the egui texture of the entity's image, used to draw its frames.
+pub fn render_animation_preview(
+    ui: &mut egui::Ui,
+    entity: Option<Entity>,
+    params: &mut AnimationPanelParams,
+    atlas_library: &AtlasLibrary,
+    images: &Assets<Image>,
+    texture: Option<egui::TextureId>,
+    commands: &mut Commands,
+) {
+    let Some((entity, (animated, sprite_asset, texture_handle))) =
+        entity.and_then(|entity| params.sprite_query.get(entity).ok().map(|components| (entity, components)))
+    else {
+        ui.label("Select an entity to preview its animation");
+        return;
+    };
+    let Some(animated) = animated else {
+        ui.label("The selected entity isn't animated");
+        if ui.button("➕ Add Animated Sprite").clicked() {
+            commands.add(ApplyReflectedComponents {
+                entity,
+                components: vec![Box::new(AnimatedSprite::default())],
+            });
+        }
+        return;
+    };
+
+    // Start over when another entity is selected or the previewed clip goes away
+    let preview = &mut params.preview;
+    if preview.entity != Some(entity) || animated.get_clip(&preview.state.clip).is_none() {
+        preview.entity = Some(entity);
+        preview.playing = false;
+        preview.state = animated
+            .current_clip()
+            .or(animated.clips.first())
+            .map(AnimationState::start)
+            .unwrap_or_default();
+    }
+
+    let mut edited = animated.clone();
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Clip:");
+        egui::ComboBox::from_id_source("animation_preview_clip")
+            .selected_text(preview.state.clip.clone())
+            .show_ui(ui, |ui| {
+                for clip in &animated.clips {
+                    if ui.selectable_label(clip.name == preview.state.clip, &clip.name).clicked() {
+                        preview.state = AnimationState::start(clip);
+                    }
+                }
+            });
+
+        if ui.button("➕ New Clip").clicked() {
+            let clip = SpriteClip {
+                name: new_clip_name(animated),
+                ..default()
+            };
+            preview.state = AnimationState::start(&clip);
+            edited.clips.push(clip);
+            changed = true;
+        }
+    });
+
+    if let Some(index) = animated.clips.iter().position(|clip| clip.name == preview.state.clip) {
+        let clip = &animated.clips[index];
+        let image_path = sprite_asset.and_then(|sprite_asset| sprite_asset.asset_path.as_deref());
+        render_frame(ui, preview.state.atlas_frame, image_path, texture_handle, atlas_library, images, texture);
+
+        // Playback controls
+        ui.horizontal(|ui| {
+            let label = if preview.playing { "⏸ Pause" } else { "▶ Play" };
+            if ui.button(label).clicked() {
+                if preview.state.finished {
+                    preview.state.seek(clip, 0);
+                }
+                preview.playing = !preview.playing;
+            }
+            if ui.button("⏮").on_hover_text("Back to the first frame").clicked() {
+                preview.state.seek(clip, 0);
+            }
+
+            let mut step = preview.state.step;
+            if ui.add(egui::Slider::new(&mut step, 0..=clip.frame_count() - 1).text("Step")).changed() {
+                preview.state.seek(clip, step);
+                preview.playing = false;
+            }
+        });
+        ui.label(format!("Atlas frame {}", preview.state.atlas_frame));
+
+        ui.separator();
+
+        // Clip editor
+        if edit_clip(ui, &mut edited.clips[index]) {
+            let renamed = &edited.clips[index].name;
+            if *renamed != clip.name {
+                if edited.clip == clip.name {
+                    edited.clip = renamed.clone();
+                }
+                preview.state.clip = renamed.clone();
+            }
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            let is_current = animated.clip == clip.name;
+            if ui.add_enabled(!is_current, egui::Button::new("Play on Entity"))
+                .on_hover_text("Make this the clip the entity plays")
+                .clicked()
+            {
+                edited.clip = clip.name.clone();
+                changed = true;
+            }
+            if ui.button("🗑 Delete Clip").clicked() {
+                edited.clips.remove(index);
+                changed = true;
+            }
+        });
+    }
+
+    ui.separator();
+
+    // Entity playback
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut edited.playing, "Playing").changed();
+        ui.label("Speed:");
+        changed |= ui.add(egui::DragValue::new(&mut edited.speed).speed(0.01).clamp_range(0.0..=10.0).suffix("x")).changed();
+    });
+
+    if changed {
+        commands.add(ApplyReflectedComponents {
+            entity,
+            components: vec![Box::new(edited)],
+        });
+    }
+}
+
+/// Draw an atlas frame of the entity's image, scaled to fit the preview square
+fn render_frame(
+    ui: &mut egui::Ui,
+    atlas_frame: usize,
+    image_path: Option<&str>,
+    texture_handle: Option<&Handle<Image>>,
+    atlas_library: &AtlasLibrary,
+    images: &Assets<Image>,
+    texture: Option<egui::TextureId>,
+) {
+    let image = texture_handle.and_then(|handle| images.get(handle));
+    let rect = image_path
+        .zip(image)
+        .and_then(|(path, image)| atlas_library.frame_rect(path, &SpriteFrame::Index(atlas_frame), image.size()));
+    let (Some(texture), Some(image), Some(rect)) = (texture, image, rect) else {
+        ui.weak("No frame to show: give the entity a sprite asset whose image has an atlas");
+        return;
+    };
+
+    let (area, _) = ui.allocate_exact_size(egui::vec2(PREVIEW_SIZE, PREVIEW_SIZE), egui::Sense::hover());
+    ui.painter().rect_filled(area, 4.0, egui::Color32::from_rgb(40, 40, 40));
+
+    let size = egui::vec2(rect.width() as f32, rect.height() as f32);
+    let fit = PREVIEW_SIZE / size.max_elem().max(1.0);
+    let image_size = image.size_f32();
+    let uv = egui::Rect::from_min_max(
+        egui::pos2(rect.min.x as f32 / image_size.x, rect.min.y as f32 / image_size.y),
+        egui::pos2(rect.max.x as f32 / image_size.x, rect.max.y as f32 / image_size.y),
+    );
+    ui.painter().image(texture, egui::Rect::from_center_size(area.center(), size * fit), uv, egui::Color32::WHITE);
+}
+
+/// Edit a clip's name, frames, rate, playback and events, returning true if anything changed
+fn edit_clip(ui: &mut egui::Ui, clip: &mut SpriteClip) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new("animation_clip_editor").num_columns(2).show(ui, |ui| {
+        ui.label("Name:");
+        changed |= ui.text_edit_singleline(&mut clip.name).changed();
+        ui.end_row();
+
+        ui.label("Frames:");
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut clip.first_frame).prefix("From: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut clip.last_frame).prefix("To: ")).changed();
+        });
+        ui.end_row();
+
+        ui.label("FPS:");
+        changed |= ui.add(egui::DragValue::new(&mut clip.fps).speed(0.1).clamp_range(0.1..=120.0)).changed();
+        ui.end_row();
+
+        ui.label("Playback:");
+        ui.horizontal(|ui| {
+            for (playback, label) in [
+                (AnimationPlayback::Loop, "Loop"),
+                (AnimationPlayback::PingPong, "Ping-Pong"),
+                (AnimationPlayback::Once, "Once"),
+            ] {
+                changed |= ui.radio_value(&mut clip.playback, playback, label).changed();
+            }
+        });
+        ui.end_row();
+    });
+
+    ui.label("Frame Events:");
+    let last_step = clip.frame_count() - 1;
+    let mut removed = None;
+    for (index, event) in clip.events.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut event.frame).clamp_range(0..=last_step).prefix("Step ")).changed();
+            changed |= ui.text_edit_singleline(&mut event.name).changed();
+            if ui.small_button("✖").on_hover_text("Remove event").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        clip.events.remove(index);
+        changed = true;
+    }
+    if ui.button("➕ Add Event").clicked() {
+        clip.events.push(AnimationFrameEvent {
+            frame: 0,
+            name: "event".to_string(),
+        });
+        changed = true;
+    }
+
+    changed
+}
+
+/// A clip name the animation doesn't use yet
+fn new_clip_name(animated: &AnimatedSprite) -> String {
+    (1..)
+        .map(|number| format!("clip {}", number))
+        .find(|name| animated.get_clip(name).is_none())
+        .unwrap_or_default()
+}
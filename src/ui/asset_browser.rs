@@ -1,18 +1,31 @@
 //! Asset Browser UI Panel
 
 use bevy::prelude::*;
+use bevy::ecs::system::Command;
 use bevy_egui::egui;
 use std::path::PathBuf;
 
-use crate::resources::{AssetRegistry, AssetImporter, AssetBrowserState};
-use crate::components::SpriteAsset;
+use crate::resources::{AssetRegistry, AssetImporter, AssetBrowserState, AtlasLibrary};
+use crate::components::{SoundEmitter, SpriteAsset, SpriteFrame};
+use crate::history::{self, EditorHistory};
+use crate::assets::AssetKind;
+use crate::assets::atlas::{self, AtlasFrame, AtlasGrid, AtlasSlicing, SpriteAtlas};
+
+/// Largest size of a frame preview in the slicing editor
+const FRAME_PREVIEW_SIZE: f32 = 96.0;
 
 /// Asset browser UI panel content
+///
+/// `preview` is the egui texture of the selected asset's image, used by the
+/// slicing editor to show the image under its cells.
 pub fn render_asset_browser_content(
     ui: &mut egui::Ui,
     asset_registry: &AssetRegistry,
     asset_importer: &mut AssetImporter,
     browser_state: &mut AssetBrowserState,
+    atlas_library: &mut AtlasLibrary,
+    images: &Assets<Image>,
+    preview: Option<egui::TextureId>,
 ) {
     // Toolbar
     ui.horizontal(|ui| {
@@ -23,6 +36,8 @@ pub fn render_asset_browser_content(
         if ui.button("Refresh").clicked() {
             // Trigger asset registry refresh
             info!("Refreshing asset registry");
+            atlas_library.atlases = atlas::read_atlas_directory().into_iter().collect();
+            browser_state.atlas_draft = None;
         }
         
         ui.separator();
@@ -47,9 +62,24 @@ pub fn render_asset_browser_content(
         });
         
     // Asset details
-    if let Some(selected) = &browser_state.selected_asset {
+    if let Some(selected) = browser_state.selected_asset.clone() {
         ui.separator();
-        asset_details_ui(ui, asset_registry, selected);
+        if asset_details_ui(ui, asset_registry, &selected, browser_state.selected_frame.as_ref()) {
+            browser_state.apply_to_selected = true;
+        }
+        
+        if AssetKind::from_path(&selected) == Some(AssetKind::Sound) {
+            sound_preview_ui(ui, &selected, browser_state);
+            return;
+        }
+        
+        let image_size = asset_registry
+            .get_texture(&selected)
+            .and_then(|handle| images.get(handle))
+            .map(|image| image.size());
+        ui.collapsing("Sprite Sheet", |ui| {
+            atlas_editor_ui(ui, &selected, browser_state, atlas_library, image_size, preview);
+        });
     }
 }
 
@@ -131,12 +161,17 @@ fn asset_list_ui(
                     .and_then(|name| name.to_str())
                     .unwrap_or("Unknown");
                 
-                if ui.selectable_label(is_selected, file_name).clicked() {
+                if ui.selectable_label(is_selected, file_name).clicked() && !is_selected {
                     browser_state.selected_asset = Some(asset_path.clone());
+                    browser_state.atlas_draft = None;
+                    browser_state.selected_frame = None;
+                    browser_state.atlas_message = None;
                 }
                 
                 if let Some((width, height)) = metadata.image_dimensions {
                     ui.label(format!("{}x{}", width, height));
+                } else if AssetKind::from_path(asset_path) == Some(AssetKind::Sound) {
+                    ui.label("🎵 Sound");
                 } else {
                     ui.label("Unknown dimensions");
                 }
@@ -155,12 +190,14 @@ fn asset_list_ui(
     }
 }
 
-/// Asset details UI
+/// Asset details UI, returning true when the asset should be applied to the selection
 fn asset_details_ui(
     ui: &mut egui::Ui,
     asset_registry: &AssetRegistry,
     selected_asset: &str,
-) {
+    selected_frame: Option<&SpriteFrame>,
+) -> bool {
+    let mut apply_to_selected = false;
     if let Some(metadata) = asset_registry.asset_metadata.get(selected_asset) {
         ui.group(|ui| {
             ui.label("Asset Details");
@@ -186,7 +223,7 @@ fn asset_details_ui(
             
             ui.horizontal(|ui| {
                 ui.label("Format:");
-                ui.label("Image"); // Default format since we don't store this in metadata
+                ui.label(AssetKind::from_path(selected_asset).unwrap_or(AssetKind::Image).label());
             });
             
             ui.horizontal(|ui| {
@@ -198,9 +235,13 @@ fn asset_details_ui(
             
             // Asset actions
             ui.horizontal(|ui| {
-                if ui.button("Apply to Selected").clicked() {
-                    info!("Applying asset {} to selected entity", selected_asset);
-                    // This would be handled by the main editor system
+                let apply_label = match selected_frame {
+                    Some(SpriteFrame::Index(index)) => format!("Apply Frame {} to Selected", index),
+                    Some(SpriteFrame::Name(name)) => format!("Apply '{}' to Selected", name),
+                    None => "Apply to Selected".to_string(),
+                };
+                if ui.button(apply_label).clicked() {
+                    apply_to_selected = true;
                 }
                 
                 if ui.button("Remove").clicked() {
@@ -210,6 +251,211 @@ fn asset_details_ui(
             });
         });
     }
+    apply_to_selected
+}
+
+/// Play and stop buttons for hearing the selected sound
+fn sound_preview_ui(ui: &mut egui::Ui, selected_sound: &str, browser_state: &mut AssetBrowserState) {
+    ui.horizontal(|ui| {
+        let playing = browser_state.previewed_sound.as_deref() == Some(selected_sound);
+        if playing {
+            if ui.button("⏹ Stop").clicked() {
+                browser_state.previewed_sound = None;
+            }
+            ui.label("Playing…");
+        } else if ui.button("▶ Preview").on_hover_text("Play the sound at the master volume").clicked() {
+            browser_state.previewed_sound = Some(selected_sound.to_string());
+        }
+    });
+}
+
+/// Slicing editor for the selected image, previewing its cells over the image
+///
+/// Clicking a cell picks it as the frame "Apply to Selected" assigns. Grid cells
+/// are picked by index and manifest frames by name, so entities keep their frame
+/// when a packer reorders the sheet.
+fn atlas_editor_ui(
+    ui: &mut egui::Ui,
+    image_path: &str,
+    browser_state: &mut AssetBrowserState,
+    atlas_library: &mut AtlasLibrary,
+    image_size: Option<UVec2>,
+    preview: Option<egui::TextureId>,
+) {
+    let saved = atlas_library.get(image_path);
+    let draft = browser_state
+        .atlas_draft
+        .get_or_insert_with(|| saved.map(|atlas| atlas.slicing.clone()).unwrap_or_default());
+    
+    // Slicing mode
+    ui.horizontal(|ui| {
+        let is_grid = matches!(draft, AtlasSlicing::Grid(_));
+        if ui.radio(is_grid, "Grid").clicked() && !is_grid {
+            *draft = AtlasSlicing::Grid(AtlasGrid::default());
+        }
+        if ui.radio(!is_grid, "Packer Manifest").clicked() && is_grid {
+            *draft = AtlasSlicing::Manifest(String::new());
+        }
+    });
+    
+    match draft {
+        AtlasSlicing::Grid(grid) => edit_atlas_grid(ui, grid),
+        AtlasSlicing::Manifest(path) => {
+            ui.horizontal(|ui| {
+                ui.label("Manifest:");
+                ui.text_edit_singleline(path)
+                    .on_hover_text("JSON or RON file written by a texture packer, relative to the assets folder");
+            });
+        }
+    }
+    
+    // Grid cells follow the settings as they're edited, while a manifest is only
+    // read when the atlas is saved
+    let frames = match (&*draft, saved) {
+        (AtlasSlicing::Grid(grid), _) => image_size.map(|size| grid.frames(size)).unwrap_or_default(),
+        (draft, Some(saved)) if *draft == saved.slicing => image_size.map(|size| saved.frames(size)).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let by_name = matches!(draft, AtlasSlicing::Manifest(_));
+    let is_saved = saved.is_some_and(|saved| saved.slicing == *draft);
+    
+    ui.horizontal(|ui| {
+        if ui.add_enabled(!is_saved, egui::Button::new("💾 Save Atlas")).clicked() {
+            browser_state.atlas_message = Some(match save_atlas(image_path, draft.clone(), atlas_library) {
+                Ok(()) => "Atlas saved".to_string(),
+                Err(e) => format!("Failed to save atlas: {}", e),
+            });
+        }
+        if atlas_library.get(image_path).is_some() && ui.button("🗑 Remove Atlas").clicked() {
+            let _ = std::fs::remove_file(atlas::atlas_path(image_path));
+            atlas_library.atlases.remove(image_path);
+            browser_state.selected_frame = None;
+            browser_state.atlas_message = Some("Atlas removed".to_string());
+        }
+    });
+    if let Some(message) = &browser_state.atlas_message {
+        ui.weak(message);
+    }
+    
+    let Some(image_size) = image_size else {
+        ui.weak("Image is still loading");
+        return;
+    };
+    ui.label(format!("{} frames", frames.len()));
+    
+    if let Some(texture) = preview {
+        render_atlas_preview(ui, texture, image_size, &frames, by_name, &mut browser_state.selected_frame);
+    }
+}
+
+/// Edit the cell size, padding, offset and cell count of a grid slicing
+fn edit_atlas_grid(ui: &mut egui::Ui, grid: &mut AtlasGrid) {
+    egui::Grid::new("atlas_grid_settings").num_columns(3).show(ui, |ui| {
+        let pairs = [
+            ("Cell Size:", &mut grid.cell_size, 1),
+            ("Padding:", &mut grid.padding, 0),
+            ("Offset:", &mut grid.offset, 0),
+        ];
+        for (label, value, min) in pairs {
+            ui.label(label);
+            ui.add(egui::DragValue::new(&mut value[0]).clamp_range(min..=4096).prefix("X: "));
+            ui.add(egui::DragValue::new(&mut value[1]).clamp_range(min..=4096).prefix("Y: "));
+            ui.end_row();
+        }
+        
+        ui.label("Cells:");
+        ui.add(egui::DragValue::new(&mut grid.columns).clamp_range(0..=1024).prefix("Columns: "))
+            .on_hover_text("0 fits as many as the image holds");
+        ui.add(egui::DragValue::new(&mut grid.rows).clamp_range(0..=1024).prefix("Rows: "))
+            .on_hover_text("0 fits as many as the image holds");
+        ui.end_row();
+    });
+}
+
+/// Write an atlas next to its image and put it in the library
+fn save_atlas(image_path: &str, slicing: AtlasSlicing, atlas_library: &mut AtlasLibrary) -> Result<(), Box<dyn std::error::Error>> {
+    let atlas = SpriteAtlas::new(slicing)?;
+    atlas::write_atlas(image_path, &atlas.slicing)?;
+    atlas_library.atlases.insert(image_path.to_string(), atlas);
+    Ok(())
+}
+
+/// Draw the image with its cells outlined, letting a cell be clicked to pick it,
+/// and the picked cell enlarged underneath
+fn render_atlas_preview(
+    ui: &mut egui::Ui,
+    texture: egui::TextureId,
+    image_size: UVec2,
+    frames: &[AtlasFrame],
+    by_name: bool,
+    selected_frame: &mut Option<SpriteFrame>,
+) {
+    let image_size = egui::vec2(image_size.x as f32, image_size.y as f32);
+    let scale = (ui.available_width() / image_size.x).min(1.0);
+    let (rect, response) = ui.allocate_exact_size(image_size * scale, egui::Sense::click());
+    let full_uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    ui.painter().image(texture, rect, full_uv, egui::Color32::WHITE);
+    
+    let frame_id = |index: usize, frame: &AtlasFrame| {
+        if by_name {
+            SpriteFrame::Name(frame.name.clone())
+        } else {
+            SpriteFrame::Index(index)
+        }
+    };
+    let frame_rect = |frame: &AtlasFrame| {
+        egui::Rect::from_min_max(
+            rect.min + egui::vec2(frame.rect.min.x as f32, frame.rect.min.y as f32) * scale,
+            rect.min + egui::vec2(frame.rect.max.x as f32, frame.rect.max.y as f32) * scale,
+        )
+    };
+    
+    let hovered = response.hover_pos().and_then(|pos| frames.iter().position(|frame| frame_rect(frame).contains(pos)));
+    let mut picked = None;
+    for (index, frame) in frames.iter().enumerate() {
+        let cell = frame_rect(frame);
+        let is_picked = selected_frame.as_ref() == Some(&frame_id(index, frame));
+        if is_picked {
+            picked = Some((index, frame));
+            ui.painter().rect_filled(cell, 0.0, egui::Color32::from_rgba_unmultiplied(100, 150, 255, 80));
+        }
+        let stroke = if is_picked || hovered == Some(index) {
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 150, 255))
+        } else {
+            egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(255, 255, 0, 160))
+        };
+        ui.painter().rect_stroke(cell, 0.0, stroke);
+    }
+    
+    if let Some(index) = hovered {
+        let frame = &frames[index];
+        let response = response.on_hover_text(format!("#{} {} ({}x{})", index, frame.name, frame.rect.width(), frame.rect.height()));
+        if response.clicked() {
+            *selected_frame = Some(frame_id(index, frame));
+        }
+    }
+    
+    // Enlarged view of the picked frame, keeping its proportions
+    if let Some((index, frame)) = picked {
+        ui.horizontal(|ui| {
+            let size = egui::vec2(frame.rect.width() as f32, frame.rect.height() as f32);
+            let fit = FRAME_PREVIEW_SIZE / size.max_elem().max(1.0);
+            let (cell_rect, _) = ui.allocate_exact_size(size * fit, egui::Sense::hover());
+            let uv = egui::Rect::from_min_max(
+                egui::pos2(frame.rect.min.x as f32 / image_size.x, frame.rect.min.y as f32 / image_size.y),
+                egui::pos2(frame.rect.max.x as f32 / image_size.x, frame.rect.max.y as f32 / image_size.y),
+            );
+            ui.painter().image(texture, cell_rect, uv, egui::Color32::WHITE);
+            
+            ui.vertical(|ui| {
+                ui.label(format!("Frame #{}", index));
+                ui.label(&frame.name);
+                if ui.small_button("Use Whole Image").clicked() {
+                    *selected_frame = None;
+                }
+            });
+        });
+    }
 }
 
 /// Open file dialog for asset import
@@ -218,6 +464,7 @@ fn open_file_dialog() -> Option<String> {
     
     let file = FileDialog::new()
         .add_filter("Image Files", &["png", "jpg", "jpeg", "gif", "bmp"])
+        .add_filter("Audio Files", &["ogg", "wav", "flac"])
         .set_directory("assets/sprites")
         .pick_file();
     
@@ -228,24 +475,85 @@ fn open_file_dialog() -> Option<String> {
 pub fn apply_asset_to_entity_system(
     mut commands: Commands,
     asset_registry: Res<AssetRegistry>,
-    browser_state: Res<AssetBrowserState>,
+    mut browser_state: ResMut<AssetBrowserState>,
     selected_entity: Option<Res<crate::resources::SelectedEntity>>,
 ) {
-    // This system would handle applying selected assets to entities
-    // Implementation would depend on how entity selection is handled
-    if let (Some(selected_entity), Some(selected_asset)) = (selected_entity, &browser_state.selected_asset) {
-        if let Some(_metadata) = asset_registry.asset_metadata.get(selected_asset) {
-            info!("Would apply asset {} to entity {:?}", selected_asset, selected_entity.entity);
-            
-            // Create SpriteAsset component
-            let sprite_asset = SpriteAsset {
-                asset_path: Some(selected_asset.clone()),
-                tint_color: [1.0, 1.0, 1.0, 1.0], // White tint
-                scale: [1.0, 1.0], // Default scale
-            };
-            
-            // Apply to entity (placeholder - would need actual entity reference)
-            // commands.entity(selected_entity.entity).insert(sprite_asset);
+    if !browser_state.apply_to_selected {
+        return;
+    }
+    browser_state.apply_to_selected = false;
+    
+    let entities = selected_entity.map(|selected_entity| selected_entity.entities.clone()).unwrap_or_default();
+    if let Some(selected_asset) = browser_state.selected_asset.as_ref().filter(|_| !entities.is_empty()) {
+        if asset_registry.asset_metadata.contains_key(selected_asset) {
+            let is_sound = AssetKind::from_path(selected_asset) == Some(AssetKind::Sound);
+            for entity in entities {
+                info!("Applying asset {} to entity {:?}", selected_asset, entity);
+                if is_sound {
+                    commands.add(AssignSound {
+                        entity,
+                        sound: selected_asset.clone(),
+                    });
+                } else {
+                    commands.add(AssignSpriteAsset {
+                        entity,
+                        asset_path: selected_asset.clone(),
+                        frame: browser_state.selected_frame.clone(),
+                    });
+                }
+            }
+        }
+    } else {
+        info!("Select an entity and an asset to apply");
+    }
+}
+
+/// Command that points an entity's sprite asset at a new path and frame, keeping its
+/// tint and scale, and records the assignment in the editor history
+pub struct AssignSpriteAsset {
+    pub entity: Entity,
+    pub asset_path: String,
+    pub frame: Option<SpriteFrame>,
+}
+
+impl Command for AssignSpriteAsset {
+    fn apply(self, world: &mut World) {
+        let Some(entity_ref) = world.get_entity(self.entity) else {
+            return;
+        };
+        
+        let sprite_asset = SpriteAsset {
+            asset_path: Some(self.asset_path),
+            frame: self.frame,
+            ..entity_ref.get::<SpriteAsset>().cloned().unwrap_or_default()
+        };
+        
+        if let Some(action) = history::set_components(world, self.entity, vec![Box::new(sprite_asset)]) {
+            world.resource_mut::<EditorHistory>().push(action);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Command that points an entity's sound emitter at a sound, adding the emitter if
+/// the entity has none, and records the assignment in the editor history
+pub struct AssignSound {
+    pub entity: Entity,
+    pub sound: String,
+}
+
+impl Command for AssignSound {
+    fn apply(self, world: &mut World) {
+        let Some(entity_ref) = world.get_entity(self.entity) else {
+            return;
+        };
+        
+        let emitter = SoundEmitter {
+            sound: Some(self.sound),
+            ..entity_ref.get::<SoundEmitter>().cloned().unwrap_or_default()
+        };
+        
+        if let Some(action) = history::set_components(world, self.entity, vec![Box::new(emitter)]) {
+            world.resource_mut::<EditorHistory>().push(action);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Copy, cut, paste and duplicate for scene entities
+//!
+//! Entities travel through the system clipboard as RON lists of reflected
+//! components, the same layout scene files use, so entities copied out of a
+//! scene file can be pasted too.
+
+use bevy::prelude::*;
+use bevy::ecs::system::Command;
+use bevy_egui::EguiClipboard;
+
+use crate::resources::*;
+use crate::history::{self, DeleteEntities, EditorAction, EditorHistory};
+use crate::systems::tween::end_timeline_preview;
+use crate::scene::{
+    assign_new_ids, entities_from_ron, entities_to_ron, serialize_entity, snapshot_entities, top_level_entities,
+    SerializableEntity,
+};
+
+/// How far a duplicate is placed from its original
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(20.0, -20.0);
+
+/// Command that copies the selected entities to the system clipboard, optionally
+/// deleting them afterwards
+pub struct CopySelection {
+    pub cut: bool,
+}
+
+impl Command for CopySelection {
+    fn apply(self, world: &mut World) {
+        end_timeline_preview(world);
+        let selected = world.resource::<SelectedEntity>().entities.clone();
+        let roots = top_level_entities(world, &selected);
+
+        let mut entities = Vec::new();
+        for &root in &roots {
+            let Some(mut data) = serialize_entity(world, root) else {
+                continue;
+            };
+
+            // Pasted entities land at the root, so store where the copy is in the world
+            if let Some(global_transform) = world.get::<GlobalTransform>(root) {
+                data.insert(global_transform.compute_transform());
+            }
+            entities.push(data);
+        }
+
+        if entities.is_empty() {
+            warn!("Selection cannot be copied");
+            return;
+        }
+
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        match entities_to_ron(&entities, &type_registry) {
+            Ok(text) => {
+                if let Some(mut clipboard) = world.get_resource_mut::<EguiClipboard>() {
+                    clipboard.set_contents(&text);
+                }
+            }
+            Err(e) => {
+                error!("Failed to copy entity: {}", e);
+                return;
+            }
+        }
+
+        if self.cut {
+            DeleteEntities { entities: roots }.apply(world);
+        }
+    }
+}
+
+/// Command that pastes entities from the system clipboard, centred on `position`
+pub struct PasteClipboard {
+    pub position: Vec2,
+}
+
+impl Command for PasteClipboard {
+    fn apply(self, world: &mut World) {
+        let Some(text) = world.get_resource::<EguiClipboard>().and_then(|clipboard| clipboard.get_contents()) else {
+            return;
+        };
+
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let parsed = entities_from_ron(&text, &type_registry.read());
+        let mut entities = match parsed {
+            Ok(entities) => entities,
+            Err(e) => {
+                warn!("Clipboard does not contain entities: {}", e);
+                return;
+            }
+        };
+
+        if entities.is_empty() {
+            return;
+        }
+
+        // Keep the entities' layout relative to each other around the paste point
+        let positions: Vec<Vec2> = entities
+            .iter()
+            .filter_map(|data| data.get::<Transform>())
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        let center = if positions.is_empty() {
+            Vec2::ZERO
+        } else {
+            positions.iter().sum::<Vec2>() / positions.len() as f32
+        };
+        offset_entities(&mut entities, self.position - center);
+        assign_new_ids(world, &mut entities);
+
+        let spawned = history::spawn_recorded(world, &entities, None);
+        world.resource_mut::<SelectedEntity>().set(spawned);
+    }
+}
+
+/// Command that duplicates the selected entities next to the originals, each under
+/// the same parent as its original, and selects the copies
+pub struct DuplicateSelection;
+
+impl Command for DuplicateSelection {
+    fn apply(self, world: &mut World) {
+        end_timeline_preview(world);
+        let selected = world.resource::<SelectedEntity>().entities.clone();
+
+        let mut spawned = Vec::new();
+        let mut actions = Vec::new();
+        for root in top_level_entities(world, &selected) {
+            let mut entities = snapshot_entities(world, &[root]);
+            offset_entities(&mut entities, DUPLICATE_OFFSET);
+            assign_new_ids(world, &mut entities);
+
+            let parent = world.get::<Parent>(root).map(Parent::get);
+            for data in &entities {
+                let (entity, action) = history::spawn_serialized(world, data, parent);
+                spawned.push(entity);
+                actions.push(action);
+            }
+        }
+
+        match actions.len() {
+            0 => {
+                warn!("Selection cannot be duplicated");
+                return;
+            }
+            1 => world.resource_mut::<EditorHistory>().push(actions.remove(0)),
+            _ => world.resource_mut::<EditorHistory>().push(EditorAction::Group(actions)),
+        }
+        world.resource_mut::<SelectedEntity>().set(spawned);
+    }
+}
+
+fn offset_entities(entities: &mut [SerializableEntity], offset: Vec2) {
+    for data in entities {
+        if let Some(mut transform) = data.get::<Transform>() {
+            transform.translation += offset.extend(0.0);
+            data.insert(transform);
+        }
+    }
+}
@@ -9,6 +9,9 @@ use crate::resources::*;
 use crate::ui::hierarchy;
 use crate::ui::inspector;
 use crate::ui::asset_browser;
+use crate::ui::menus;
+use crate::ui::{AssetPanelParams, EditorEntityQuery};
+use crate::scene::prefab::RefreshPrefabs;
 
 /// Render editor panels and tools
 pub fn render_editor_panels(
@@ -22,10 +25,11 @@ pub fn render_editor_panels(
     editor_scene_state: &mut EditorSceneState,
     commands: &mut Commands,
     selected_entity: &mut SelectedEntity,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    asset_registry: &AssetRegistry,
-    asset_importer: &mut AssetImporter,
-    asset_browser_state: &mut AssetBrowserState,
+    entity_query: &EditorEntityQuery,
+    inspector_params: &mut inspector::InspectorParams,
+    hierarchy_params: &mut hierarchy::HierarchyParams,
+    assets: &mut AssetPanelParams,
+    prefab_library: &PrefabLibrary,
 ) {
     // Bottom panel for editor status - always visible
     egui::TopBottomPanel::bottom("editor_status").show(ctx, |ui| {
@@ -36,11 +40,11 @@ pub fn render_editor_panels(
             ui.separator();
             
             ui.label("🎯 Selected:");
-            if let Some(_) = selected_entity.entity {
-                ui.label("Entity");
-            } else {
-                ui.label("None");
-            }
+            match selected_entity.len() {
+                0 => ui.label("None"),
+                1 => ui.label("Entity"),
+                count => ui.label(format!("{} Entities", count)),
+            };
             
             ui.separator();
             
@@ -51,10 +55,10 @@ pub fn render_editor_panels(
 
     match editor_state.window_layout_mode {
         WindowLayoutMode::OverlayPanels => {
-            render_overlay_panels(ctx, editor_state, grid_settings, background_settings, scene_manager, shooting_stats, game_state, editor_scene_state, commands, selected_entity, entity_query, asset_registry, asset_importer, asset_browser_state);
+            render_overlay_panels(ctx, editor_state, grid_settings, background_settings, scene_manager, shooting_stats, game_state, editor_scene_state, commands, selected_entity, entity_query, inspector_params, hierarchy_params, assets, prefab_library);
         },
         WindowLayoutMode::SeparateWindows => {
-            render_separate_windows(ctx, editor_state, grid_settings, background_settings, scene_manager, shooting_stats, game_state, editor_scene_state, commands, selected_entity, entity_query, asset_registry, asset_importer, asset_browser_state);
+            render_separate_windows(ctx, editor_state, grid_settings, background_settings, scene_manager, shooting_stats, game_state, editor_scene_state, commands, selected_entity, entity_query, inspector_params, hierarchy_params, assets, prefab_library);
         },
     }
 }
@@ -71,10 +75,11 @@ fn render_overlay_panels(
     editor_scene_state: &mut EditorSceneState,
     commands: &mut Commands,
     selected_entity: &mut SelectedEntity,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    asset_registry: &AssetRegistry,
-    asset_importer: &mut AssetImporter,
-    asset_browser_state: &mut AssetBrowserState,
+    entity_query: &EditorEntityQuery,
+    inspector_params: &mut inspector::InspectorParams,
+    hierarchy_params: &mut hierarchy::HierarchyParams,
+    assets: &mut AssetPanelParams,
+    prefab_library: &PrefabLibrary,
 ) {
     // Check if any panels should be shown
     let show_any_panel = editor_state.show_inspector || editor_state.show_hierarchy ||
@@ -91,7 +96,7 @@ fn render_overlay_panels(
                     // Inspector Panel
                     if editor_state.show_inspector {
                         ui.collapsing("Inspector", |ui| {
-                            inspector::render_inspector_content(ui, selected_entity, entity_query);
+                            inspector::render_inspector_content(ui, selected_entity, entity_query, inspector_params, commands);
                         });
                         ui.separator();
                     }
@@ -99,7 +104,7 @@ fn render_overlay_panels(
                     // Hierarchy Panel
                     if editor_state.show_hierarchy {
                         ui.collapsing("Hierarchy", |ui| {
-                            hierarchy::render_hierarchy_content(ui, entity_query, selected_entity, commands, editor_state, scene_manager);
+                            hierarchy::render_hierarchy_content(ui, entity_query, hierarchy_params, selected_entity, commands, editor_state, scene_manager);
                         });
                         ui.separator();
                     }
@@ -115,7 +120,7 @@ fn render_overlay_panels(
                     // Entity Spawner Panel
                     if editor_state.show_entity_spawner {
                         ui.collapsing("Entity Spawner", |ui| {
-                            render_entity_spawner_content(ui, editor_state, scene_manager);
+                            render_entity_spawner_content(ui, editor_state, scene_manager, prefab_library, commands);
                         });
                         ui.separator();
                     }
@@ -123,7 +128,7 @@ fn render_overlay_panels(
                     // Asset Manager Panel
                     if editor_state.show_asset_manager {
                         ui.collapsing("Asset Manager", |ui| {
-                            render_asset_manager_content(ui);
+                            render_asset_manager_content(ui, &assets.asset_registry, &mut assets.asset_importer);
                         });
                         ui.separator();
                     }
@@ -131,7 +136,7 @@ fn render_overlay_panels(
                     // Asset Browser Panel
                     if editor_state.show_asset_browser {
                         ui.collapsing("Asset Browser", |ui| {
-                            asset_browser::render_asset_browser_content(ui, asset_registry, asset_importer, asset_browser_state);
+                            asset_browser::render_asset_browser_content(ui, &assets.asset_registry, &mut assets.asset_importer, &mut assets.asset_browser_state, &mut assets.atlas_library, &assets.images, None);
                         });
                         ui.separator();
                     }
@@ -175,10 +180,11 @@ fn render_separate_windows(
     editor_scene_state: &mut EditorSceneState,
     commands: &mut Commands,
     selected_entity: &mut SelectedEntity,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    asset_registry: &AssetRegistry,
-    asset_importer: &mut AssetImporter,
-    asset_browser_state: &mut AssetBrowserState,
+    entity_query: &EditorEntityQuery,
+    inspector_params: &mut inspector::InspectorParams,
+    hierarchy_params: &mut hierarchy::HierarchyParams,
+    assets: &mut AssetPanelParams,
+    prefab_library: &PrefabLibrary,
 ) {
     // Inspector Window
     if editor_state.show_inspector {
@@ -187,7 +193,7 @@ fn render_separate_windows(
             .default_width(300.0)
             .resizable(true)
             .show(ctx, |ui| {
-                inspector::render_inspector_content(ui, selected_entity, entity_query);
+                inspector::render_inspector_content(ui, selected_entity, entity_query, inspector_params, commands);
             });
     }
 
@@ -199,7 +205,7 @@ fn render_separate_windows(
             .default_width(250.0)
             .resizable(true)
             .show(ctx, |ui| {
-                hierarchy::render_hierarchy_content(ui, entity_query, selected_entity, commands, editor_state, scene_manager);
+                hierarchy::render_hierarchy_content(ui, entity_query, hierarchy_params, selected_entity, commands, editor_state, scene_manager);
             });
         editor_state.show_hierarchy = show_hierarchy;
     }
@@ -223,7 +229,7 @@ fn render_separate_windows(
             .default_width(250.0)
             .resizable(true)
             .show(ctx, |ui| {
-                render_entity_spawner_content(ui, editor_state, scene_manager);
+                render_entity_spawner_content(ui, editor_state, scene_manager, prefab_library, commands);
             });
         editor_state.show_entity_spawner = show_entity_spawner;
     }
@@ -235,7 +241,7 @@ fn render_separate_windows(
             .default_width(300.0)
             .resizable(true)
             .show(ctx, |ui| {
-                render_asset_manager_content(ui);
+                render_asset_manager_content(ui, &assets.asset_registry, &mut assets.asset_importer);
             });
     }
     
@@ -247,7 +253,7 @@ fn render_separate_windows(
             .default_height(400.0)
             .resizable(true)
             .show(ctx, |ui| {
-                asset_browser::render_asset_browser_content(ui, asset_registry, asset_importer, asset_browser_state);
+                asset_browser::render_asset_browser_content(ui, &assets.asset_registry, &mut assets.asset_importer, &mut assets.asset_browser_state, &mut assets.atlas_library, &assets.images, None);
             });
     }
 
@@ -288,7 +294,7 @@ fn render_separate_windows(
 
 
 /// Render scene manager content
-fn render_scene_manager_content(ui: &mut egui::Ui, scene_manager: &SceneManager) {
+pub fn render_scene_manager_content(ui: &mut egui::Ui, scene_manager: &mut SceneManager) {
     ui.horizontal(|ui| {
         ui.label("Save Path:");
         ui.label(&scene_manager.save_path);
@@ -297,29 +303,86 @@ fn render_scene_manager_content(ui: &mut egui::Ui, scene_manager: &SceneManager)
     ui.separator();
     
     if ui.button("Save Scene").clicked() {
-        println!("Saving scene to: {}", scene_manager.save_path);
+        let path = scene_manager.save_path.clone();
+        scene_manager.pending_operations.push(SceneOperation::Save(path));
+    }
+    
+    if ui.button("Save Scene As...").clicked() {
+        if let Some(path) = menus::pick_scene_save_path() {
+            scene_manager.pending_operations.push(SceneOperation::Save(path));
+        }
     }
     
-    if ui.button("Load Scene").clicked() {
-        println!("Loading scene from: {}", scene_manager.save_path);
+    if ui.button("Load Scene...").clicked() {
+        if let Some(path) = menus::pick_scene_to_open() {
+            scene_manager.pending_operations.push(SceneOperation::Load(path));
+        }
     }
     
     if ui.button("New Scene").clicked() {
-        println!("Creating new scene");
+        scene_manager.pending_operations.push(SceneOperation::New);
+    }
+    
+    if let Some(status) = &scene_manager.status_message {
+        ui.separator();
+        ui.small(status);
+    }
+    
+    if !scene_manager.recent_scenes.is_empty() {
+        ui.separator();
+        ui.label("Recent Scenes:");
+        
+        let mut selected = None;
+        for path in &scene_manager.recent_scenes {
+            if ui.small_button(path).clicked() {
+                selected = Some(path.clone());
+            }
+        }
+        
+        if let Some(path) = selected {
+            scene_manager.pending_operations.push(SceneOperation::Load(path));
+        }
     }
 }
 
 /// Render entity spawner content
-pub fn render_entity_spawner_content(ui: &mut egui::Ui, editor_state: &EditorState, scene_manager: &mut SceneManager) {
+///
+/// The type list offers the built-in entity types followed by every prefab in the
+/// prefab library.
+pub fn render_entity_spawner_content(
+    ui: &mut egui::Ui,
+    editor_state: &EditorState,
+    scene_manager: &mut SceneManager,
+    prefab_library: &PrefabLibrary,
+    commands: &mut Commands,
+) {
     ui.horizontal(|ui| {
         ui.label("Type:");
         egui::ComboBox::from_label("")
-            .selected_text(format!("{:?}", scene_manager.spawn_entity_type))
+            .selected_text(scene_manager.spawn_template_name())
             .show_ui(ui, |ui| {
-                ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Player, "Player");
-                ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Enemy, "Enemy");
-                ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Projectile, "Projectile");
+                for entity_type in [EntityType::Player, EntityType::Enemy, EntityType::Projectile] {
+                    let selected = scene_manager.spawn_prefab.is_none() && scene_manager.spawn_entity_type == entity_type;
+                    if ui.selectable_label(selected, format!("{:?}", entity_type)).clicked() {
+                        scene_manager.spawn_entity_type = entity_type;
+                        scene_manager.spawn_prefab = None;
+                    }
+                }
+                
+                if !prefab_library.prefabs.is_empty() {
+                    ui.separator();
+                }
+                for (path, prefab) in &prefab_library.prefabs {
+                    let selected = scene_manager.spawn_prefab.as_ref() == Some(path);
+                    if ui.selectable_label(selected, format!("📦 {}", prefab.name)).clicked() {
+                        scene_manager.spawn_prefab = Some(path.clone());
+                    }
+                }
             });
+        
+        if ui.small_button("🔄").on_hover_text("Refresh Prefabs").clicked() {
+            commands.add(RefreshPrefabs);
+        }
     });
     
     ui.horizontal(|ui| {
@@ -356,21 +419,56 @@ pub fn render_entity_spawner_content(ui: &mut egui::Ui, editor_state: &EditorSta
 }
 
 /// Render asset manager content
-pub fn render_asset_manager_content(ui: &mut egui::Ui) {
-    ui.label("Assets:");
-    ui.separator();
-    ui.label("No assets loaded");
+pub fn render_asset_manager_content(ui: &mut egui::Ui, asset_registry: &AssetRegistry, asset_importer: &mut AssetImporter) {
+    ui.horizontal(|ui| {
+        ui.label("Loaded Assets:");
+        ui.label(format!("{}", asset_registry.loaded_images.len() + asset_registry.loaded_sounds.len()));
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Loading:");
+        ui.label(format!("{}", asset_registry.loading_assets.len() + asset_registry.loading_sounds.len()));
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Queued Imports:");
+        ui.label(format!("{}", asset_importer.import_queue.len() + asset_importer.pending_imports.len()));
+    });
+    
     ui.separator();
     
-    if ui.button("Import Asset").clicked() {
-        println!("Asset import dialog would open here");
+    if asset_registry.asset_metadata.is_empty() {
+        ui.label("No assets loaded");
+    } else {
+        let total_size: u64 = asset_registry.asset_metadata.values().map(|m| m.file_size).sum();
+        ui.label(format!("Total Size: {}", crate::assets::utils::format_file_size(total_size)));
     }
     
-    if ui.button("Refresh Assets").clicked() {
-        println!("Refreshing asset list");
+    if !asset_importer.failed_imports.is_empty() {
+        ui.separator();
+        ui.colored_label(egui::Color32::RED, "Failed Imports:");
+        for (path, error) in &asset_importer.failed_imports {
+            ui.small(format!("• {}: {}", path, error));
+        }
+        
+        if ui.button("Clear Failed").clicked() {
+            asset_importer.clear_failed_imports();
+        }
     }
 }
 
+/// Render the audio bus volumes
+pub fn render_audio_mixer_content(ui: &mut egui::Ui, audio_settings: &mut AudioSettings) {
+    ui.heading("Audio");
+    
+    ui.checkbox(&mut audio_settings.muted, "Mute");
+    ui.add_enabled_ui(!audio_settings.muted, |ui| {
+        ui.add(egui::Slider::new(&mut audio_settings.master, 0.0..=1.0).text("Master"));
+        ui.add(egui::Slider::new(&mut audio_settings.music, 0.0..=1.0).text("Music"));
+        ui.add(egui::Slider::new(&mut audio_settings.sfx, 0.0..=1.0).text("Effects"));
+    });
+}
+
 /// Render game controls content
 pub fn render_game_controls_content(ui: &mut egui::Ui, game_state: &mut GameState) {
     ui.heading("Game Controls");
@@ -492,6 +590,21 @@ pub fn render_grid_settings_content(ui: &mut egui::Ui, grid_settings: &mut GridS
             grid_settings.color = Color::rgb(color[0], color[1], color[2]);
         }
     });
+    
+    ui.separator();
+    
+    ui.checkbox(&mut grid_settings.snap_enabled, "Snap to Grid");
+    
+    ui.checkbox(&mut grid_settings.rotation_snap_enabled, "Snap Rotation");
+    ui.add_enabled_ui(grid_settings.rotation_snap_enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Rotation Step:");
+            ui.add(egui::DragValue::new(&mut grid_settings.rotation_snap_degrees)
+                .speed(1.0)
+                .clamp_range(1.0..=90.0)
+                .suffix("°"));
+        });
+    });
 }
 
 /// Render background settings content
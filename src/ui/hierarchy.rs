@@ -1,15 +1,118 @@
 //! Hierarchy panel for entity management
+//!
+//! Scene entities are shown as a tree following Bevy's `Parent`/`Children` links.
+//! Dragging a row onto another makes it a child of that entity, and dropping it
+//! below the tree moves it back to the top level. Double-clicking a row renames the
+//! entity, and the filter above the tree lists only the entities whose name, tag or
+//! component type matches.
 
 use bevy::prelude::*;
+use bevy::ecs::archetype::Archetypes;
+use bevy::ecs::component::Components;
+use bevy::ecs::entity::Entities;
+use bevy::ecs::system::SystemParam;
+use bevy::utils::get_short_name;
 use bevy_egui::egui;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::history::{DeleteEntity, ReparentEntity};
+use crate::scene::SceneRootFilter;
+use crate::ui::inspector::ApplyReflectedComponents;
+use crate::ui::EditorEntityQuery;
+
+/// What a hierarchy row shows of an entity: its label, id and built-in type
+type RowData = (
+    Option<&'static EntityLabel>,
+    Option<&'static EntityId>,
+    Has<Player>,
+    Has<Enemy>,
+    Has<Projectile>,
+);
+
+/// World access the hierarchy panel needs besides the shared entity query
+#[derive(SystemParam)]
+pub struct HierarchyParams<'w, 's> {
+    pub root_query: Query<'w, 's, Entity, SceneRootFilter>,
+    pub children_query: Query<'w, 's, &'static Children>,
+    pub label_query: Query<'w, 's, RowData>,
+    pub state: ResMut<'w, HierarchyState>,
+    pub camera_controller: ResMut<'w, CameraController>,
+    // Used to match the component filter against every component of an entity
+    pub entities: &'w Entities,
+    pub archetypes: &'w Archetypes,
+    pub components: &'w Components,
+}
+
+impl HierarchyParams<'_, '_> {
+    /// Label shown for an entity: its name, or its type and stable ID when unnamed
+    pub fn entity_name(&self, entity: Entity) -> String {
+        let Ok((label, id, player, enemy, projectile)) = self.label_query.get(entity) else {
+            return format!("Entity ({})", entity.index());
+        };
+
+        if let Some(label) = label.filter(|label| !label.name.is_empty()) {
+            return label.name.clone();
+        }
+
+        let kind = if player {
+            "Player"
+        } else if enemy {
+            "Enemy"
+        } else if projectile {
+            "Projectile"
+        } else {
+            "Entity"
+        };
+        match id {
+            Some(id) => format!("{} #{}", kind, id.0),
+            None => format!("{} ({})", kind, entity.index()),
+        }
+    }
+
+    /// The scene entities in tree order: each root followed by its descendants
+    fn entities_in_order(&self) -> Vec<Entity> {
+        let mut roots: Vec<Entity> = self.root_query.iter().collect();
+        roots.sort();
+
+        let mut ordered = Vec::new();
+        let mut stack: Vec<Entity> = roots.into_iter().rev().collect();
+        while let Some(entity) = stack.pop() {
+            ordered.push(entity);
+            if let Ok(children) = self.children_query.get(entity) {
+                stack.extend(children.iter().rev());
+            }
+        }
+        ordered
+    }
+
+    /// Whether an entity matches the filter text, which is already lowercase
+    fn matches_filter(&self, entity: Entity, filter: &str) -> bool {
+        match self.state.filter_mode {
+            HierarchyFilterMode::Name => self.entity_name(entity).to_lowercase().contains(filter),
+            HierarchyFilterMode::Tag => self
+                .label_query
+                .get(entity)
+                .ok()
+                .and_then(|(label, ..)| label)
+                .is_some_and(|label| label.tags.iter().any(|tag| tag.to_lowercase().contains(filter))),
+            HierarchyFilterMode::Component => {
+                let Some(location) = self.entities.get(entity) else {
+                    return false;
+                };
+                self.archetypes[location.archetype_id]
+                    .components()
+                    .filter_map(|component_id| self.components.get_info(component_id))
+                    .any(|info| get_short_name(info.name()).to_lowercase().contains(filter))
+            }
+        }
+    }
+}
 
 /// Render the hierarchy panel
 pub fn render_hierarchy(
     ctx: &egui::Context,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    entity_query: &EditorEntityQuery,
     selected_entity: &mut SelectedEntity,
     commands: &mut Commands,
     editor_state: &mut EditorState,
@@ -33,7 +136,7 @@ pub fn render_hierarchy(
                     format!("Entity ({})", entity.index())
                 };
                 
-                let is_selected = selected_entity.entity == Some(entity);
+                let is_selected = selected_entity.contains(entity);
                 
                 ui.horizontal(|ui| {
                     // Entity name button
@@ -45,15 +148,13 @@ pub fn render_hierarchy(
                         });
                     
                     if ui.add(button).clicked() {
-                        selected_entity.entity = Some(entity);
+                        selected_entity.select(entity);
                     }
                     
                     // Delete button
                     if ui.small_button("🗑").clicked() {
-                        if selected_entity.entity == Some(entity) {
-                            selected_entity.entity = None;
-                        }
-                        commands.entity(entity).despawn();
+                        selected_entity.retain(|&selected| selected != entity);
+                        commands.add(DeleteEntity { entity });
                     }
                 });
                 
@@ -87,10 +188,15 @@ pub fn render_hierarchy(
             ui.horizontal(|ui| {
                 ui.label("Type:");
                 egui::ComboBox::from_label("")
-                    .selected_text(format!("{:?}", scene_manager.spawn_entity_type))
+                    .selected_text(scene_manager.spawn_template_name())
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Player, "Player");
-                        ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Enemy, "Enemy");
+                        for entity_type in [EntityType::Player, EntityType::Enemy] {
+                            let selected = scene_manager.spawn_prefab.is_none() && scene_manager.spawn_entity_type == entity_type;
+                            if ui.selectable_label(selected, format!("{:?}", entity_type)).clicked() {
+                                scene_manager.spawn_entity_type = entity_type;
+                                scene_manager.spawn_prefab = None;
+                            }
+                        }
                     });
             });
             
@@ -115,73 +221,52 @@ pub fn render_hierarchy(
 /// Render hierarchy content without window management - for use by unified panel system
 pub fn render_hierarchy_content(
     ui: &mut egui::Ui,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    entity_query: &EditorEntityQuery,
+    hierarchy_params: &mut HierarchyParams,
     selected_entity: &mut SelectedEntity,
     commands: &mut Commands,
     editor_state: &EditorState,
     scene_manager: &mut SceneManager,
 ) {
+    render_filter_bar(ui, &mut hierarchy_params.state);
+
     ui.separator();
-    
+
+    let filter = hierarchy_params.state.filter.trim().to_lowercase();
+
     egui::ScrollArea::vertical().show(ui, |ui| {
-        for (entity, transform, player, enemy, health, collision) in entity_query.iter() {
-            // Skip camera and other non-game entities
-            if player.is_none() && enemy.is_none() {
-                continue;
+        if filter.is_empty() {
+            // Query order changes as entities move between archetypes, so sort for a stable list
+            let mut roots: Vec<Entity> = hierarchy_params.root_query.iter().collect();
+            roots.sort();
+
+            for entity in roots {
+                render_entity_node(ui, entity, entity_query, hierarchy_params, selected_entity, commands);
             }
-            
-            let entity_name = if player.is_some() {
-                format!("Player ({})", entity.index())
-            } else if enemy.is_some() {
-                format!("Enemy ({})", entity.index())
-            } else {
-                format!("Entity ({})", entity.index())
-            };
-            
-            let is_selected = selected_entity.entity == Some(entity);
-            
-            ui.horizontal(|ui| {
-                // Entity name button
-                let button = egui::Button::new(&entity_name)
-                    .fill(if is_selected {
-                        egui::Color32::from_rgb(100, 100, 150)
-                    } else {
-                        egui::Color32::TRANSPARENT
-                    });
-                
-                if ui.add(button).clicked() {
-                    selected_entity.entity = Some(entity);
-                }
-                
-                // Delete button
-                if ui.small_button("🗑").clicked() {
-                    if selected_entity.entity == Some(entity) {
-                        selected_entity.entity = None;
-                    }
-                    commands.entity(entity).despawn();
-                }
-            });
-            
-            // Show entity info in a smaller font
-            if is_selected {
-                ui.indent("entity_info", |ui| {
-                    ui.small(format!("Pos: ({:.1}, {:.1}, {:.1})",
-                        transform.translation.x,
-                        transform.translation.y,
-                        transform.translation.z
-                    ));
-                    
-                    if let Some(health) = health {
-                        ui.small(format!("Health: {:.1}/{:.1}", health.current, health.max));
-                    }
-                    
-                    if let Some(collision) = collision {
-                        ui.small(format!("Collision Radius: {:.1}", collision.radius));
-                    }
-                });
+        } else {
+            // Matches are listed flat so they are not hidden inside collapsed parents
+            let matches: Vec<Entity> = hierarchy_params
+                .entities_in_order()
+                .into_iter()
+                .filter(|&entity| hierarchy_params.matches_filter(entity, &filter))
+                .collect();
+
+            if matches.is_empty() {
+                ui.weak("No matching entities");
+            }
+            for entity in matches {
+                render_entity_row(ui, entity, hierarchy_params, selected_entity, commands);
+                render_entity_info(ui, entity, entity_query, selected_entity);
             }
-            
-            ui.separator();
+        }
+
+        // Dropping an entity below the tree moves it back to the top level
+        let (_, dropped) = ui.dnd_drop_zone::<Entity>(egui::Frame::none().inner_margin(4.0), |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.small("Drop here to move to the top level");
+        });
+        if let Some(dropped) = dropped {
+            commands.add(ReparentEntity { entity: *dropped, parent: None });
         }
     });
     
@@ -192,10 +277,15 @@ pub fn render_hierarchy_content(
         ui.horizontal(|ui| {
             ui.label("Type:");
             egui::ComboBox::from_label("")
-                .selected_text(format!("{:?}", scene_manager.spawn_entity_type))
+                .selected_text(scene_manager.spawn_template_name())
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Player, "Player");
-                    ui.selectable_value(&mut scene_manager.spawn_entity_type, EntityType::Enemy, "Enemy");
+                    for entity_type in [EntityType::Player, EntityType::Enemy] {
+                        let selected = scene_manager.spawn_prefab.is_none() && scene_manager.spawn_entity_type == entity_type;
+                        if ui.selectable_label(selected, format!("{:?}", entity_type)).clicked() {
+                            scene_manager.spawn_entity_type = entity_type;
+                            scene_manager.spawn_prefab = None;
+                        }
+                    }
                 });
         });
         
@@ -211,4 +301,205 @@ pub fn render_hierarchy_content(
             }
         });
     });
-}
\ No newline at end of file
+}
+
+/// Render the filter text box and what it matches against
+fn render_filter_bar(ui: &mut egui::Ui, state: &mut HierarchyState) {
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.filter)
+                .hint_text("Filter")
+                .desired_width(120.0),
+        );
+
+        egui::ComboBox::from_id_source("hierarchy_filter_mode")
+            .selected_text(format!("{:?}", state.filter_mode))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.filter_mode, HierarchyFilterMode::Name, "Name");
+                ui.selectable_value(&mut state.filter_mode, HierarchyFilterMode::Tag, "Tag");
+                ui.selectable_value(&mut state.filter_mode, HierarchyFilterMode::Component, "Component");
+            });
+
+        if !state.filter.is_empty() && ui.small_button("✖").clicked() {
+            state.filter.clear();
+        }
+    });
+}
+
+/// Render one entity and, when expanded, its children
+fn render_entity_node(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    entity_query: &EditorEntityQuery,
+    hierarchy_params: &mut HierarchyParams,
+    selected_entity: &mut SelectedEntity,
+    commands: &mut Commands,
+) {
+    if entity_query.get(entity).is_err() {
+        return;
+    }
+
+    let children: Vec<Entity> = hierarchy_params
+        .children_query
+        .get(entity)
+        .map(|children| children.iter().copied().collect())
+        .unwrap_or_default();
+
+    if children.is_empty() {
+        ui.horizontal(|ui| {
+            // Line up with the rows that have an expand button
+            ui.add_space(ui.spacing().indent);
+            render_entity_row(ui, entity, hierarchy_params, selected_entity, commands);
+        });
+    } else {
+        let id = ui.make_persistent_id(("hierarchy_node", entity));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+            .show_header(ui, |ui| {
+                render_entity_row(ui, entity, hierarchy_params, selected_entity, commands);
+            })
+            .body(|ui| {
+                for child in children {
+                    render_entity_node(ui, child, entity_query, hierarchy_params, selected_entity, commands);
+                }
+            });
+    }
+
+    render_entity_info(ui, entity, entity_query, selected_entity);
+}
+
+/// Show the selected entity's details in a smaller font
+fn render_entity_info(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    entity_query: &EditorEntityQuery,
+    selected_entity: &SelectedEntity,
+) {
+    if selected_entity.entity != Some(entity) {
+        return;
+    }
+    let Ok((_, transform, _, _, health, collision)) = entity_query.get(entity) else {
+        return;
+    };
+
+    ui.indent("entity_info", |ui| {
+        ui.small(format!("Pos: ({:.1}, {:.1}, {:.1})",
+            transform.translation.x,
+            transform.translation.y,
+            transform.translation.z
+        ));
+        
+        if let Some(health) = health {
+            ui.small(format!("Health: {:.1}/{:.1}", health.current, health.max));
+        }
+        
+        if let Some(collision) = collision {
+            ui.small(format!("Collision Radius: {:.1}", collision.radius));
+        }
+    });
+}
+
+/// Render an entity's selectable, draggable name and its delete button, or the
+/// rename box while it is being renamed
+fn render_entity_row(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    hierarchy_params: &mut HierarchyParams,
+    selected_entity: &mut SelectedEntity,
+    commands: &mut Commands,
+) {
+    let rename_id = egui::Id::new(("hierarchy_rename", entity));
+
+    if let Some((_, name)) = hierarchy_params.state.renaming.as_mut().filter(|(renaming, _)| *renaming == entity) {
+        let response = ui.add(egui::TextEdit::singleline(name).id(rename_id).desired_width(140.0));
+
+        // Enter or clicking away keeps the name, Escape throws it away
+        if response.lost_focus() {
+            let cancelled = ui.input(|input| input.key_pressed(egui::Key::Escape));
+            if let Some((_, name)) = hierarchy_params.state.renaming.take() {
+                if !cancelled {
+                    rename_entity(hierarchy_params, commands, entity, name.trim());
+                }
+            }
+        }
+        return;
+    }
+
+    let is_selected = selected_entity.contains(entity);
+    let entity_name = hierarchy_params.entity_name(entity);
+
+    // Entity name button
+    let button = egui::Button::new(&entity_name)
+        .fill(if is_selected {
+            egui::Color32::from_rgb(100, 100, 150)
+        } else {
+            egui::Color32::TRANSPARENT
+        })
+        .sense(egui::Sense::click_and_drag());
+    let response = ui.add(button);
+
+    // Shift- or Ctrl-click adds to or removes from the selection
+    if response.clicked() {
+        if ui.input(|input| input.modifiers.shift || input.modifiers.command) {
+            selected_entity.toggle(entity);
+        } else {
+            selected_entity.select(entity);
+        }
+    }
+
+    let mut start_rename = response.double_clicked();
+    response.context_menu(|ui| {
+        if ui.button("Rename").clicked() {
+            start_rename = true;
+            ui.close_menu();
+        }
+        
+        // Keep the scene view centred on the entity until the view is dragged
+        let following = hierarchy_params.camera_controller.following_entity == Some(entity);
+        if ui.button(if following { "Stop Following" } else { "Follow in Scene View" }).clicked() {
+            hierarchy_params.camera_controller.following_entity = (!following).then_some(entity);
+            ui.close_menu();
+        }
+    });
+    if start_rename {
+        hierarchy_params.state.renaming = Some((entity, entity_name));
+        ui.memory_mut(|memory| memory.request_focus(rename_id));
+    }
+
+    // Dragging a row onto another makes it that entity's child
+    response.dnd_set_drag_payload(entity);
+    if response.dnd_hover_payload::<Entity>().is_some_and(|dragged| *dragged != entity) {
+        ui.painter().rect_stroke(response.rect, 2.0, ui.visuals().selection.stroke);
+    }
+    if let Some(dragged) = response.dnd_release_payload::<Entity>() {
+        if *dragged != entity {
+            commands.add(ReparentEntity { entity: *dragged, parent: Some(entity) });
+        }
+    }
+
+    // Delete button
+    if ui.small_button("🗑").clicked() {
+        selected_entity.retain(|&selected| selected != entity);
+        commands.add(DeleteEntity { entity });
+    }
+}
+
+/// Store a new name on an entity through the history, keeping its tags
+fn rename_entity(hierarchy_params: &HierarchyParams, commands: &mut Commands, entity: Entity, name: &str) {
+    let mut label = hierarchy_params
+        .label_query
+        .get(entity)
+        .ok()
+        .and_then(|(label, ..)| label.cloned())
+        .unwrap_or_default();
+
+    if label.name == name {
+        return;
+    }
+    label.name = name.to_string();
+
+    commands.add(ApplyReflectedComponents {
+        entity,
+        components: vec![Box::new(label)],
+    });
+}
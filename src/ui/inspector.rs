@@ -1,17 +1,114 @@
 //! Inspector panel for entity properties
 
 use bevy::prelude::*;
+use bevy::ecs::system::{Command, SystemParam};
 use bevy::math::EulerRot;
 use bevy_egui::egui;
+use std::any::TypeId;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::history::{self, EditorAction, EditorHistory};
+use crate::scene::prefab::{prefab_name, prefab_path, ApplyToPrefab, CreatePrefab, RevertPrefabOverride, CHILDREN_OVERRIDE};
+use crate::ui::align;
+use crate::ui::reflect_editor;
+use crate::ui::EditorEntityQuery;
+use crate::systems::tween::{end_timeline_preview, is_previewing};
+
+/// A sprite asset with the texture and sprite it shows, for the sprite section
+type SpriteAssetData = (&'static SpriteAsset, Option<&'static Handle<Image>>, Option<&'static Sprite>);
+
+/// System parameters needed by the inspector in addition to the shared entity query
+#[derive(SystemParam)]
+pub struct InspectorParams<'w, 's> {
+    pub sprite_query: Query<'w, 's, SpriteAssetData>,
+    pub asset_registry: Res<'w, AssetRegistry>,
+    pub label_query: Query<'w, 's, (Option<&'static EntityLabel>, Option<&'static EntityId>)>,
+    pub prefab_query: Query<'w, 's, &'static PrefabInstance>,
+    pub prefab_library: Res<'w, PrefabLibrary>,
+    pub snapshot: ResMut<'w, InspectorSnapshot>,
+    pub type_registry: Res<'w, AppTypeRegistry>,
+}
+
+/// Components that the generic reflected view skips, either because they have a
+/// dedicated editor above or because they are derived by the engine every frame
+pub fn is_hidden_component(type_id: TypeId) -> bool {
+    type_id == TypeId::of::<Transform>()
+        || type_id == TypeId::of::<GlobalTransform>()
+        || type_id == TypeId::of::<InheritedVisibility>()
+        || type_id == TypeId::of::<ViewVisibility>()
+        || type_id == TypeId::of::<Handle<Image>>()
+        || type_id == TypeId::of::<SpriteAsset>()
+        || type_id == TypeId::of::<EntityLabel>()
+        || type_id == TypeId::of::<EntityId>()
+        || type_id == TypeId::of::<PrefabInstance>()
+}
+
+/// Command that writes edited reflected components back to their entity and records
+/// the change in the editor history
+pub struct ApplyReflectedComponents {
+    pub entity: Entity,
+    pub components: Vec<Box<dyn Reflect>>,
+}
+
+impl Command for ApplyReflectedComponents {
+    fn apply(self, world: &mut World) {
+        // The entity may have been despawned since the edit was made
+        if world.get_entity(self.entity).is_none() {
+            return;
+        }
+        end_preview_before_edit(world, self.entity, &self.components);
+        
+        if let Some(action) = history::set_components(world, self.entity, self.components) {
+            let now = world.resource::<Time>().elapsed_seconds_f64();
+            world.resource_mut::<EditorHistory>().push_merged(action, now);
+        }
+    }
+}
+
+/// Command that writes edited reflected components to several entities as one undo
+/// step, used when a multiple selection is edited together
+pub struct ApplyReflectedEdits {
+    pub edits: Vec<(Entity, Vec<Box<dyn Reflect>>)>,
+}
+
+impl Command for ApplyReflectedEdits {
+    fn apply(self, world: &mut World) {
+        let mut actions = Vec::new();
+        for (entity, components) in self.edits {
+            // The entity may have been despawned since the edit was made
+            if world.get_entity(entity).is_none() {
+                continue;
+            }
+            end_preview_before_edit(world, entity, &components);
+            actions.extend(history::set_components(world, entity, components));
+        }
+        
+        if !actions.is_empty() {
+            let now = world.resource::<Time>().elapsed_seconds_f64();
+            world.resource_mut::<EditorHistory>().push_merged(EditorAction::Group(actions), now);
+        }
+    }
+}
+
+/// End the timeline preview before an edit of the previewed entity, so the edit
+/// applies to the entity as authored rather than its pose
+///
+/// Timeline edits from the timeline tab keep the preview running.
+fn end_preview_before_edit(world: &mut World, entity: Entity, components: &[Box<dyn Reflect>]) {
+    let edits_pose = components.iter().any(|component| {
+        component.get_represented_type_info().map(|info| info.type_id()) != Some(TypeId::of::<Timeline>())
+    });
+    if edits_pose && world.get_resource::<TimelinePreview>().is_some_and(|preview| is_previewing(preview, entity)) {
+        end_timeline_preview(world);
+    }
+}
 
 /// Render the inspector panel
 pub fn render_inspector(
     ctx: &egui::Context,
     selected_entity: &SelectedEntity,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    entity_query: &EditorEntityQuery,
     editor_state: &mut EditorState,
 ) {
     let render_content = |ui: &mut egui::Ui| {
@@ -114,19 +211,35 @@ pub fn render_inspector(
 }
 
 /// Render inspector content without window management - for use by unified panel system
+///
+/// Edited values are written back through `commands`, which avoids holding a mutable
+/// query alongside the shared read-only entity query. Every registered component other
+/// than the transform and sprite asset is edited generically through reflection.
 pub fn render_inspector_content(
     ui: &mut egui::Ui,
     selected_entity: &SelectedEntity,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    entity_query: &EditorEntityQuery,
+    inspector_params: &mut InspectorParams,
+    commands: &mut Commands,
 ) {
+    if selected_entity.len() > 1 {
+        render_selection_content(ui, selected_entity, entity_query, inspector_params, commands);
+        return;
+    }
+    
     if let Some(entity) = selected_entity.entity {
-        if let Ok((_, transform, player, enemy, health, collision)) = entity_query.get(entity) {
+        if let Ok((_, transform, player, enemy, _, _)) = entity_query.get(entity) {
             ui.separator();
             
-            // Entity ID and type
+            let (label, id) = inspector_params.label_query.get(entity).unwrap_or((None, None));
+            
+            // Entity ID and type, preferring the stable ID that is saved with the scene
             ui.horizontal(|ui| {
                 ui.label("Entity ID:");
-                ui.label(format!("{}", entity.index()));
+                match id {
+                    Some(id) => ui.label(format!("#{}", id.0)),
+                    None => ui.label(format!("{}", entity.index())),
+                };
             });
             
             if player.is_some() {
@@ -139,63 +252,83 @@ pub fn render_inspector_content(
             
             ui.separator();
             
-            // Transform section (read-only for now to avoid query conflicts)
-            ui.collapsing("Transform", |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("Position:");
-                    ui.label(format!("({:.2}, {:.2}, {:.2})",
-                        transform.translation.x,
-                        transform.translation.y,
-                        transform.translation.z
-                    ));
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.label("Scale:");
-                    ui.label(format!("({:.2}, {:.2})",
-                        transform.scale.x,
-                        transform.scale.y
-                    ));
-                });
-                
-                let rotation_degrees = transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees();
-                ui.horizontal(|ui| {
-                    ui.label("Rotation:");
-                    ui.label(format!("{:.1}°", rotation_degrees));
-                });
-                
-                ui.small("Note: Use mouse dragging to move entities");
+            // Name and tags section
+            ui.collapsing("Name & Tags", |ui| {
+                if let Some(edited) = edit_label(ui, entity, label) {
+                    commands.add(ApplyReflectedComponents {
+                        entity,
+                        components: vec![Box::new(edited)],
+                    });
+                }
             });
             
-            // Health section
-            if let Some(health) = health {
-                ui.separator();
-                ui.collapsing("Health", |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Current:");
-                        ui.label(format!("{:.1}", health.current));
-                    });
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("Max:");
-                        ui.label(format!("{:.1}", health.max));
+            // Prefab section
+            let instance = inspector_params.prefab_query.get(entity).ok();
+            ui.collapsing("Prefab", |ui| {
+                edit_prefab(ui, entity, instance, label, &inspector_params.prefab_library, commands);
+            });
+            
+            // Transform section
+            ui.collapsing("Transform", |ui| {
+                if let Some(edited) = edit_transform(ui, transform) {
+                    commands.add(ApplyReflectedComponents {
+                        entity,
+                        components: vec![Box::new(edited)],
                     });
-                    
-                    let health_ratio = health.current / health.max;
-                    ui.add(egui::ProgressBar::new(health_ratio)
-                        .text(format!("{:.1}/{:.1}", health.current, health.max)));
+                }
+            });
+            
+            // Sprite asset section
+            ui.separator();
+            if let Ok((sprite_asset, texture, sprite)) = inspector_params.sprite_query.get(entity) {
+                ui.collapsing("Sprite Asset", |ui| {
+                    let edited = edit_sprite_asset(ui, sprite_asset);
+                    render_sprite_texture_status(ui, sprite_asset, texture, sprite, &inspector_params.asset_registry);
+                    if let Some(edited) = edited {
+                        // The asset scale is baked into the transform at spawn time,
+                        // so rescale the transform by the change in asset scale
+                        let old_scale = sprite_asset.get_scale();
+                        let new_scale = edited.get_scale();
+                        let mut rescaled = *transform;
+                        if old_scale.x != 0.0 && old_scale.y != 0.0 {
+                            rescaled.scale.x *= new_scale.x / old_scale.x;
+                            rescaled.scale.y *= new_scale.y / old_scale.y;
+                        }
+                        
+                        // Always send both so consecutive edits merge into one undo step
+                        commands.add(ApplyReflectedComponents {
+                            entity,
+                            components: vec![Box::new(edited), Box::new(rescaled)],
+                        });
+                    }
+                });
+            } else if ui.button("➕ Add Sprite Asset").clicked() {
+                commands.add(ApplyReflectedComponents {
+                    entity,
+                    components: vec![Box::new(SpriteAsset::default())],
                 });
             }
             
-            // Collision section
-            if let Some(collision) = collision {
-                ui.separator();
-                ui.collapsing("Collision", |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Radius:");
-                        ui.label(format!("{:.1}", collision.radius));
+            // Reflected components
+            if inspector_params.snapshot.entity == Some(entity) {
+                let type_registry = inspector_params.type_registry.read();
+                for (index, component) in inspector_params.snapshot.components.iter_mut().enumerate() {
+                    ui.separator();
+                    let name = component
+                        .get_represented_type_info()
+                        .map(|info| info.type_path_table().short_path())
+                        .unwrap_or("Component");
+                    ui.push_id(index, |ui| {
+                        ui.collapsing(name, |ui| {
+                            if reflect_editor::reflect_value_ui(ui, &mut **component, &type_registry) {
+                                commands.add(ApplyReflectedComponents {
+                                    entity,
+                                    components: vec![component.clone_value()],
+                                });
+                            }
+                        });
                     });
-                });
+                }
             }
             
         } else {
@@ -211,4 +344,411 @@ pub fn render_inspector_content(
         ui.small("• H: Toggle Hierarchy");
         ui.small("• G: Toggle Grid Settings");
     }
-}
\ No newline at end of file
+}
+
+/// Render the values a multiple selection has in common
+///
+/// The transform and every reflected component that all selected entities have are
+/// shown with the primary entity's values. Editing a value sets it on every selected
+/// entity and leaves their other values alone.
+fn render_selection_content(
+    ui: &mut egui::Ui,
+    selected_entity: &SelectedEntity,
+    entity_query: &EditorEntityQuery,
+    inspector_params: &mut InspectorParams,
+    commands: &mut Commands,
+) {
+    ui.separator();
+    ui.label(format!("{} entities selected", selected_entity.len()));
+    ui.separator();
+    
+    // Transform section
+    let transforms: Vec<(Entity, Transform)> = selected_entity
+        .entities
+        .iter()
+        .filter_map(|&entity| entity_query.get(entity).ok())
+        .map(|(entity, transform, ..)| (entity, *transform))
+        .collect();
+    ui.collapsing("Transform", |ui| {
+        if let Some(edited) = edit_shared_transform(ui, &transforms) {
+            commands.add(ApplyReflectedEdits {
+                edits: edited
+                    .into_iter()
+                    .map(|(entity, transform)| (entity, vec![Box::new(transform) as Box<dyn Reflect>]))
+                    .collect(),
+            });
+        }
+    });
+    
+    ui.collapsing("Align & Distribute", |ui| {
+        align::render_align_tools(ui, selected_entity.len(), commands);
+    });
+    
+    // Reflected components shared by the whole selection
+    let snapshot = &mut *inspector_params.snapshot;
+    let Some(primary) = selected_entity.entity.filter(|&entity| snapshot.entity == Some(entity)) else {
+        return;
+    };
+    let type_registry = inspector_params.type_registry.read();
+    for (index, component) in snapshot.components.iter_mut().enumerate() {
+        let Some(type_id) = component.get_represented_type_info().map(|info| info.type_id()) else {
+            continue;
+        };
+        let others: Vec<(Entity, &dyn Reflect)> = snapshot
+            .others
+            .iter()
+            .filter_map(|(entity, components)| {
+                components
+                    .iter()
+                    .find(|other| other.get_represented_type_info().map(|info| info.type_id()) == Some(type_id))
+                    .map(|other| (*entity, &**other))
+            })
+            .collect();
+        if others.len() != snapshot.others.len() {
+            continue; // Not every selected entity has this component
+        }
+        
+        ui.separator();
+        let name = component
+            .get_represented_type_info()
+            .map(|info| info.type_path_table().short_path())
+            .unwrap_or("Component");
+        let mixed = others
+            .iter()
+            .any(|(_, other)| !other.reflect_partial_eq(&**component).unwrap_or(false));
+        
+        ui.push_id(index, |ui| {
+            ui.collapsing(name, |ui| {
+                if mixed {
+                    ui.weak("Values differ; edits only change the edited fields");
+                }
+                
+                let before = component.clone_value();
+                if reflect_editor::reflect_value_ui(ui, &mut **component, &type_registry) {
+                    let mut edits = vec![(primary, vec![component.clone_value()])];
+                    for (entity, other) in &others {
+                        let mut edited = other.clone_value();
+                        reflect_editor::copy_changed_fields(&*before, &**component, &mut *edited);
+                        edits.push((*entity, vec![edited]));
+                    }
+                    commands.add(ApplyReflectedEdits { edits });
+                }
+            });
+        });
+    }
+}
+
+/// Prefix, drag speed and accessors of one value in the shared transform editor
+type TransformField = (&'static str, f64, fn(&Transform) -> f32, fn(&mut Transform, f32));
+
+/// Edit the transforms of several entities together, returning the new transforms if
+/// anything changed
+///
+/// Values the entities disagree on show as "—". Editing a value sets it on every
+/// entity and keeps their other values.
+fn edit_shared_transform(ui: &mut egui::Ui, transforms: &[(Entity, Transform)]) -> Option<Vec<(Entity, Transform)>> {
+    let (_, first) = transforms.first()?;
+    
+    // Every value, in display order
+    let fields: [TransformField; 6] = [
+        ("X: ", 1.0, |transform| transform.translation.x, |transform, value| transform.translation.x = value),
+        ("Y: ", 1.0, |transform| transform.translation.y, |transform, value| transform.translation.y = value),
+        ("Z: ", 0.1, |transform| transform.translation.z, |transform, value| transform.translation.z = value),
+        (
+            "",
+            1.0,
+            |transform| transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees(),
+            |transform, value| transform.rotation = Quat::from_rotation_z(value.to_radians()),
+        ),
+        ("X: ", 0.5, |transform| transform.scale.x, |transform, value| transform.scale.x = value),
+        ("Y: ", 0.5, |transform| transform.scale.y, |transform, value| transform.scale.y = value),
+    ];
+    
+    let mut changed = None;
+    let mut field_ui = |ui: &mut egui::Ui, index: usize| {
+        let (prefix, speed, get, _) = fields[index];
+        let mut value = get(first);
+        let mixed = transforms.iter().any(|(_, transform)| (get(transform) - value).abs() > 1e-4);
+        
+        let mut drag = egui::DragValue::new(&mut value).speed(speed).prefix(prefix);
+        if index == 3 {
+            drag = drag.suffix("°");
+        }
+        if mixed {
+            drag = drag.custom_formatter(|_, _| "—".to_string());
+        }
+        if ui.add(drag).changed() {
+            changed = Some((index, value));
+        }
+    };
+    
+    ui.horizontal(|ui| {
+        ui.label("Position:");
+        for index in 0..3 {
+            field_ui(ui, index);
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Rotation:");
+        field_ui(ui, 3);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Scale:");
+        for index in 4..6 {
+            field_ui(ui, index);
+        }
+    });
+    
+    let (index, value) = changed?;
+    let set = fields[index].3;
+    Some(
+        transforms
+            .iter()
+            .map(|&(entity, mut transform)| {
+                set(&mut transform, value);
+                (entity, transform)
+            })
+            .collect(),
+    )
+}
+
+/// Edit a transform, returning the new value if anything changed
+fn edit_transform(ui: &mut egui::Ui, transform: &Transform) -> Option<Transform> {
+    let mut edited = *transform;
+    let mut changed = false;
+    
+    ui.horizontal(|ui| {
+        ui.label("Position:");
+        changed |= ui.add(egui::DragValue::new(&mut edited.translation.x).speed(1.0).prefix("X: ")).changed();
+        changed |= ui.add(egui::DragValue::new(&mut edited.translation.y).speed(1.0).prefix("Y: ")).changed();
+        changed |= ui.add(egui::DragValue::new(&mut edited.translation.z).speed(0.1).prefix("Z: ")).changed();
+    });
+    
+    let mut rotation_degrees = transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees();
+    ui.horizontal(|ui| {
+        ui.label("Rotation:");
+        if ui.add(egui::DragValue::new(&mut rotation_degrees).speed(1.0).suffix("°")).changed() {
+            edited.rotation = Quat::from_rotation_z(rotation_degrees.to_radians());
+            changed = true;
+        }
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Scale:");
+        changed |= ui.add(egui::DragValue::new(&mut edited.scale.x).speed(0.5).prefix("X: ")).changed();
+        changed |= ui.add(egui::DragValue::new(&mut edited.scale.y).speed(0.5).prefix("Y: ")).changed();
+    });
+    
+    changed.then_some(edited)
+}
+
+/// Edit an entity's name and tags, returning the new label if anything changed
+fn edit_label(ui: &mut egui::Ui, entity: Entity, label: Option<&EntityLabel>) -> Option<EntityLabel> {
+    let mut edited = label.cloned().unwrap_or_default();
+    let mut changed = false;
+    
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        changed |= ui.text_edit_singleline(&mut edited.name).changed();
+    });
+    
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Tags:");
+        let mut removed = None;
+        for (index, tag) in edited.tags.iter().enumerate() {
+            if ui.small_button(format!("{} ✖", tag)).on_hover_text("Remove tag").clicked() {
+                removed = Some(index);
+            }
+        }
+        if let Some(index) = removed {
+            edited.tags.remove(index);
+            changed = true;
+        }
+    });
+    
+    // The tag being typed is kept in egui's memory until it is added
+    let new_tag_id = ui.make_persistent_id(("new_tag", entity));
+    let mut new_tag = ui.data_mut(|data| data.get_temp::<String>(new_tag_id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        let response = ui.add(egui::TextEdit::singleline(&mut new_tag).hint_text("New tag").desired_width(100.0));
+        let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        
+        if (ui.small_button("➕").clicked() || submitted) && !new_tag.trim().is_empty() {
+            let tag = new_tag.trim().to_string();
+            if !edited.has_tag(&tag) {
+                edited.tags.push(tag);
+                changed = true;
+            }
+            new_tag.clear();
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(new_tag_id, new_tag));
+    
+    changed.then_some(edited)
+}
+
+/// Show an instance's prefab link and overrides, or offer to save the entity as a prefab
+fn edit_prefab(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    instance: Option<&PrefabInstance>,
+    label: Option<&EntityLabel>,
+    prefab_library: &PrefabLibrary,
+    commands: &mut Commands,
+) {
+    if let Some(instance) = instance {
+        ui.label(format!("Instance of: {}", prefab_name(&instance.path)));
+        
+        if instance.overrides.is_empty() {
+            ui.small("Matches the prefab");
+        } else {
+            ui.label("Overrides:");
+            for type_path in &instance.overrides {
+                ui.horizontal(|ui| {
+                    let name = if type_path == CHILDREN_OVERRIDE {
+                        "Children"
+                    } else {
+                        type_path.rsplit("::").next().unwrap_or(type_path)
+                    };
+                    ui.label(format!("• {}", name)).on_hover_text(type_path);
+                    if ui.small_button("↺ Revert").on_hover_text("Take the prefab's value").clicked() {
+                        commands.add(RevertPrefabOverride {
+                            entity,
+                            type_path: type_path.clone(),
+                        });
+                    }
+                });
+            }
+        }
+        
+        if ui.button("⬆ Apply to Prefab").on_hover_text("Save this instance to the prefab and update every instance").clicked() {
+            commands.add(ApplyToPrefab { entity });
+        }
+        return;
+    }
+    
+    // The name being typed is kept in egui's memory until the prefab is saved
+    let name_id = ui.make_persistent_id(("prefab_name", entity));
+    let mut name = ui
+        .data_mut(|data| data.get_temp::<String>(name_id))
+        .unwrap_or_else(|| label.map(|label| label.name.clone()).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        ui.add(egui::TextEdit::singleline(&mut name).hint_text("Prefab name").desired_width(120.0));
+    });
+    
+    let valid = !name.trim().is_empty() && !name.contains(['/', '\\']);
+    let exists = prefab_library.get(&prefab_path(name.trim())).is_some();
+    if valid && exists {
+        // Saving over a prefab changes every instance of it, so ask first
+        ui.colored_label(egui::Color32::YELLOW, "⚠ A prefab with this name exists");
+        if ui.button("💾 Replace Prefab").on_hover_text("Overwrite the prefab and update its instances").clicked() {
+            commands.add(CreatePrefab {
+                entity,
+                name: name.trim().to_string(),
+                replace: true,
+            });
+        }
+    } else if ui.add_enabled(valid, egui::Button::new("💾 Save as Prefab")).clicked() {
+        commands.add(CreatePrefab {
+            entity,
+            name: name.trim().to_string(),
+            replace: false,
+        });
+    }
+    ui.data_mut(|data| data.insert_temp(name_id, name));
+}
+
+/// Show whether the sprite asset's texture is still loading or couldn't be found,
+/// and whether its frame was found in the image's atlas
+fn render_sprite_texture_status(
+    ui: &mut egui::Ui,
+    sprite_asset: &SpriteAsset,
+    texture: Option<&Handle<Image>>,
+    sprite: Option<&Sprite>,
+    asset_registry: &AssetRegistry,
+) {
+    let Some(path) = &sprite_asset.asset_path else {
+        return;
+    };
+    if texture == Some(&asset_registry.missing_texture) {
+        ui.colored_label(egui::Color32::YELLOW, "⚠ File not found — showing placeholder");
+    } else if asset_registry.is_loading(path) {
+        ui.weak("Loading…");
+    } else if sprite_asset.frame.is_some() && sprite.is_some_and(|sprite| sprite.rect.is_none()) {
+        // The texture system leaves the rectangle empty when the atlas has no such frame
+        ui.colored_label(egui::Color32::YELLOW, "⚠ Frame not in the image's atlas — showing whole image");
+    }
+}
+
+/// Edit sprite asset tint, scale, path and frame, returning the new value if anything changed
+fn edit_sprite_asset(ui: &mut egui::Ui, sprite_asset: &SpriteAsset) -> Option<SpriteAsset> {
+    let mut edited = sprite_asset.clone();
+    let mut changed = false;
+    
+    ui.horizontal(|ui| {
+        ui.label("Tint:");
+        changed |= ui.color_edit_button_rgba_unmultiplied(&mut edited.tint_color).changed();
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Scale:");
+        changed |= ui.add(egui::DragValue::new(&mut edited.scale[0]).speed(0.01).clamp_range(0.01..=100.0).prefix("X: ")).changed();
+        changed |= ui.add(egui::DragValue::new(&mut edited.scale[1]).speed(0.01).clamp_range(0.01..=100.0).prefix("Y: ")).changed();
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Path:");
+        let mut path = edited.asset_path.clone().unwrap_or_default();
+        if ui.text_edit_singleline(&mut path).changed() {
+            edited.asset_path = if path.is_empty() { None } else { Some(path) };
+            changed = true;
+        }
+        
+        if edited.asset_path.is_some() && ui.small_button("✖").on_hover_text("Clear path").clicked() {
+            edited.asset_path = None;
+            changed = true;
+        }
+    });
+    
+    ui.horizontal(|ui| {
+        ui.label("Frame:");
+        let selected_text = match &edited.frame {
+            None => "Whole Image",
+            Some(SpriteFrame::Index(_)) => "By Index",
+            Some(SpriteFrame::Name(_)) => "By Name",
+        };
+        egui::ComboBox::from_id_source("sprite_asset_frame")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(edited.frame.is_none(), "Whole Image").clicked() && edited.frame.is_some() {
+                    edited.frame = None;
+                    changed = true;
+                }
+                if ui.selectable_label(matches!(edited.frame, Some(SpriteFrame::Index(_))), "By Index").clicked()
+                    && !matches!(edited.frame, Some(SpriteFrame::Index(_)))
+                {
+                    edited.frame = Some(SpriteFrame::Index(0));
+                    changed = true;
+                }
+                if ui.selectable_label(matches!(edited.frame, Some(SpriteFrame::Name(_))), "By Name").clicked()
+                    && !matches!(edited.frame, Some(SpriteFrame::Name(_)))
+                {
+                    edited.frame = Some(SpriteFrame::Name(String::new()));
+                    changed = true;
+                }
+            });
+        
+        match &mut edited.frame {
+            Some(SpriteFrame::Index(index)) => {
+                changed |= ui.add(egui::DragValue::new(index).prefix("#")).changed();
+            }
+            Some(SpriteFrame::Name(name)) => {
+                changed |= ui.text_edit_singleline(name).changed();
+            }
+            None => {}
+        }
+    });
+    
+    changed.then_some(edited)
+}
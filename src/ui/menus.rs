@@ -3,9 +3,13 @@
 use bevy::prelude::*;
 use bevy_egui::egui;
 
-use crate::components::*;
+use crate::components::EntityId;
 use crate::resources::*;
 use crate::scene::*;
+use crate::history::{EditorHistory, Redo, Undo};
+use crate::systems::tween::end_timeline_preview;
+use crate::ui::align::{AlignMode, AlignSelection};
+use crate::ui::clipboard::{CopySelection, DuplicateSelection, PasteClipboard};
 
 /// Render the main menu bar with integrated game controls
 pub fn render_menu_bar(
@@ -16,8 +20,8 @@ pub fn render_menu_bar(
     grid_settings: &mut GridSettings,
     game_state: &mut GameState,
     shooting_stats: &ShootingStats,
-    editor_scene_state: &mut EditorSceneState,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    commands: &mut Commands,
+    history: &mut EditorHistory,
 ) {
     egui::TopBottomPanel::top("unified_menu_bar").show(ctx, |ui| {
         // Menu bar row
@@ -50,17 +54,10 @@ pub fn render_menu_bar(
                 
                 ui.separator();
                 
-                if ui.button("Save Scene").clicked() {
-                    // This would be handled by the scene system
-                    println!("Saving scene to: {}", scene_manager.save_path);
-                    ui.close_menu();
-                }
-                
-                if ui.button("Load Scene").clicked() {
-                    // This would be handled by the scene system
-                    println!("Loading scene from: {}", scene_manager.save_path);
-                    ui.close_menu();
-                }
+                // Scene files hold the scene as edited, which play mode has changed
+                ui.add_enabled_ui(!game_state.playing, |ui| {
+                    render_scene_file_menu(ui, scene_manager);
+                });
                 
                 ui.separator();
                 
@@ -70,27 +67,81 @@ pub fn render_menu_bar(
             });
             
             ui.menu_button("Edit", |ui| {
-                if ui.button("Undo").clicked() {
-                    println!("Undo functionality not implemented yet");
+                let undo_text = match history.undo_description() {
+                    Some(description) => format!("Undo {}", description),
+                    None => "Undo".to_string(),
+                };
+                if ui.add_enabled(history.can_undo(), egui::Button::new(undo_text).shortcut_text("Ctrl+Z")).clicked() {
+                    commands.add(Undo);
                     ui.close_menu();
                 }
                 
-                if ui.button("Redo").clicked() {
-                    println!("Redo functionality not implemented yet");
+                let redo_text = match history.redo_description() {
+                    Some(description) => format!("Redo {}", description),
+                    None => "Redo".to_string(),
+                };
+                if ui.add_enabled(history.can_redo(), egui::Button::new(redo_text).shortcut_text("Ctrl+Shift+Z")).clicked() {
+                    commands.add(Redo);
+                    ui.close_menu();
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.label("History Depth:");
+                    let mut max_depth = history.max_depth();
+                    if ui.add(egui::DragValue::new(&mut max_depth).clamp_range(1..=1000)).changed() {
+                        history.set_max_depth(max_depth);
+                    }
+                });
+                
+                if ui.button("Clear History").clicked() {
+                    history.clear();
                     ui.close_menu();
                 }
                 
                 ui.separator();
                 
-                if ui.button("Copy").clicked() {
-                    println!("Copy functionality not implemented yet");
+                if ui.add(egui::Button::new("Cut").shortcut_text("Ctrl+X")).clicked() {
+                    commands.add(CopySelection { cut: true });
+                    ui.close_menu();
+                }
+                
+                if ui.add(egui::Button::new("Copy").shortcut_text("Ctrl+C")).clicked() {
+                    commands.add(CopySelection { cut: false });
+                    ui.close_menu();
+                }
+                
+                if ui.add(egui::Button::new("Paste").shortcut_text("Ctrl+V")).clicked() {
+                    commands.add(PasteClipboard { position: editor_state.mouse_world_position });
                     ui.close_menu();
                 }
                 
-                if ui.button("Paste").clicked() {
-                    println!("Paste functionality not implemented yet");
+                if ui.add(egui::Button::new("Duplicate").shortcut_text("Ctrl+D")).clicked() {
+                    commands.add(DuplicateSelection);
                     ui.close_menu();
                 }
+                
+                ui.separator();
+                
+                ui.checkbox(&mut grid_settings.snap_enabled, "Snap to Grid");
+                ui.checkbox(&mut grid_settings.rotation_snap_enabled, "Snap Rotation");
+                
+                ui.menu_button("Align", |ui| {
+                    for mode in [
+                        AlignMode::Left,
+                        AlignMode::CenterX,
+                        AlignMode::Right,
+                        AlignMode::Top,
+                        AlignMode::CenterY,
+                        AlignMode::Bottom,
+                        AlignMode::DistributeX,
+                        AlignMode::DistributeY,
+                    ] {
+                        if ui.button(mode.label()).clicked() {
+                            commands.add(AlignSelection { mode });
+                            ui.close_menu();
+                        }
+                    }
+                });
             });
             
             ui.menu_button("View", |ui| {
@@ -160,7 +211,7 @@ pub fn render_menu_bar(
                     info!("Game {}", if game_state.paused { "paused" } else { "resumed" });
                 } else {
                     // Save scene state before starting play mode
-                    save_scene_state_for_play(editor_scene_state, entity_query);
+                    save_scene_state_for_play(commands);
                     
                     // Start playing
                     game_state.playing = true;
@@ -221,46 +272,144 @@ pub fn render_menu_bar(
 }
 
 /// Save scene state for play mode
-fn save_scene_state_for_play(
-    editor_scene_state: &mut EditorSceneState,
-    entity_query: &Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-) {
-    // Create a temporary scene data string
-    let mut scene_entities = Vec::new();
-    for (_, transform, player, enemy, health, collision) in entity_query.iter() {
-        let entity_type = if player.is_some() {
-            crate::components::EntityType::Player
-        } else if enemy.is_some() {
-            crate::components::EntityType::Enemy
-        } else {
-            continue;
-        };
-        
-        let serializable_entity = crate::components::SerializableEntity {
-            entity_type,
-            transform: crate::components::SerializableTransform::from(*transform),
-            health: health.map(|h| (h.current, h.max)),
-            collision_radius: collision.map(|c| c.radius),
-            sprite_asset: None, // Default to None for now
-        };
-        
-        scene_entities.push(serializable_entity);
+fn save_scene_state_for_play(commands: &mut Commands) {
+    commands.add(|world: &mut World| {
+        end_timeline_preview(world);
+        let scene = snapshot_scene(world);
+        let saved_entities = world
+            .query::<(Entity, &EntityId)>()
+            .iter(world)
+            .map(|(entity, id)| (*id, entity))
+            .collect();
+        let mut editor_scene_state = world.resource_mut::<EditorSceneState>();
+        editor_scene_state.saved_scene = Some(scene);
+        editor_scene_state.saved_entities = saved_entities;
+        info!("Scene state saved for play mode");
+    });
+}
+
+/// Scene entries of the File menu
+fn render_scene_file_menu(ui: &mut egui::Ui, scene_manager: &mut SceneManager) {
+    if ui.button("New Scene").clicked() {
+        scene_manager.pending_operations.push(SceneOperation::New);
+        ui.close_menu();
+    }
+    
+    if ui.add(egui::Button::new("Save Scene").shortcut_text("Ctrl+S")).clicked() {
+        let path = scene_manager.save_path.clone();
+        scene_manager.pending_operations.push(SceneOperation::Save(path));
+        ui.close_menu();
+    }
+    
+    if ui.button("Save Scene As...").clicked() {
+        if let Some(path) = pick_scene_save_path() {
+            scene_manager.pending_operations.push(SceneOperation::Save(path));
+        }
+        ui.close_menu();
+    }
+    
+    if ui.button("Load Scene...").clicked() {
+        if let Some(path) = pick_scene_to_open() {
+            scene_manager.pending_operations.push(SceneOperation::Load(path));
+        }
+        ui.close_menu();
     }
     
-    let scene = crate::scene::Scene {
-        entities: scene_entities,
-        metadata: crate::scene::SceneMetadata {
-            name: "Play Mode Save".to_string(),
-            version: "1.0".to_string(),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            last_modified: chrono::Utc::now().to_rfc3339(),
-        },
+    if ui.button("Convert Scene...").clicked() {
+        if let Some(from) = pick_scene_to_open() {
+            if let Some(to) = pick_scene_save_path() {
+                scene_manager.pending_operations.push(SceneOperation::Convert { from, to });
+            }
+        }
+        ui.close_menu();
+    }
+    
+    ui.add_enabled_ui(!scene_manager.recent_scenes.is_empty(), |ui| {
+        ui.menu_button("Recent Scenes", |ui| {
+            let mut selected = None;
+            for path in &scene_manager.recent_scenes {
+                if ui.button(path).clicked() {
+                    selected = Some(path.clone());
+                }
+            }
+            
+            if let Some(path) = selected {
+                scene_manager.pending_operations.push(SceneOperation::Load(path));
+                ui.close_menu();
+            }
+        });
+    });
+}
+
+/// Ask what to do with unsaved changes before a queued operation replaces the scene
+pub fn render_unsaved_changes_dialog(
+    ctx: &egui::Context,
+    scene_manager: &mut SceneManager,
+    project_manager: &mut ProjectManager,
+) {
+    let Some(operation) = scene_manager.awaiting_confirmation.clone() else {
+        return;
     };
     
-    if let Ok(scene_data) = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default()) {
-        editor_scene_state.saved_scene_data = Some(scene_data);
-        info!("Scene state saved for play mode");
-    } else {
-        warn!("Failed to save scene state");
+    egui::Window::new("Unsaved Changes")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("The current scene has unsaved changes.");
+            ui.label("Do you want to save them first?");
+            ui.separator();
+            
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    // The operation only runs if the save succeeds and clears the flag
+                    let path = scene_manager.save_path.clone();
+                    scene_manager.pending_operations.insert(0, operation.clone());
+                    scene_manager.pending_operations.insert(0, SceneOperation::Save(path));
+                    scene_manager.awaiting_confirmation = None;
+                }
+                
+                if ui.button("Discard").clicked() {
+                    project_manager.unsaved_changes = false;
+                    scene_manager.pending_operations.insert(0, operation.clone());
+                    scene_manager.awaiting_confirmation = None;
+                }
+                
+                if ui.button("Cancel").clicked() {
+                    scene_manager.pending_operations.clear();
+                    scene_manager.awaiting_confirmation = None;
+                }
+            });
+        });
+}
+
+/// Show a save dialog for scene files
+pub fn pick_scene_save_path() -> Option<String> {
+    use rfd::FileDialog;
+    
+    // The first filter is the default; the chosen extension picks the file format
+    let mut dialog = FileDialog::new();
+    for format in SceneFileFormat::ALL {
+        dialog = dialog.add_filter(format.label(), &[format.extension()]);
     }
-}
\ No newline at end of file
+    
+    let file = dialog
+        .set_directory("scenes")
+        .set_file_name("scene.ron")
+        .save_file();
+    
+    file.map(|path| path.to_string_lossy().to_string())
+}
+
+/// Show an open dialog for scene files
+pub fn pick_scene_to_open() -> Option<String> {
+    use rfd::FileDialog;
+    
+    let extensions = SceneFileFormat::ALL.map(SceneFileFormat::extension);
+    let file = FileDialog::new()
+        .add_filter("Scene Files", &extensions)
+        .set_directory("scenes")
+        .pick_file();
+    
+    file.map(|path| path.to_string_lossy().to_string())
+}
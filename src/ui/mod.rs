@@ -1,6 +1,7 @@
 //! User interface modules
 
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use bevy_egui::{EguiContexts, egui};
 use egui_dock::{DockArea, TabViewer};
 
@@ -9,9 +10,16 @@ pub mod inspector;
 pub mod hierarchy;
 pub mod menus;
 pub mod asset_browser;
+pub mod reflect_editor;
+pub mod clipboard;
+pub mod align;
+pub mod animation;
+pub mod timeline;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::history::{EditorHistory, RecordSpawn};
+use crate::scene::spawn_game_camera;
 
 /// Menu UI system
 pub fn menu_ui(
@@ -22,8 +30,8 @@ pub fn menu_ui(
     mut grid_settings: ResMut<GridSettings>,
     mut game_state: ResMut<GameState>,
     shooting_stats: Res<ShootingStats>,
-    mut editor_scene_state: ResMut<EditorSceneState>,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    mut commands: Commands,
+    mut history: ResMut<EditorHistory>,
 ) {
     let ctx = contexts.ctx_mut();
     menus::render_menu_bar(
@@ -34,8 +42,8 @@ pub fn menu_ui(
         &mut grid_settings,
         &mut game_state,
         &shooting_stats,
-        &mut editor_scene_state,
-        &entity_query,
+        &mut commands,
+        &mut history,
     );
 }
 
@@ -44,7 +52,9 @@ pub fn inspector_ui(
     mut contexts: EguiContexts,
     mut editor_state: ResMut<EditorState>,
     selected_entity: Res<SelectedEntity>,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    mut commands: Commands,
+    entity_query: EditorEntityQuery,
+    mut inspector_params: inspector::InspectorParams,
 ) {
     // Only render if in separate windows mode to avoid conflicts with unified panel
     if matches!(editor_state.window_layout_mode, WindowLayoutMode::SeparateWindows) && editor_state.show_inspector {
@@ -54,7 +64,7 @@ pub fn inspector_ui(
             .default_width(300.0)
             .resizable(true)
             .show(ctx, |ui| {
-                inspector::render_inspector_content(ui, &selected_entity, &entity_query);
+                inspector::render_inspector_content(ui, &selected_entity, &entity_query, &mut inspector_params, &mut commands);
             });
     }
 }
@@ -65,7 +75,8 @@ pub fn hierarchy_ui(
     mut editor_state: ResMut<EditorState>,
     mut selected_entity: ResMut<SelectedEntity>,
     mut commands: Commands,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
+    entity_query: EditorEntityQuery,
+    mut hierarchy_params: hierarchy::HierarchyParams,
     mut scene_manager: ResMut<SceneManager>,
 ) {
     // Only render if in separate windows mode to avoid conflicts with unified panel
@@ -78,7 +89,7 @@ pub fn hierarchy_ui(
             .resizable(true)
             .show(ctx, |ui| {
                 ui.heading("Scene Entities");
-                hierarchy::render_hierarchy_content(ui, &entity_query, &mut selected_entity, &mut commands, &editor_state, &mut scene_manager);
+                hierarchy::render_hierarchy_content(ui, &entity_query, &mut hierarchy_params, &mut selected_entity, &mut commands, &editor_state, &mut scene_manager);
             });
         editor_state.show_hierarchy = show_hierarchy;
     }
@@ -89,20 +100,27 @@ pub fn dockable_ui_system(
     mut contexts: EguiContexts,
     mut dock_tree: ResMut<DockTree>,
     mut project_manager: ResMut<ProjectManager>,
-    mut scene_manager: ResMut<SceneManager>,
-    mut editor_state: ResMut<EditorState>,
-    mut grid_settings: ResMut<GridSettings>,
-    mut background_settings: ResMut<BackgroundSettings>,
-    mut game_state: ResMut<GameState>,
     shooting_stats: Res<ShootingStats>,
-    mut editor_scene_state: ResMut<EditorSceneState>,
-    mut commands: Commands,
-    mut selected_entity: ResMut<SelectedEntity>,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    asset_registry: Res<AssetRegistry>,
-    mut asset_importer: ResMut<AssetImporter>,
-    mut asset_browser_state: ResMut<AssetBrowserState>,
+    mut history: ResMut<EditorHistory>,
+    mut panels: EditorPanelParams,
 ) {
+    // Textures egui draws the viewport images and the selected asset with
+    let scene_texture = contexts.add_image(panels.viewports.scene.image.clone_weak());
+    let game_texture = contexts.add_image(panels.viewports.game.image.clone_weak());
+    let asset_texture = panels
+        .assets
+        .asset_browser_state
+        .selected_asset
+        .as_ref()
+        .and_then(|path| panels.assets.asset_registry.get_texture(path))
+        .map(|handle| contexts.add_image(handle.clone_weak()));
+    let animation_texture = panels
+        .selected_entity
+        .entity
+        .and_then(|entity| panels.animation.sprite_query.get(entity).ok())
+        .and_then(|(_, _, handle)| handle)
+        .map(|handle| contexts.add_image(handle.clone_weak()));
+    
     let ctx = contexts.ctx_mut();
 
     // Top menu bar with enhanced layout controls
@@ -112,13 +130,13 @@ pub fn dockable_ui_system(
             menus::render_menu_bar(
                 ctx,
                 &mut project_manager,
-                &mut scene_manager,
-                &mut editor_state,
-                &mut grid_settings,
-                &mut game_state,
+                &mut panels.scene_manager,
+                &mut panels.editor_state,
+                &mut panels.grid_settings,
+                &mut panels.game_state,
                 &shooting_stats,
-                &mut editor_scene_state,
-                &entity_query,
+                &mut panels.commands,
+                &mut history,
             );
             
             ui.separator();
@@ -154,57 +172,158 @@ pub fn dockable_ui_system(
                     ui.close_menu();
                 }
             });
+            
+            ui.separator();
+            
+            // Transform gizmo tools, also switched with W/E/R
+            let tool = &mut panels.editor_state.transform_tool;
+            ui.selectable_value(tool, TransformTool::Translate, "Move (W)");
+            ui.selectable_value(tool, TransformTool::Rotate, "Rotate (E)");
+            ui.selectable_value(tool, TransformTool::Scale, "Scale (R)");
+            
+            ui.separator();
+            
+            ui.toggle_value(&mut panels.grid_settings.snap_enabled, "Snap")
+                .on_hover_text("Snap moved entities to the grid");
+            ui.toggle_value(&mut panels.grid_settings.rotation_snap_enabled, "Snap Rotation")
+                .on_hover_text("Rotate in steps set in Grid Settings");
         });
     });
 
-    // Create a simple TabViewer that uses direct function calls
-    let mut tab_viewer = DirectTabViewer;
+    menus::render_unsaved_changes_dialog(ctx, &mut panels.scene_manager, &mut project_manager);
+
+    // Set again below for the viewport tabs visible this frame
+    panels.viewports.scene.rect = None;
+    panels.viewports.game.rect = None;
+    
+    // Tab viewer carrying the live world borrows for every docked panel
+    let mut tab_viewer = EditorTabViewer {
+        panels: &mut panels,
+        scene_texture,
+        game_texture,
+        asset_texture,
+        animation_texture,
+    };
     
     // Main dockable area
     DockArea::new(&mut dock_tree.state)
         .show(ctx, &mut tab_viewer);
 }
 
-// Simple TabViewer that doesn't store any references
-struct DirectTabViewer;
+/// Scene entities as the inspector and hierarchy list them
+pub type EditorEntityQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, Option<&'static Player>, Option<&'static Enemy>, Option<&'static Health>, Option<&'static Collision>),
+    (Without<Camera>, Without<GridLine>, Without<BackgroundImage>),
+>;
+
+/// Asset resources shared by the asset browser and asset manager panels
+#[derive(SystemParam)]
+pub struct AssetPanelParams<'w> {
+    pub asset_registry: Res<'w, AssetRegistry>,
+    pub asset_importer: ResMut<'w, AssetImporter>,
+    pub asset_browser_state: ResMut<'w, AssetBrowserState>,
+    pub atlas_library: ResMut<'w, AtlasLibrary>,
+    pub images: Res<'w, Assets<Image>>,
+}
+
+/// World access shared by the docked editor panels
+#[derive(SystemParam)]
+pub struct EditorPanelParams<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub selected_entity: ResMut<'w, SelectedEntity>,
+    pub entity_query: EditorEntityQuery<'w, 's>,
+    pub inspector: inspector::InspectorParams<'w, 's>,
+    pub hierarchy: hierarchy::HierarchyParams<'w, 's>,
+    pub assets: AssetPanelParams<'w>,
+    pub prefab_library: Res<'w, PrefabLibrary>,
+    pub grid_settings: ResMut<'w, GridSettings>,
+    pub background_settings: ResMut<'w, BackgroundSettings>,
+    pub scene_manager: ResMut<'w, SceneManager>,
+    pub editor_state: ResMut<'w, EditorState>,
+    pub game_state: ResMut<'w, GameState>,
+    pub audio_settings: ResMut<'w, AudioSettings>,
+    pub console_log: ResMut<'w, ConsoleLog>,
+    pub viewports: ResMut<'w, EditorViewports>,
+    pub game_camera_query: Query<'w, 's, (), With<GameCamera>>,
+    pub animation: animation::AnimationPanelParams<'w, 's>,
+    pub timeline: timeline::TimelinePanelParams<'w, 's>,
+}
+
+/// TabViewer that renders each dock tab against the live ECS data
+struct EditorTabViewer<'a, 'w, 's> {
+    panels: &'a mut EditorPanelParams<'w, 's>,
+    scene_texture: egui::TextureId,
+    game_texture: egui::TextureId,
+    asset_texture: Option<egui::TextureId>,
+    animation_texture: Option<egui::TextureId>,
+}
 
-impl TabViewer for DirectTabViewer {
+impl TabViewer for EditorTabViewer<'_, '_, '_> {
     type Tab = EditorTab;
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        let panels = &mut *self.panels;
         match tab {
             EditorTab::Viewport => {
-                render_viewport_tab(ui);
+                render_viewport_tab(ui, panels.viewports.get_mut(ViewportKind::Scene), self.scene_texture);
+            }
+            EditorTab::GameView => {
+                let rect = render_viewport_tab(ui, panels.viewports.get_mut(ViewportKind::Game), self.game_texture);
+                if panels.game_camera_query.is_empty() {
+                    render_missing_game_camera(ui, rect, &mut panels.scene_manager, &mut panels.commands);
+                }
             }
             EditorTab::Inspector => {
-                render_inspector_tab(ui);
+                inspector::render_inspector_content(ui, &panels.selected_entity, &panels.entity_query, &mut panels.inspector, &mut panels.commands);
             }
             EditorTab::Hierarchy => {
-                render_hierarchy_tab(ui);
+                hierarchy::render_hierarchy_content(ui, &panels.entity_query, &mut panels.hierarchy, &mut panels.selected_entity, &mut panels.commands, &panels.editor_state, &mut panels.scene_manager);
             }
             EditorTab::AssetBrowser => {
-                render_asset_browser_tab(ui);
+                let assets = &mut panels.assets;
+                asset_browser::render_asset_browser_content(ui, &assets.asset_registry, &mut assets.asset_importer, &mut assets.asset_browser_state, &mut assets.atlas_library, &assets.images, self.asset_texture);
             }
             EditorTab::Console => {
-                render_console_tab(ui);
+                render_console_tab(ui, &mut panels.console_log);
             }
             EditorTab::SceneSettings => {
-                render_scene_settings_tab(ui);
+                editor::render_scene_manager_content(ui, &mut panels.scene_manager);
             }
             EditorTab::GameControls => {
-                render_game_controls_tab(ui);
+                editor::render_game_controls_content(ui, &mut panels.game_state);
+                ui.separator();
+                // Playing sounds pick up volume changes, so only flag real ones
+                let mut audio_settings = panels.audio_settings.clone();
+                editor::render_audio_mixer_content(ui, &mut audio_settings);
+                panels.audio_settings.set_if_neq(audio_settings);
             }
             EditorTab::EntitySpawner => {
-                render_entity_spawner_tab(ui);
+                editor::render_entity_spawner_content(ui, &panels.editor_state, &mut panels.scene_manager, &panels.prefab_library, &mut panels.commands);
             }
             EditorTab::AssetManager => {
-                render_asset_manager_tab(ui);
+                editor::render_asset_manager_content(ui, &panels.assets.asset_registry, &mut panels.assets.asset_importer);
             }
             EditorTab::GridSettings => {
-                render_grid_settings_tab(ui);
+                editor::render_grid_settings_content(ui, &mut panels.grid_settings);
             }
             EditorTab::BackgroundSettings => {
-                render_background_settings_tab(ui);
+                editor::render_background_settings_content(ui, &mut panels.background_settings);
+            }
+            EditorTab::AnimationPreview => {
+                animation::render_animation_preview(
+                    ui,
+                    panels.selected_entity.entity,
+                    &mut panels.animation,
+                    &panels.assets.atlas_library,
+                    &panels.assets.images,
+                    self.animation_texture,
+                    &mut panels.commands,
+                );
+            }
+            EditorTab::Timeline => {
+                timeline::render_timeline(ui, panels.selected_entity.entity, &mut panels.timeline, &mut panels.commands);
             }
         }
     }
@@ -212,6 +331,7 @@ impl TabViewer for DirectTabViewer {
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
         match tab {
             EditorTab::Viewport => "🎮 Viewport".into(),
+            EditorTab::GameView => "🕹 Game View".into(),
             EditorTab::Inspector => "🔍 Inspector".into(),
             EditorTab::Hierarchy => "🌳 Hierarchy".into(),
             EditorTab::AssetBrowser => "📁 Asset Browser".into(),
@@ -222,6 +342,8 @@ impl TabViewer for DirectTabViewer {
             EditorTab::AssetManager => "📦 Asset Manager".into(),
             EditorTab::GridSettings => "⚏ Grid Settings".into(),
             EditorTab::BackgroundSettings => "🖼️ Background Settings".into(),
+            EditorTab::AnimationPreview => "🎞 Animation".into(),
+            EditorTab::Timeline => "⏱ Timeline".into(),
         }
     }
     
@@ -241,8 +363,12 @@ impl TabViewer for DirectTabViewer {
         
         match tab {
             EditorTab::Viewport => {
-                ui.label("Main game viewport");
-                ui.label("Shows the rendered game world");
+                ui.label("Scene viewport");
+                ui.label("Select and arrange entities");
+            }
+            EditorTab::GameView => {
+                ui.label("Game view");
+                ui.label("Shows what the player sees");
             }
             EditorTab::Hierarchy => {
                 ui.label("Scene entity hierarchy");
@@ -268,46 +394,64 @@ impl TabViewer for DirectTabViewer {
     }
 }
 
-fn render_viewport_tab(ui: &mut egui::Ui) {
-    ui.heading("🎮 Game Viewport");
-    ui.separator();
-    
-    // Main viewport content area - this is where the Bevy game world renders
+/// Show a viewport camera's image filling the tab
+///
+/// The tab's area is recorded so the image can be resized to match and the cursor
+/// mapped into the world through it. Returns the area the image fills.
+fn render_viewport_tab(ui: &mut egui::Ui, viewport: &mut ViewportTarget, texture: egui::TextureId) -> egui::Rect {
     let available_rect = ui.available_rect_before_wrap();
+    ui.allocate_rect(available_rect, egui::Sense::hover());
+    
+    viewport.rect = Some(Rect::new(
+        available_rect.min.x,
+        available_rect.min.y,
+        available_rect.max.x,
+        available_rect.max.y,
+    ));
     
-    // Draw a background to show the viewport area
-    ui.painter().rect_filled(
+    ui.painter().image(
+        texture,
         available_rect,
-        egui::Rounding::same(2.0),
-        egui::Color32::from_gray(30),
+        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+        egui::Color32::WHITE,
     );
-    
-    // Add viewport instructions
-    ui.allocate_ui_at_rect(available_rect, |ui| {
-        ui.centered_and_justified(|ui| {
-            ui.vertical_centered(|ui| {
-                ui.label("🎮 Game Viewport");
-                ui.small("The Bevy game world renders here");
-                ui.separator();
-                ui.small("Controls:");
-                ui.small("• Mouse: Select/Drag entities (Editor mode)");
-                ui.small("• WASD: Move player (Play mode)");
-                ui.small("• Space: Shoot (Play mode)");
-                ui.small("• Scroll: Zoom camera");
-            });
+    available_rect
+}
+
+/// Offer to add a game camera when the scene has none for the game view to look through
+fn render_missing_game_camera(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    scene_manager: &mut SceneManager,
+    commands: &mut Commands,
+) {
+    ui.allocate_ui_at_rect(rect, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(rect.height() * 0.4);
+            ui.label("This scene has no game camera");
+            if ui.button("📷 Add Game Camera").clicked() {
+                let id = scene_manager.allocate_id();
+                let entity = spawn_game_camera(commands, id, Vec2::ZERO, None);
+                commands.add(RecordSpawn { entity });
+            }
         });
     });
 }
 
-fn render_console_tab(ui: &mut egui::Ui) {
+fn render_console_tab(ui: &mut egui::Ui, console_log: &mut ConsoleLog) {
     ui.horizontal(|ui| {
         ui.heading("🖥️ Console");
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             if ui.button("Clear").clicked() {
-                // TODO: Clear console
+                console_log.entries.clear();
             }
-            if ui.button("Export").clicked() {
-                // TODO: Export console log
+            if ui.add_enabled(!console_log.entries.is_empty(), egui::Button::new("Export")).clicked() {
+                if let Some(path) = pick_console_export_path() {
+                    match std::fs::write(&path, console_log.to_text()) {
+                        Ok(()) => info!("Console log exported to {}", path),
+                        Err(e) => error!("Failed to export console log to {}: {}", path, e),
+                    }
+                }
             }
         });
     });
@@ -319,325 +463,67 @@ fn render_console_tab(ui: &mut egui::Ui) {
         .max_height(300.0)
         .show(ui, |ui| {
             ui.vertical(|ui| {
-                // Sample console entries with timestamps and different log levels
-                ui.horizontal(|ui| {
-                    ui.small("12:34:56");
-                    ui.colored_label(egui::Color32::GREEN, "[INFO]");
-                    ui.label("🚀 GameMaker Rust v0.3.0 initialized");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:34:56");
-                    ui.colored_label(egui::Color32::BLUE, "[INFO]");
-                    ui.label("🎨 Dockable UI system loaded successfully");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:34:57");
-                    ui.colored_label(egui::Color32::GREEN, "[INFO]");
-                    ui.label("📦 Assets loaded from assets/");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:34:57");
-                    ui.colored_label(egui::Color32::YELLOW, "[DEBUG]");
-                    ui.label("🔧 Entity Component System active");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:34:58");
-                    ui.colored_label(egui::Color32::GREEN, "[INFO]");
-                    ui.label("🎬 Scene 'default_scene.ron' loaded");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:34:59");
-                    ui.colored_label(egui::Color32::LIGHT_BLUE, "[TRACE]");
-                    ui.label("🎮 Entering editor mode");
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.small("12:35:00");
-                    ui.colored_label(egui::Color32::GRAY, "[TRACE]");
-                    ui.label("📊 Rendering at 60 FPS");
-                });
+                if console_log.entries.is_empty() {
+                    ui.weak("No log output");
+                }
+                for entry in &console_log.entries {
+                    ui.horizontal(|ui| {
+                        ui.small(&entry.time);
+                        ui.colored_label(log_level_color(entry.level), format!("[{}]", entry.level));
+                        ui.label(&entry.message).on_hover_text(&entry.target);
+                    });
+                }
             });
         });
 }
 
-fn render_inspector_tab(ui: &mut egui::Ui) {
-    ui.heading("🔍 Inspector");
-    ui.separator();
-    
-    ui.collapsing("Transform", |ui| {
-        ui.horizontal(|ui| {
-            ui.label("Position:");
-            ui.add(egui::DragValue::new(&mut 0.0f32).prefix("X: "));
-            ui.add(egui::DragValue::new(&mut 0.0f32).prefix("Y: "));
-        });
-        ui.horizontal(|ui| {
-            ui.label("Rotation:");
-            ui.add(egui::DragValue::new(&mut 0.0f32).suffix("°"));
-        });
-        ui.horizontal(|ui| {
-            ui.label("Scale:");
-            ui.add(egui::DragValue::new(&mut 1.0f32).prefix("X: "));
-            ui.add(egui::DragValue::new(&mut 1.0f32).prefix("Y: "));
-        });
-    });
-    
-    ui.collapsing("Components", |ui| {
-        ui.label("No entity selected");
-    });
-}
-
-fn render_hierarchy_tab(ui: &mut egui::Ui) {
-    ui.heading("🌳 Hierarchy");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("➕ Add Entity").clicked() {
-            // Add entity logic
-        }
-        if ui.button("🗑️ Delete").clicked() {
-            // Delete entity logic
-        }
-        if ui.button("🔄 Refresh").clicked() {
-            // Refresh hierarchy logic
-        }
-    });
-    
-    ui.separator();
-    
-    ui.collapsing("Scene Objects", |ui| {
-        ui.label("📦 Entity 1");
-        ui.label("📦 Entity 2");
-        ui.label("📦 Entity 3");
-    });
-}
-
-fn render_asset_browser_tab(ui: &mut egui::Ui) {
-    ui.heading("📁 Asset Browser");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("📂 Import").clicked() {
-            // Import asset logic
-        }
-        if ui.button("🗂️ New Folder").clicked() {
-            // Create folder logic
-        }
-        if ui.button("🔄 Refresh").clicked() {
-            // Refresh assets logic
-        }
-    });
-    
-    ui.separator();
-    
-    ui.collapsing("📁 Textures", |ui| {
-        ui.label("🖼️ sprite1.png");
-        ui.label("🖼️ sprite2.png");
-    });
-    
-    ui.collapsing("📁 Audio", |ui| {
-        ui.label("🎵 sound1.wav");
-        ui.label("🎵 sound2.ogg");
-    });
-}
-
-fn render_game_controls_tab(ui: &mut egui::Ui) {
-    ui.heading("🎮 Game Controls");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("▶️ Play").clicked() {
-            // Play game logic
-        }
-        if ui.button("⏸️ Pause").clicked() {
-            // Pause game logic
-        }
-        if ui.button("⏹️ Stop").clicked() {
-            // Stop game logic
-        }
-    });
-    
-    ui.separator();
-    
-    ui.group(|ui| {
-        ui.label("Game State: Editor Mode");
-        ui.label("FPS: 60");
-        ui.label("Entities: 0");
-    });
-}
-
-fn render_entity_spawner_tab(ui: &mut egui::Ui) {
-    ui.heading("➕ Entity Spawner");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("👤 Player").clicked() {
-            // Spawn player logic
-        }
-        if ui.button("👹 Enemy").clicked() {
-            // Spawn enemy logic
-        }
-        if ui.button("🏠 Object").clicked() {
-            // Spawn object logic
-        }
-    });
-    
-    ui.separator();
-    
-    ui.collapsing("Spawn Settings", |ui| {
-        ui.horizontal(|ui| {
-            ui.label("Position:");
-            ui.add(egui::DragValue::new(&mut 0.0f32).prefix("X: "));
-            ui.add(egui::DragValue::new(&mut 0.0f32).prefix("Y: "));
-        });
-    });
-}
-
-fn render_asset_manager_tab(ui: &mut egui::Ui) {
-    ui.heading("📦 Asset Manager");
-    ui.separator();
-    
-    ui.group(|ui| {
-        ui.label("Memory Usage: 45.2 MB");
-        ui.label("Loaded Assets: 23");
-        ui.label("Cache Size: 12.1 MB");
-    });
-    
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("🔄 Reload All").clicked() {
-            // Reload assets logic
-        }
-        if ui.button("🗑️ Clear Cache").clicked() {
-            // Clear cache logic
-        }
-    });
-}
-
-fn render_grid_settings_tab(ui: &mut egui::Ui) {
-    ui.heading("⚏ Grid Settings");
-    ui.separator();
-    
-    ui.checkbox(&mut true, "Show Grid");
-    ui.checkbox(&mut false, "Snap to Grid");
-    
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        ui.label("Grid Size:");
-        ui.add(egui::DragValue::new(&mut 32.0f32).suffix("px"));
-    });
-    
-    ui.horizontal(|ui| {
-        ui.label("Opacity:");
-        ui.add(egui::Slider::new(&mut 0.5f32, 0.0..=1.0));
-    });
+/// Color the console shows a log level in
+fn log_level_color(level: bevy::log::Level) -> egui::Color32 {
+    match level {
+        bevy::log::Level::ERROR => egui::Color32::RED,
+        bevy::log::Level::WARN => egui::Color32::YELLOW,
+        bevy::log::Level::INFO => egui::Color32::GREEN,
+        bevy::log::Level::DEBUG => egui::Color32::LIGHT_BLUE,
+        _ => egui::Color32::GRAY,
+    }
 }
 
-fn render_background_settings_tab(ui: &mut egui::Ui) {
-    ui.heading("🖼️ Background Settings");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("📂 Load Image").clicked() {
-            // Load background image logic
-        }
-        if ui.button("🗑️ Remove").clicked() {
-            // Remove background logic
-        }
-    });
-    
-    ui.separator();
+/// Show a save dialog for exporting the console log
+fn pick_console_export_path() -> Option<String> {
+    use rfd::FileDialog;
     
-    ui.collapsing("Position", |ui| {
-        ui.horizontal(|ui| {
-            ui.label("X:");
-            ui.add(egui::DragValue::new(&mut 0.0f32));
-        });
-        ui.horizontal(|ui| {
-            ui.label("Y:");
-            ui.add(egui::DragValue::new(&mut 0.0f32));
-        });
-    });
+    let file = FileDialog::new()
+        .add_filter("Log Files", &["log", "txt"])
+        .set_file_name("console.log")
+        .save_file();
     
-    ui.collapsing("Scale", |ui| {
-        ui.horizontal(|ui| {
-            ui.label("Scale:");
-            ui.add(egui::Slider::new(&mut 1.0f32, 0.1..=5.0));
-        });
-    });
-}
-
-fn render_scene_settings_tab(ui: &mut egui::Ui) {
-    ui.heading("🎬 Scene Settings");
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        ui.label("📂 Save Path:");
-        ui.label("scenes/default_scene.ron");
-    });
-    
-    ui.separator();
-    
-    ui.horizontal(|ui| {
-        if ui.button("💾 Save Scene").clicked() {
-            info!("Saving scene to: scenes/default_scene.ron");
-        }
-        
-        if ui.button("📁 Load Scene").clicked() {
-            info!("Loading scene from: scenes/default_scene.ron");
-        }
-        
-        if ui.button("🆕 New Scene").clicked() {
-            info!("Creating new scene");
-        }
-    });
-    
-    ui.separator();
-    
-    ui.label("Scene Properties:");
-    ui.small("• Entity Count: Active entities in scene");
-    ui.small("• Last Modified: When scene was last saved");
-    ui.small("• File Size: Current scene file size");
+    file.map(|path| path.to_string_lossy().to_string())
 }
 
 /// Editor panels UI system
 pub fn editor_panels_ui(
     mut contexts: EguiContexts,
-    mut editor_state: ResMut<EditorState>,
-    mut grid_settings: ResMut<GridSettings>,
-    mut background_settings: ResMut<BackgroundSettings>,
-    mut scene_manager: ResMut<SceneManager>,
     shooting_stats: Res<ShootingStats>,
-    mut game_state: ResMut<GameState>,
     mut editor_scene_state: ResMut<EditorSceneState>,
-    mut commands: Commands,
-    mut selected_entity: ResMut<SelectedEntity>,
-    entity_query: Query<(Entity, &Transform, Option<&Player>, Option<&Enemy>, Option<&Health>, Option<&Collision>), (Without<Camera>, Without<GridLine>, Without<BackgroundImage>)>,
-    asset_registry: Res<AssetRegistry>,
-    mut asset_importer: ResMut<AssetImporter>,
-    mut asset_browser_state: ResMut<AssetBrowserState>,
+    mut panels: EditorPanelParams,
 ) {
     let ctx = contexts.ctx_mut();
+    let panels = &mut panels;
     editor::render_editor_panels(
         ctx,
-        &mut editor_state,
-        &mut grid_settings,
-        &mut background_settings,
-        &mut scene_manager,
+        &mut panels.editor_state,
+        &mut panels.grid_settings,
+        &mut panels.background_settings,
+        &mut panels.scene_manager,
         &shooting_stats,
-        &mut game_state,
+        &mut panels.game_state,
         &mut editor_scene_state,
-        &mut commands,
-        &mut selected_entity,
-        &entity_query,
-        &asset_registry,
-        &mut asset_importer,
-        &mut asset_browser_state,
+        &mut panels.commands,
+        &mut panels.selected_entity,
+        &panels.entity_query,
+        &mut panels.inspector,
+        &mut panels.hierarchy,
+        &mut panels.assets,
+        &panels.prefab_library,
     );
 }
\ No newline at end of file
@@ -0,0 +1,299 @@
+//! Reflection-driven value editors for the inspector
+
+use bevy::prelude::*;
+use bevy::reflect::std_traits::ReflectDefault;
+use bevy::reflect::{
+    DynamicEnum, DynamicStruct, DynamicTuple, DynamicVariant, Enum, ReflectMut, ReflectRef,
+    Struct, TypeInfo, TypeRegistry, VariantInfo,
+};
+use bevy_egui::egui;
+
+/// Render an editor for any reflected value, returning true if it was modified
+pub fn reflect_value_ui(ui: &mut egui::Ui, value: &mut dyn Reflect, registry: &TypeRegistry) -> bool {
+    if let Some(changed) = primitive_value_ui(ui, value) {
+        return changed;
+    }
+
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => struct_ui(ui, value, registry),
+        ReflectMut::TupleStruct(value) => {
+            let mut changed = false;
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    changed |= field_ui(ui, &index.to_string(), index, field, registry);
+                }
+            }
+            changed
+        }
+        ReflectMut::Tuple(value) => {
+            let mut changed = false;
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    changed |= field_ui(ui, &index.to_string(), index, field, registry);
+                }
+            }
+            changed
+        }
+        ReflectMut::List(value) => {
+            let mut changed = false;
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    changed |= field_ui(ui, &format!("[{}]", index), index, item, registry);
+                }
+            }
+            changed
+        }
+        ReflectMut::Array(value) => {
+            let mut changed = false;
+            for index in 0..value.len() {
+                if let Some(item) = value.get_mut(index) {
+                    changed |= field_ui(ui, &format!("[{}]", index), index, item, registry);
+                }
+            }
+            changed
+        }
+        ReflectMut::Enum(value) => enum_ui(ui, value, registry),
+        ReflectMut::Map(value) => {
+            ui.weak(format!("Map ({} entries)", value.len()));
+            false
+        }
+        ReflectMut::Value(value) => {
+            ui.label(format!("{:?}", value));
+            false
+        }
+    }
+}
+
+/// Copy onto `target` the fields that differ between `before` and `after`, leaving its
+/// other fields as they are; values without named or numbered fields are copied whole
+///
+/// Used to apply one edit to several entities without overwriting the fields that
+/// were not touched.
+pub fn copy_changed_fields(before: &dyn Reflect, after: &dyn Reflect, target: &mut dyn Reflect) {
+    let merged = match (before.reflect_ref(), after.reflect_ref(), target.reflect_mut()) {
+        (ReflectRef::Struct(before), ReflectRef::Struct(after), ReflectMut::Struct(target)) => {
+            for index in 0..after.field_len() {
+                if !field_changed(before.field_at(index), after.field_at(index)) {
+                    continue;
+                }
+                if let (Some(name), Some(value)) = (after.name_at(index), after.field_at(index)) {
+                    if let Some(field) = target.field_mut(name) {
+                        field.apply(value);
+                    }
+                }
+            }
+            true
+        }
+        (ReflectRef::TupleStruct(before), ReflectRef::TupleStruct(after), ReflectMut::TupleStruct(target)) => {
+            for index in 0..after.field_len() {
+                if !field_changed(before.field(index), after.field(index)) {
+                    continue;
+                }
+                if let (Some(field), Some(value)) = (target.field_mut(index), after.field(index)) {
+                    field.apply(value);
+                }
+            }
+            true
+        }
+        _ => false,
+    };
+
+    // Enums and plain values have no fields to merge
+    if !merged {
+        target.apply(after);
+    }
+}
+
+fn field_changed(before: Option<&dyn Reflect>, after: Option<&dyn Reflect>) -> bool {
+    match (before, after) {
+        (Some(before), Some(after)) => !before.reflect_partial_eq(after).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Editors for leaf types that have a natural egui widget
+fn primitive_value_ui(ui: &mut egui::Ui, value: &mut dyn Reflect) -> Option<bool> {
+    macro_rules! drag_numbers {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(number) = value.downcast_mut::<$ty>() {
+                    return Some(ui.add(egui::DragValue::new(number)).changed());
+                }
+            )*
+        };
+    }
+
+    drag_numbers!(f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+    if let Some(flag) = value.downcast_mut::<bool>() {
+        return Some(ui.checkbox(flag, "").changed());
+    }
+
+    if let Some(text) = value.downcast_mut::<String>() {
+        return Some(ui.text_edit_singleline(text).changed());
+    }
+
+    if let Some(vector) = value.downcast_mut::<Vec2>() {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut vector.x).prefix("X: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.y).prefix("Y: ")).changed();
+        });
+        return Some(changed);
+    }
+
+    if let Some(vector) = value.downcast_mut::<Vec3>() {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut vector.x).prefix("X: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.y).prefix("Y: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.z).prefix("Z: ")).changed();
+        });
+        return Some(changed);
+    }
+
+    if let Some(vector) = value.downcast_mut::<Vec4>() {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::DragValue::new(&mut vector.x).prefix("X: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.y).prefix("Y: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.z).prefix("Z: ")).changed();
+            changed |= ui.add(egui::DragValue::new(&mut vector.w).prefix("W: ")).changed();
+        });
+        return Some(changed);
+    }
+
+    if let Some(rotation) = value.downcast_mut::<Quat>() {
+        // 2D rotation is edited as degrees around Z
+        let mut degrees = rotation.to_euler(EulerRot::ZYX).0.to_degrees();
+        if ui.add(egui::DragValue::new(&mut degrees).suffix("°")).changed() {
+            *rotation = Quat::from_rotation_z(degrees.to_radians());
+            return Some(true);
+        }
+        return Some(false);
+    }
+
+    if let Some(color) = value.downcast_mut::<Color>() {
+        let mut rgba = color.as_rgba_f32();
+        if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+            *color = Color::rgba(rgba[0], rgba[1], rgba[2], rgba[3]);
+            return Some(true);
+        }
+        return Some(false);
+    }
+
+    None
+}
+
+/// Whether a value is drawn on the same row as its label
+fn is_inline(value: &dyn Reflect) -> bool {
+    value.is::<Vec2>()
+        || value.is::<Vec3>()
+        || value.is::<Vec4>()
+        || value.is::<Quat>()
+        || value.is::<Color>()
+        || matches!(value.reflect_ref(), ReflectRef::Value(_))
+}
+
+/// Render a labelled field, nesting composite values under their label
+fn field_ui(ui: &mut egui::Ui, label: &str, index: usize, field: &mut dyn Reflect, registry: &TypeRegistry) -> bool {
+    ui.push_id(index, |ui| {
+        if is_inline(field) {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                reflect_value_ui(ui, field, registry)
+            }).inner
+        } else {
+            ui.label(label);
+            ui.indent(label, |ui| reflect_value_ui(ui, field, registry)).inner
+        }
+    }).inner
+}
+
+/// Render every named field of a struct
+fn struct_ui(ui: &mut egui::Ui, value: &mut dyn Struct, registry: &TypeRegistry) -> bool {
+    if value.field_len() == 0 {
+        ui.weak("No editable fields");
+        return false;
+    }
+
+    let mut changed = false;
+    for index in 0..value.field_len() {
+        let name = value.name_at(index).unwrap_or_default().to_string();
+        if let Some(field) = value.field_at_mut(index) {
+            changed |= field_ui(ui, &name, index, field, registry);
+        }
+    }
+    changed
+}
+
+/// Render a variant selector followed by the fields of the active variant
+fn enum_ui(ui: &mut egui::Ui, value: &mut dyn Enum, registry: &TypeRegistry) -> bool {
+    let mut changed = false;
+    let current_variant = value.variant_name().to_string();
+
+    if let Some(TypeInfo::Enum(info)) = value.get_represented_type_info() {
+        let mut new_value = None;
+        egui::ComboBox::from_id_source(ui.next_auto_id())
+            .selected_text(&current_variant)
+            .show_ui(ui, |ui| {
+                for variant in info.iter() {
+                    // Variants whose fields have no registered default cannot be constructed
+                    let default_value = default_variant(variant, registry);
+                    let enabled = default_value.is_some() || variant.name() == current_variant;
+                    let response = ui.add_enabled(
+                        enabled,
+                        egui::SelectableLabel::new(variant.name() == current_variant, variant.name()),
+                    );
+                    if response.clicked() && variant.name() != current_variant {
+                        new_value = default_value;
+                    }
+                }
+            });
+
+        if let Some(new_value) = new_value {
+            value.apply(&new_value);
+            changed = true;
+        }
+    } else {
+        ui.label(&current_variant);
+    }
+
+    for index in 0..value.field_len() {
+        let name = value.name_at(index).map(str::to_string).unwrap_or_else(|| index.to_string());
+        if let Some(field) = value.field_at_mut(index) {
+            changed |= field_ui(ui, &name, index, field, registry);
+        }
+    }
+
+    changed
+}
+
+/// Build a variant value with default fields, if every field type registers `Default`
+fn default_variant(variant: &VariantInfo, registry: &TypeRegistry) -> Option<DynamicEnum> {
+    let default_of = |type_id| {
+        registry
+            .get_type_data::<ReflectDefault>(type_id)
+            .map(|reflect_default| reflect_default.default())
+    };
+
+    let dynamic_variant = match variant {
+        VariantInfo::Unit(_) => DynamicVariant::Unit,
+        VariantInfo::Tuple(info) => {
+            let mut fields = DynamicTuple::default();
+            for field in info.iter() {
+                fields.insert_boxed(default_of(field.type_id())?);
+            }
+            DynamicVariant::Tuple(fields)
+        }
+        VariantInfo::Struct(info) => {
+            let mut fields = DynamicStruct::default();
+            for field in info.iter() {
+                fields.insert_boxed(field.name(), default_of(field.type_id())?);
+            }
+            DynamicVariant::Struct(fields)
+        }
+    };
+
+    Some(DynamicEnum::new(variant.name(), dynamic_variant))
+}
@@ -0,0 +1,307 @@
+//! Timeline tab: authors an entity's keyframe tweens and previews them in edit mode
+//!
+//! Scrubbing or playing the preview poses the entity in the scene view; the tween
+//! preview system puts it back when the preview stops. Editing the entity other
+//! than through its timeline ends the preview.
+
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy_egui::egui;
+
+use crate::components::*;
+use crate::resources::*;
+use crate::ui::inspector::ApplyReflectedComponents;
+
+/// Height of each track's row in the keyframe strip
+const ROW_HEIGHT: f32 = 18.0;
+
+/// World access needed by the timeline tab
+#[derive(SystemParam)]
+pub struct TimelinePanelParams<'w, 's> {
+    pub timeline_query: Query<'w, 's, (Option<&'static Timeline>, &'static Transform, Option<&'static Sprite>)>,
+    pub preview: ResMut<'w, TimelinePreview>,
+}
+
+/// Render the timeline editor and preview controls for the selected entity
+pub fn render_timeline(
+    ui: &mut egui::Ui,
+    entity: Option<Entity>,
+    params: &mut TimelinePanelParams,
+    commands: &mut Commands,
+) {
+    let Some((entity, (timeline, transform, sprite))) =
+        entity.and_then(|entity| params.timeline_query.get(entity).ok().map(|components| (entity, components)))
+    else {
+        ui.label("Select an entity to edit its timeline");
+        return;
+    };
+    let Some(timeline) = timeline else {
+        ui.label("The selected entity has no timeline");
+        if ui.button("➕ Add Timeline").clicked() {
+            commands.add(ApplyReflectedComponents {
+                entity,
+                components: vec![Box::new(Timeline::default())],
+            });
+        }
+        return;
+    };
+
+    // Start over when another entity is selected
+    let preview = &mut params.preview;
+    if preview.entity != Some(entity) {
+        preview.entity = Some(entity);
+        preview.state = TimelineState::default();
+        preview.playing = false;
+        preview.active = false;
+    }
+
+    // Values to key come from the entity as it was before the preview posed it
+    let (current_transform, current_color) = match preview.original {
+        Some((original_entity, transform, color)) if original_entity == entity => (transform, color),
+        _ => (*transform, sprite.map(|sprite| sprite.color).unwrap_or(Color::WHITE)),
+    };
+
+    let mut edited = timeline.clone();
+    let mut changed = false;
+
+    egui::Grid::new("timeline_settings").num_columns(2).show(ui, |ui| {
+        ui.label("Name:");
+        changed |= ui.text_edit_singleline(&mut edited.name).changed();
+        ui.end_row();
+
+        ui.label("Repeat:");
+        ui.horizontal(|ui| {
+            for (repeat, label) in [
+                (TimelineRepeat::Once, "Once"),
+                (TimelineRepeat::Loop, "Loop"),
+                (TimelineRepeat::PingPong, "Ping-Pong"),
+            ] {
+                changed |= ui.radio_value(&mut edited.repeat, repeat, label).changed();
+            }
+        });
+        ui.end_row();
+
+        ui.label("Playback:");
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut edited.playing, "Playing")
+                .on_hover_text("Whether the timeline plays in play mode")
+                .changed();
+            changed |= ui.checkbox(&mut edited.relative, "Relative")
+                .on_hover_text("Position and rotation keys are offsets, and scale keys multiples, of where the entity starts")
+                .changed();
+            ui.label("Speed:");
+            changed |= ui.add(egui::DragValue::new(&mut edited.speed).speed(0.01).clamp_range(0.0..=10.0).suffix("x")).changed();
+        });
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    // Preview controls
+    let duration = timeline.duration();
+    ui.horizontal(|ui| {
+        let label = if preview.playing { "⏸ Pause" } else { "▶ Preview" };
+        if ui.button(label).clicked() {
+            if preview.state.finished || preview.state.time >= duration {
+                preview.state = TimelineState::default();
+            }
+            preview.active = true;
+            preview.playing = !preview.playing;
+        }
+        if ui.add_enabled(preview.active, egui::Button::new("⏹ Stop"))
+            .on_hover_text("Stop the preview and put the entity back")
+            .clicked()
+        {
+            preview.state = TimelineState::default();
+            preview.active = false;
+            preview.playing = false;
+        }
+
+        let mut time = preview.state.time;
+        if ui.add(egui::Slider::new(&mut time, 0.0..=duration.max(0.01)).text("Time").suffix("s")).changed() {
+            scrub(preview, time);
+        }
+    });
+
+    if let Some(time) = render_keyframe_strip(ui, timeline, preview.state.time) {
+        scrub(preview, time);
+    }
+
+    ui.separator();
+
+    // Track editors
+    let key_time = preview.state.time;
+    let mut removed_track = None;
+    for (index, track) in edited.tracks.iter_mut().enumerate() {
+        egui::CollapsingHeader::new(track.property.label())
+            .id_source(("timeline_track", index))
+            .default_open(true)
+            .show(ui, |ui| {
+                changed |= edit_track(ui, index, track);
+
+                ui.horizontal(|ui| {
+                    if ui.button("➕ Key Current Value")
+                        .on_hover_text("Key the entity's current value at the preview time")
+                        .clicked()
+                    {
+                        track.keyframes.push(TweenKeyframe {
+                            time: key_time,
+                            value: track.property.read(&current_transform, current_color),
+                            easing: Easing::Linear,
+                        });
+                        track.sort();
+                        changed = true;
+                    }
+                    if ui.button("🗑 Remove Track").clicked() {
+                        removed_track = Some(index);
+                    }
+                });
+            });
+    }
+    if let Some(index) = removed_track {
+        edited.tracks.remove(index);
+        changed = true;
+    }
+
+    ui.menu_button("➕ Add Track", |ui| {
+        for property in TweenProperty::ALL {
+            if ui.add_enabled(timeline.get_track(property).is_none(), egui::Button::new(property.label())).clicked() {
+                // A new track starts from the entity's current value
+                edited.insert_keyframe(property, TweenKeyframe {
+                    time: 0.0,
+                    value: property.read(&current_transform, current_color),
+                    easing: Easing::Linear,
+                });
+                changed = true;
+                ui.close_menu();
+            }
+        }
+    });
+
+    if changed {
+        commands.add(ApplyReflectedComponents {
+            entity,
+            components: vec![Box::new(edited)],
+        });
+    }
+}
+
+/// Pose the preview at a time, pausing it
+fn scrub(preview: &mut TimelinePreview, time: f32) {
+    preview.state.time = time;
+    preview.state.reversing = false;
+    preview.state.finished = false;
+    preview.active = true;
+    preview.playing = false;
+}
+
+/// Draw each track's keyframes along the timeline with the preview time marked,
+/// returning the time clicked or dragged to
+fn render_keyframe_strip(ui: &mut egui::Ui, timeline: &Timeline, time: f32) -> Option<f32> {
+    let rows = timeline.tracks.len().max(1) as f32;
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), rows * ROW_HEIGHT),
+        egui::Sense::click_and_drag(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(40, 40, 40));
+
+    // Keep keyframes clear of the edges of the strip
+    let span = timeline.duration().max(0.01);
+    let inner = rect.shrink2(egui::vec2(6.0, 0.0));
+    let x_of = |time: f32| inner.left() + inner.width() * (time / span).clamp(0.0, 1.0);
+
+    for (row, track) in timeline.tracks.iter().enumerate() {
+        let y = rect.top() + (row as f32 + 0.5) * ROW_HEIGHT;
+        painter.hline(inner.x_range(), y, egui::Stroke::new(1.0, egui::Color32::from_gray(70)));
+        painter.text(
+            egui::pos2(rect.left() + 4.0, y),
+            egui::Align2::LEFT_CENTER,
+            track.property.label(),
+            egui::FontId::proportional(10.0),
+            egui::Color32::from_gray(140),
+        );
+        for keyframe in &track.keyframes {
+            let center = egui::pos2(x_of(keyframe.time), y);
+            let diamond = [
+                center + egui::vec2(0.0, -5.0),
+                center + egui::vec2(5.0, 0.0),
+                center + egui::vec2(0.0, 5.0),
+                center + egui::vec2(-5.0, 0.0),
+            ];
+            painter.add(egui::Shape::convex_polygon(diamond.to_vec(), egui::Color32::from_rgb(255, 200, 80), egui::Stroke::NONE));
+        }
+    }
+
+    painter.vline(x_of(time), rect.y_range(), egui::Stroke::new(1.5, egui::Color32::from_rgb(100, 160, 255)));
+
+    if response.clicked() || response.dragged() {
+        let x = response.interact_pointer_pos()?.x;
+        return Some(((x - inner.left()) / inner.width()).clamp(0.0, 1.0) * span);
+    }
+    None
+}
+
+/// Edit a track's keyframes, returning true if anything changed
+fn edit_track(ui: &mut egui::Ui, track_index: usize, track: &mut TweenTrack) -> bool {
+    let mut changed = false;
+    let mut resort = false;
+    let mut removed = None;
+
+    for (index, keyframe) in track.keyframes.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::DragValue::new(&mut keyframe.time).speed(0.01).clamp_range(0.0..=f32::MAX).suffix("s"));
+            changed |= response.changed();
+            // Reordering rows under the pointer would hand the drag to another key
+            resort |= response.drag_released() || (response.changed() && !response.dragged());
+
+            changed |= edit_keyframe_value(ui, track.property, &mut keyframe.value);
+
+            egui::ComboBox::from_id_source(("timeline_easing", track_index, index))
+                .selected_text(keyframe.easing.label())
+                .show_ui(ui, |ui| {
+                    for easing in Easing::ALL {
+                        changed |= ui.selectable_value(&mut keyframe.easing, easing, easing.label()).changed();
+                    }
+                });
+
+            if ui.small_button("✖").on_hover_text("Remove keyframe").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        track.keyframes.remove(index);
+        changed = true;
+    }
+    if resort {
+        track.sort();
+    }
+    if track.keyframes.is_empty() {
+        ui.weak("No keyframes: the property is left alone");
+    }
+
+    changed
+}
+
+/// Edit a keyframe value in the layout its property uses
+fn edit_keyframe_value(ui: &mut egui::Ui, property: TweenProperty, value: &mut Vec4) -> bool {
+    match property {
+        TweenProperty::Position => {
+            ui.add(egui::DragValue::new(&mut value.x).prefix("X: ")).changed()
+                | ui.add(egui::DragValue::new(&mut value.y).prefix("Y: ")).changed()
+        }
+        TweenProperty::Rotation => ui.add(egui::DragValue::new(&mut value.x).suffix("°")).changed(),
+        TweenProperty::Scale => {
+            ui.add(egui::DragValue::new(&mut value.x).speed(0.01).prefix("X: ")).changed()
+                | ui.add(egui::DragValue::new(&mut value.y).speed(0.01).prefix("Y: ")).changed()
+        }
+        TweenProperty::Tint => {
+            let mut rgba = value.to_array();
+            let changed = ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed();
+            *value = Vec4::from_array(rgba);
+            changed
+        }
+        TweenProperty::Opacity => ui.add(egui::Slider::new(&mut value.x, 0.0..=1.0)).changed(),
+    }
+}